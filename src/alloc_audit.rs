@@ -0,0 +1,107 @@
+// Global allocator wrapper counting allocations per frame, enabled with the
+// `alloc-audit` feature to keep the hot loop honest
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+pub struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static LAST_FRAME_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LAST_FRAME_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Const and without a destructor, so counting into it never allocates
+    static THREAD_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count(bytes: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(bytes, Ordering::Relaxed);
+    let _ = THREAD_ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+// Called at the start of every frame to close out the previous one's counts
+pub fn end_frame() {
+    LAST_FRAME_ALLOCATIONS.store(ALLOCATIONS.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+    LAST_FRAME_BYTES.store(BYTES.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+}
+
+// Allocations and bytes allocated during the last full frame
+pub fn last_frame() -> (usize, usize) {
+    (
+        LAST_FRAME_ALLOCATIONS.load(Ordering::Relaxed),
+        LAST_FRAME_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+// Allocations the calling thread has made so far, which work on other
+// threads can't throw off
+#[cfg(test)]
+pub fn thread_allocations() -> usize {
+    THREAD_ALLOCATIONS.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use crate::timer::Countdown;
+    use crate::{Game, Input};
+
+    #[test]
+    fn busy_frames_stay_within_the_allocation_budget() {
+        // A storm field of hundreds of rocks, the ship spinning, firing and
+        // going up in a full death explosion every half second
+        let settings = Settings {
+            storm: true,
+            log_events: false,
+            deterministic_effects: true,
+            ..Settings::default()
+        };
+        let mut game = Game::with_size(7, settings, 1920.0, 1080.0);
+        game.player.invulnerable = Countdown::new(f32::MAX);
+        let input = Input {
+            rotate_left: true,
+            fire: true,
+            ..Input::default()
+        };
+        let frame = 1.0 / 60.0;
+        let mut worst = 0;
+        for i in 0..600 {
+            if i % 30 == 0 {
+                game.spawn_death_effect();
+            }
+            let before = thread_allocations();
+            game.tick(frame, &input);
+            // Past warmup, once the entity lists have grown to size
+            if i >= 300 {
+                worst = worst.max(thread_allocations() - before);
+            }
+        }
+        assert!(game.asteroids.len() > 100);
+        assert!(worst <= 8, "{} allocations in one frame", worst);
+    }
+}
@@ -0,0 +1,151 @@
+// Files loaded from the assets folder at startup, behind a loading screen so
+// the window comes up straight away. Everything the game can run without is
+// optional: a missing optional file is expected and only logged, while one
+// that's there but won't load, or a missing required one, is listed once on
+// the title screen.
+use crate::music::Layer;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+struct AssetSpec {
+    path: String,
+    // What the game goes without if it's missing, for the startup summary
+    feature: &'static str,
+    required: bool,
+}
+
+fn manifest() -> Vec<AssetSpec> {
+    Layer::ALL
+        .iter()
+        .map(|layer| AssetSpec {
+            path: stem_path(*layer),
+            feature: "music",
+            required: false,
+        })
+        .collect()
+}
+
+pub fn stem_path(layer: Layer) -> String {
+    format!("assets/music/{}.ogg", layer.name())
+}
+
+#[derive(Default)]
+pub struct AssetStore {
+    files: HashMap<String, Vec<u8>>,
+    // One line per feature something went wrong for
+    pub problems: Vec<String>,
+    // Features already in `problems`
+    failed: Vec<&'static str>,
+}
+impl AssetStore {
+    pub async fn load() -> AssetStore {
+        let manifest = manifest();
+        let mut store = AssetStore::default();
+        for (i, spec) in manifest.iter().enumerate() {
+            render_progress(i as f32 / manifest.len() as f32);
+            next_frame().await;
+
+            let exists = std::path::Path::new(&spec.path).exists();
+            let loaded = load_file(&spec.path).await.map_err(|e| e.to_string());
+            store.add(spec, loaded, exists);
+        }
+        store
+    }
+
+    // Keep a file that loaded, or note what the game goes without
+    fn add(&mut self, spec: &AssetSpec, loaded: Result<Vec<u8>, String>, exists: bool) {
+        match loaded {
+            Ok(bytes) => {
+                self.files.insert(spec.path.clone(), bytes);
+            }
+            Err(e) if exists || spec.required => {
+                eprintln!("Failed to load {}: {}", spec.path, e);
+                if !self.failed.contains(&spec.feature) {
+                    self.failed.push(spec.feature);
+                    let reason = if exists { "didn't load" } else { "not found" };
+                    self.problems.push(format!(
+                        "Running without {}: {} {}",
+                        spec.feature, spec.path, reason
+                    ));
+                }
+            }
+            Err(_) => eprintln!("No {}, going without it", spec.path),
+        }
+    }
+
+    pub fn has(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+fn render_progress(progress: f32) {
+    clear_background(BLACK);
+    let width = (screen_width() * 0.4).min(400.0);
+    let (x, y) = ((screen_width() - width) / 2.0, screen_height() / 2.0);
+    draw_rectangle_lines(x, y, width, 12.0, 1.0, GRAY);
+    draw_rectangle(x + 2.0, y + 2.0, (width - 4.0) * progress, 8.0, WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missing() -> Result<Vec<u8>, String> {
+        Err(String::from("not found"))
+    }
+
+    #[test]
+    fn missing_optional_files_are_no_problem() {
+        let mut store = AssetStore::default();
+        for spec in manifest() {
+            store.add(&spec, missing(), false);
+        }
+        assert!(manifest().iter().all(|spec| !store.has(&spec.path)));
+        assert!(store.problems.is_empty());
+    }
+
+    #[test]
+    fn some_files_missing_keeps_the_rest() {
+        let manifest = manifest();
+        let mut store = AssetStore::default();
+        for (i, spec) in manifest.iter().enumerate() {
+            if i % 2 == 0 {
+                store.add(spec, Ok(vec![1, 2, 3]), true);
+            } else {
+                store.add(spec, missing(), false);
+            }
+        }
+        for (i, spec) in manifest.iter().enumerate() {
+            assert_eq!(store.has(&spec.path), i % 2 == 0);
+        }
+        assert!(store.problems.is_empty());
+    }
+
+    #[test]
+    fn broken_or_required_files_are_listed_once_per_feature() {
+        let spec = |path: &str, feature, required| AssetSpec {
+            path: String::from(path),
+            feature,
+            required,
+        };
+        let mut store = AssetStore::default();
+        store.add(
+            &spec("a.ogg", "music", false),
+            Err(String::from("bad")),
+            true,
+        );
+        store.add(
+            &spec("b.ogg", "music", false),
+            Err(String::from("bad")),
+            true,
+        );
+        store.add(&spec("font.ttf", "fonts", true), missing(), false);
+        assert_eq!(
+            store.problems,
+            [
+                "Running without music: a.ogg didn't load",
+                "Running without fonts: font.ttf not found",
+            ]
+        );
+    }
+}
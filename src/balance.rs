@@ -0,0 +1,189 @@
+// Gameplay tuning numbers that don't belong to a single entity
+#[derive(Clone)]
+pub struct Balance {
+    // Target number of asteroids per million pixels of play field
+    pub asteroid_density: f32,
+    // Speed of newly spawned asteroids at 1080p, in pixels per second
+    pub asteroid_speed: f32,
+    // Hits it takes to break a Huge asteroid
+    pub huge_asteroid_health: u32,
+    // Relative chance of each size class from Small to Huge when spawning.
+    // The defaults match the uniform 10-100 radius roll used before classes.
+    pub size_weights: [f32; 4],
+    // Fraction of the speed into a wall an asteroid keeps when bouncing off it
+    pub wall_restitution: f32,
+    // Asteroids never leave with walls on, so fewer are spawned to begin with
+    pub walls_density_scale: f32,
+    // Points lost per second in Pressure mode
+    pub pressure_decay_rate: f32,
+    // Score that has to be held in Pressure mode to win
+    pub pressure_target: u32,
+    // Seconds the target score has to be held without dipping below it
+    pub pressure_hold_duration: f32,
+    // Points lost in Pressure mode when an asteroid leaves the screen
+    pub pressure_escape_penalty: f32,
+    // Chance that a destroyed asteroid drops a Repair power-up
+    pub repair_drop_chance: f32,
+    // Chance it drops a Deflector instead, the seconds the arc lasts, the speed
+    // multiplier on asteroids it reflects and the ship's recoil speed
+    pub deflector_drop_chance: f32,
+    pub deflector_duration: f32,
+    pub deflector_boost: f32,
+    pub deflector_recoil: f32,
+    // Points awarded instead when a Repair is picked up at full health
+    pub repair_full_health_points: f32,
+    // Ammo economy: lasers in the magazine at the start and at most, the chance a
+    // destroyed asteroid drops salvage and the ammo it restores, seconds per
+    // round of passive regen, and the count the magazine warns below
+    pub ammo_start: u32,
+    pub ammo_max: u32,
+    pub salvage_drop_chance: f32,
+    pub salvage_ammo: (u32, u32),
+    pub ammo_regen_interval: f32,
+    pub ammo_low: u32,
+    // Seconds without taking damage before field repair restores 1 health
+    pub field_repair_interval: f32,
+    // Seconds the ship has to survive to win a versus match
+    pub versus_duration: f32,
+    // Asteroid budget of the attacker in versus, spent by size when throwing
+    pub versus_budget_max: f32,
+    pub versus_budget_regen: f32,
+    // Seconds between the attacker's throws
+    pub versus_launch_cooldown: f32,
+    // Average seconds between gold rush events in Wave mode, and how long one lasts
+    pub gold_rush_interval: f32,
+    pub gold_rush_duration: f32,
+    pub gold_rush_radius: f32,
+    // Point multiplier for asteroids destroyed inside the zone
+    pub gold_rush_multiplier: f32,
+    // Points per second for keeping the ship inside the zone
+    pub gold_rush_ship_points: f32,
+    // Extra asteroids sent toward the zone when it opens
+    pub gold_rush_extra_spawns: usize,
+    // Average seconds between derelicts drifting across, their speed and size
+    // at 1080p, and points for shooting one down
+    pub derelict_interval: f32,
+    pub derelict_speed: f32,
+    pub derelict_radius: f32,
+    pub derelict_destroy_points: f32,
+    // Distance at 1080p the ship scans a derelict from, the seconds in range a
+    // scan takes (they don't have to be in one go) and the points it's worth
+    pub derelict_scan_range: f32,
+    pub derelict_scan_duration: f32,
+    pub derelict_scan_points: f32,
+    // Spawn director: budget earned per second at the start of a run, how much
+    // that grows per minute played, and the most it can save up
+    pub director_rate: f32,
+    pub director_rate_per_minute: f32,
+    pub director_budget_max: f32,
+    // Cost of an asteroid of each size from Small to Huge and the seconds
+    // between any two. Which size it picks follows `size_weights`.
+    pub director_asteroid_costs: [f32; 4],
+    pub director_asteroid_cooldown: f32,
+    // Cost, relative chance against the asteroids and cooldown of a gold rush
+    pub director_gold_rush_cost: f32,
+    pub director_gold_rush_weight: f32,
+    pub director_gold_rush_cooldown: f32,
+    // Wave mode ring ambush: the first wave it can happen in, average seconds
+    // between them and the warning before the ring appears
+    pub ring_min_wave: u32,
+    pub ring_interval: f32,
+    pub ring_warning: f32,
+    // Fewest and most Medium asteroids in the ring, its radius around the ship
+    // and their speed relative to normal spawns
+    pub ring_count: (usize, usize),
+    pub ring_radius: f32,
+    pub ring_speed_scale: f32,
+    // Focus meter gained per asteroid destroyed, out of 1
+    pub focus_per_kill: f32,
+    // Focus meter used per second while active and per shot fired during it
+    pub focus_drain_rate: f32,
+    pub focus_fire_cost: f32,
+    // Speed of asteroids, lasers and world timers while focus is active
+    pub focus_time_scale: f32,
+    // Wave bonus for clearing without taking damage, multiplied by the wave number
+    pub no_damage_bonus: f32,
+    // Par time for a wave per asteroid it starts with, and points per second under par
+    pub wave_par_per_asteroid: f32,
+    pub speed_bonus_per_second: f32,
+    // Starting score estimate for a practice run from a later wave: points per
+    // asteroid each skipped wave starts with, counting its splits, and bonus
+    // points per skipped wave
+    pub practice_points_per_asteroid: f32,
+    pub practice_bonus_per_wave: f32,
+    // Ore needed to win a Mining run, and the seconds available to collect it
+    pub mining_ore_target: u32,
+    pub mining_duration: f32,
+}
+impl Default for Balance {
+    fn default() -> Balance {
+        Balance {
+            // 20 asteroids on a 1920x1080 screen
+            asteroid_density: 9.65,
+            asteroid_speed: 100.0,
+            huge_asteroid_health: 3,
+            size_weights: [1.0, 2.0, 2.0, 4.0],
+            wall_restitution: 0.9,
+            walls_density_scale: 0.6,
+            pressure_decay_rate: 1.0,
+            pressure_target: 25,
+            pressure_hold_duration: 10.0,
+            pressure_escape_penalty: 5.0,
+            repair_drop_chance: 0.03,
+            deflector_drop_chance: 0.02,
+            deflector_duration: 6.0,
+            deflector_boost: 1.3,
+            deflector_recoil: 250.0,
+            repair_full_health_points: 25.0,
+            ammo_start: 40,
+            ammo_max: 60,
+            salvage_drop_chance: 0.5,
+            salvage_ammo: (3, 6),
+            ammo_regen_interval: 4.0,
+            ammo_low: 10,
+            field_repair_interval: 60.0,
+            versus_duration: 120.0,
+            versus_budget_max: 8.0,
+            versus_budget_regen: 0.5,
+            versus_launch_cooldown: 0.75,
+            gold_rush_interval: 120.0,
+            gold_rush_duration: 15.0,
+            gold_rush_radius: 180.0,
+            gold_rush_multiplier: 3.0,
+            gold_rush_ship_points: 1.0,
+            gold_rush_extra_spawns: 3,
+            derelict_interval: 75.0,
+            derelict_speed: 40.0,
+            derelict_radius: 26.0,
+            derelict_destroy_points: 50.0,
+            derelict_scan_range: 80.0,
+            derelict_scan_duration: 3.0,
+            derelict_scan_points: 200.0,
+            director_rate: 2.0,
+            director_rate_per_minute: 0.5,
+            director_budget_max: 16.0,
+            director_asteroid_costs: [1.0, 2.0, 3.0, 5.0],
+            director_asteroid_cooldown: 0.25,
+            director_gold_rush_cost: 12.0,
+            director_gold_rush_weight: 0.5,
+            director_gold_rush_cooldown: 90.0,
+            ring_min_wave: 4,
+            ring_interval: 90.0,
+            ring_warning: 3.0,
+            ring_count: (8, 12),
+            ring_radius: 400.0,
+            ring_speed_scale: 0.5,
+            focus_per_kill: 0.05,
+            focus_drain_rate: 0.25,
+            focus_fire_cost: 0.05,
+            focus_time_scale: 0.4,
+            mining_ore_target: 50,
+            mining_duration: 180.0,
+            no_damage_bonus: 200.0,
+            wave_par_per_asteroid: 4.0,
+            speed_bonus_per_second: 20.0,
+            practice_points_per_asteroid: 3.0,
+            practice_bonus_per_wave: 100.0,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use macroquad::prelude::*;
+
+// Headline sized to the screen that types itself in one letter at a time and
+// then bobs gently. The caller owns the timeline and passes in the seconds
+// since the banner came up, so skipping ahead is just moving that forward.
+pub struct Banner<'a> {
+    pub text: &'a str,
+    pub color: Color,
+    // Letter height as a share of the screen height
+    pub height: f32,
+}
+
+impl Banner<'_> {
+    // Seconds to reveal every letter, however long the text
+    pub const REVEAL_DURATION: f32 = 0.6;
+    // Seconds each letter takes to fade and drop into place
+    const LETTER_FADE: f32 = 0.12;
+    // Bob height as a share of the letter size, and its speed in radians per second
+    const BOB_AMPLITUDE: f32 = 0.04;
+    const BOB_SPEED: f32 = 3.0;
+
+    // Warm for a win, a little red for a loss
+    pub const WIN_TINT: Color = Color::new(1.0, 0.9, 0.7, 1.0);
+    pub const LOSS_TINT: Color = Color::new(1.0, 0.72, 0.72, 1.0);
+
+    // Centered on the screen with its baseline at `y`. Reduced motion draws the
+    // finished banner standing still.
+    pub fn render(&self, y: f32, shown: f32, reduced_motion: bool) {
+        let letters: Vec<char> = self.text.chars().collect();
+        if letters.is_empty() {
+            return;
+        }
+        // Shrunk to fit a narrow window
+        let mut font_size = (screen_height() * self.height).max(1.0);
+        let total: f32 = letters.iter().map(|c| letter_width(*c, font_size)).sum();
+        let max_width = screen_width() - 20.0;
+        if total > max_width {
+            font_size *= max_width / total;
+        }
+        // Laid out a letter at a time so each can move on its own
+        let widths: Vec<f32> = letters
+            .iter()
+            .map(|c| letter_width(*c, font_size))
+            .collect();
+        let shown = if reduced_motion {
+            Self::REVEAL_DURATION
+        } else {
+            shown
+        };
+        // The bob eases in over a second once the last letter is down
+        let bob = if reduced_motion {
+            0.0
+        } else {
+            (shown - Self::REVEAL_DURATION).clamp(0.0, 1.0) * Self::BOB_AMPLITUDE * font_size
+        };
+
+        let stagger =
+            (Self::REVEAL_DURATION - Self::LETTER_FADE) / (letters.len() - 1).max(1) as f32;
+        let mut x = (screen_width() - widths.iter().sum::<f32>()) / 2.0;
+        for (i, (letter, width)) in letters.iter().zip(widths).enumerate() {
+            // The last letter finishes fading in right as the reveal ends
+            let appears_at = i as f32 * stagger;
+            let t = ((shown - appears_at) / Self::LETTER_FADE).clamp(0.0, 1.0);
+            if t > 0.0 {
+                let drop = (1.0 - t) * font_size * 0.15;
+                let wave = (shown * Self::BOB_SPEED + i as f32 * 0.5).sin() * bob;
+                let color = Color {
+                    a: self.color.a * t,
+                    ..self.color
+                };
+                let mut buffer = [0; 4];
+                draw_text(
+                    letter.encode_utf8(&mut buffer),
+                    x,
+                    y - drop + wave,
+                    font_size,
+                    color,
+                );
+            }
+            x += width;
+        }
+    }
+}
+
+fn letter_width(letter: char, font_size: f32) -> f32 {
+    let mut buffer = [0; 4];
+    measure_text(letter.encode_utf8(&mut buffer), None, font_size as u16, 1.0).width
+}
@@ -0,0 +1,46 @@
+// Reports the first event where two `--log-file` logs disagree, for example a
+// live run and its replay.
+//
+//     cargo run --bin logdiff -- live.log replay.log
+
+use std::{fs, process};
+
+fn read_lines(path: &str) -> Vec<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => contents.lines().map(String::from).collect(),
+        Err(err) => {
+            eprintln!("Couldn't read {}: {}", path, err);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("Usage: logdiff <first.log> <second.log>");
+        process::exit(2);
+    }
+    let first = read_lines(&args[0]);
+    let second = read_lines(&args[1]);
+
+    for (i, (a, b)) in first.iter().zip(&second).enumerate() {
+        if a != b {
+            println!("Logs diverge at line {}:", i + 1);
+            println!("  {}: {}", args[0], a);
+            println!("  {}: {}", args[1], b);
+            process::exit(1);
+        }
+    }
+    if first.len() != second.len() {
+        let (longer, extra) = if first.len() > second.len() {
+            (&args[0], &first[second.len()])
+        } else {
+            (&args[1], &second[first.len()])
+        };
+        println!("Logs match until {} continues with:", longer);
+        println!("  {}", extra);
+        process::exit(1);
+    }
+    println!("Logs match ({} events)", first.len());
+}
@@ -0,0 +1,252 @@
+use crate::keys;
+use macroquad::prelude::*;
+
+// Actions read while playing, which the player can rebind
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameAction {
+    Forward,
+    Backward,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    // Slow the world down using the focus meter
+    Focus,
+    // Side thrusters, once unlocked
+    StrafeLeft,
+    StrafeRight,
+    // Held to throw the run away and start over
+    Restart,
+}
+impl GameAction {
+    pub const ALL: [GameAction; 9] = [
+        GameAction::Forward,
+        GameAction::Backward,
+        GameAction::RotateLeft,
+        GameAction::RotateRight,
+        GameAction::Fire,
+        GameAction::Focus,
+        GameAction::StrafeLeft,
+        GameAction::StrafeRight,
+        GameAction::Restart,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameAction::Forward => "forward",
+            GameAction::Backward => "backward",
+            GameAction::RotateLeft => "rotate_left",
+            GameAction::RotateRight => "rotate_right",
+            GameAction::Fire => "fire",
+            GameAction::Focus => "focus",
+            GameAction::StrafeLeft => "strafe_left",
+            GameAction::StrafeRight => "strafe_right",
+            GameAction::Restart => "restart",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GameAction> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    // What the action does, for the controls overlay
+    pub fn description(self) -> &'static str {
+        match self {
+            GameAction::Forward => "Thrust",
+            GameAction::Backward => "Reverse",
+            GameAction::RotateLeft => "Rotate left",
+            GameAction::RotateRight => "Rotate right",
+            GameAction::Fire => "Fire",
+            GameAction::Focus => "Focus",
+            GameAction::StrafeLeft => "Strafe left",
+            GameAction::StrafeRight => "Strafe right",
+            GameAction::Restart => "Hold to restart",
+        }
+    }
+
+    // Steering actions, drawn as a key cluster rather than a list
+    pub fn is_movement(self) -> bool {
+        matches!(
+            self,
+            GameAction::Forward
+                | GameAction::Backward
+                | GameAction::RotateLeft
+                | GameAction::RotateRight
+        )
+    }
+}
+
+// Menu actions. They stay on fixed keys so a gameplay rebind can't lock up a menu.
+#[derive(Clone, Copy, Debug)]
+pub enum UiAction {
+    Confirm,
+    // Also pauses during play
+    Back,
+    NavUp,
+    NavDown,
+    NavLeft,
+    NavRight,
+}
+impl UiAction {
+    const ALL: [UiAction; 6] = [
+        UiAction::Confirm,
+        UiAction::Back,
+        UiAction::NavUp,
+        UiAction::NavDown,
+        UiAction::NavLeft,
+        UiAction::NavRight,
+    ];
+
+    pub fn from_name(name: &str) -> Option<UiAction> {
+        Self::ALL.into_iter().find(|a| format!("{:?}", a) == name)
+    }
+
+    pub fn key(self) -> KeyCode {
+        match self {
+            UiAction::Confirm => KeyCode::Enter,
+            UiAction::Back => KeyCode::Escape,
+            UiAction::NavUp => KeyCode::Up,
+            UiAction::NavDown => KeyCode::Down,
+            UiAction::NavLeft => KeyCode::Left,
+            UiAction::NavRight => KeyCode::Right,
+        }
+    }
+
+    pub fn is_pressed(self) -> bool {
+        keys::is_pressed(self.key())
+    }
+}
+
+#[derive(Clone)]
+pub struct Bindings {
+    // Indexed by `GameAction`
+    keys: [KeyCode; 9],
+}
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings {
+            keys: [
+                KeyCode::W,
+                KeyCode::S,
+                KeyCode::A,
+                KeyCode::D,
+                KeyCode::Space,
+                KeyCode::LeftShift,
+                KeyCode::Q,
+                KeyCode::E,
+                KeyCode::R,
+            ],
+        }
+    }
+}
+impl Bindings {
+    pub fn key(&self, action: GameAction) -> KeyCode {
+        self.keys[action as usize]
+    }
+
+    // Whether `key` is bound to any gameplay action
+    pub fn is_bound(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn is_down(&self, action: GameAction) -> bool {
+        keys::is_down(self.key(action))
+    }
+
+    // Two gameplay actions can't share a key. Sharing one with a menu action is
+    // allowed, since menus and play never read input at the same time, and is
+    // returned so the caller can warn about it.
+    pub fn rebind(&mut self, action: GameAction, key: KeyCode) -> Result<Option<UiAction>, String> {
+        if let Some(other) = GameAction::ALL
+            .into_iter()
+            .find(|&other| other != action && self.keys[other as usize] == key)
+        {
+            return Err(format!("{:?} is already bound to {}", key, other.name()));
+        }
+        self.keys[action as usize] = key;
+        Ok(UiAction::ALL.into_iter().find(|ui| ui.key() == key))
+    }
+}
+
+// Key names accepted by `--bind`, like `Space`, `Enter` or `Q`
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Space,
+        "enter" => KeyCode::Enter,
+        "escape" => KeyCode::Escape,
+        "tab" => KeyCode::Tab,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "leftshift" => KeyCode::LeftShift,
+        "rightshift" => KeyCode::RightShift,
+        "leftcontrol" => KeyCode::LeftControl,
+        "rightcontrol" => KeyCode::RightControl,
+        "a" => KeyCode::A,
+        "b" => KeyCode::B,
+        "c" => KeyCode::C,
+        "d" => KeyCode::D,
+        "e" => KeyCode::E,
+        "f" => KeyCode::F,
+        "g" => KeyCode::G,
+        "h" => KeyCode::H,
+        "i" => KeyCode::I,
+        "j" => KeyCode::J,
+        "k" => KeyCode::K,
+        "l" => KeyCode::L,
+        "m" => KeyCode::M,
+        "n" => KeyCode::N,
+        "o" => KeyCode::O,
+        "p" => KeyCode::P,
+        "q" => KeyCode::Q,
+        "r" => KeyCode::R,
+        "s" => KeyCode::S,
+        "t" => KeyCode::T,
+        "u" => KeyCode::U,
+        "v" => KeyCode::V,
+        "w" => KeyCode::W,
+        "x" => KeyCode::X,
+        "y" => KeyCode::Y,
+        "z" => KeyCode::Z,
+        _ => return None,
+    };
+    Some(key)
+}
+
+// Short name of a key for prompts and menus
+pub fn key_label(key: KeyCode) -> String {
+    let label = match key {
+        KeyCode::Escape => "Esc",
+        KeyCode::LeftShift | KeyCode::RightShift => "Shift",
+        KeyCode::LeftControl | KeyCode::RightControl => "Ctrl",
+        KeyCode::GraveAccent => "`",
+        _ => return format!("{:?}", key),
+    };
+    String::from(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_onto_a_menu_key_is_allowed_with_a_warning() {
+        let mut bindings = Bindings::default();
+        let result = bindings.rebind(GameAction::Fire, KeyCode::Enter);
+        assert!(matches!(result, Ok(Some(UiAction::Confirm))));
+        assert_eq!(bindings.key(GameAction::Fire), KeyCode::Enter);
+    }
+
+    #[test]
+    fn two_gameplay_actions_cannot_share_a_key() {
+        let mut bindings = Bindings::default();
+        let forward = bindings.key(GameAction::Forward);
+        assert!(bindings.rebind(GameAction::Fire, forward).is_err());
+        assert_eq!(bindings.key(GameAction::Fire), KeyCode::Space);
+        // Rebinding an action onto its own key is fine
+        assert!(matches!(
+            bindings.rebind(GameAction::Forward, forward),
+            Ok(None)
+        ));
+    }
+}
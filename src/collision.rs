@@ -0,0 +1,78 @@
+use crate::geom;
+use macroquad::prelude::*;
+
+// Whether a point moving from `start` to `end` touched a circle that moved from
+// `circle_start` to `circle_end` over the same tick. Fast movers can otherwise
+// skip straight over a small circle between two sampled positions.
+pub fn swept_point_hits_circle(
+    start: Vec2,
+    end: Vec2,
+    circle_start: Vec2,
+    circle_end: Vec2,
+    radius: f32,
+) -> bool {
+    // Work in the circle's frame of reference so only the point moves
+    geom::segment_circle_intersect(start - circle_start, end - circle_end, Vec2::ZERO, radius)
+}
+
+// Where a swept point first touched a moving circle, like `swept_point_hits_circle`
+// but also reporting the contact point in world space
+pub fn swept_point_circle_contact(
+    start: Vec2,
+    end: Vec2,
+    circle_start: Vec2,
+    circle_end: Vec2,
+    radius: f32,
+) -> Option<Vec2> {
+    let from = start - circle_start;
+    let to = end - circle_end;
+    if from.length_squared() <= radius * radius {
+        return Some(start);
+    }
+
+    // First t in [0, 1] where the point is `radius` from the circle's center
+    let d = to - from;
+    let a = d.length_squared();
+    let b = 2.0 * from.dot(d);
+    let c = from.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if !(0.0..=1.0).contains(&t) {
+        return None;
+    }
+    Some(circle_start.lerp(circle_end, t) + from + d * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fast_ship_hits_asteroid_it_passes_over() {
+        // 15 radii in one tick, with the asteroid halfway along: neither
+        // sampled position is inside it
+        let radius = 20.0;
+        let start = vec2(-7.5 * radius, 0.0);
+        let end = vec2(7.5 * radius, 0.0);
+        let asteroid = vec2(0.0, 5.0);
+        assert!(swept_point_hits_circle(
+            start, end, asteroid, asteroid, radius
+        ));
+        assert!(swept_point_circle_contact(start, end, asteroid, asteroid, radius).is_some());
+    }
+
+    #[test]
+    fn fast_ship_misses_asteroid_off_its_path() {
+        let radius = 20.0;
+        let start = vec2(-7.5 * radius, 0.0);
+        let end = vec2(7.5 * radius, 0.0);
+        let asteroid = vec2(0.0, radius * 1.5);
+        assert!(!swept_point_hits_circle(
+            start, end, asteroid, asteroid, radius
+        ));
+        assert!(swept_point_circle_contact(start, end, asteroid, asteroid, radius).is_none());
+    }
+}
@@ -0,0 +1,367 @@
+use crate::keys;
+use crate::timer::Countdown;
+use crate::{AsteroidTemplate, Game, GameMode, PowerUp, PowerUpKind};
+use macroquad::prelude::*;
+use std::{collections::VecDeque, str::FromStr};
+
+type Handler = fn(&mut Game, &[&str]) -> Result<String, String>;
+
+// A console command, each one a single entry in `COMMANDS`
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    run: Handler,
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        usage: "help",
+        run: help,
+    },
+    Command {
+        name: "spawn",
+        usage: "spawn asteroid <radius> <x> <y>",
+        run: spawn,
+    },
+    Command {
+        name: "give",
+        usage: "give repair|ore|focus",
+        run: give,
+    },
+    Command {
+        name: "set",
+        usage: "set health|score|stroke <value>",
+        run: set,
+    },
+    Command {
+        name: "wave",
+        usage: "wave <number>",
+        run: wave,
+    },
+    Command {
+        name: "timescale",
+        usage: "timescale <multiplier>",
+        run: timescale,
+    },
+    Command {
+        name: "seed",
+        usage: "seed <seed>",
+        run: seed,
+    },
+    Command {
+        name: "kill",
+        usage: "kill all",
+        run: kill,
+    },
+    Command {
+        name: "storm",
+        usage: "storm on|off",
+        run: storm,
+    },
+    Command {
+        name: "stats",
+        usage: "stats",
+        run: stats,
+    },
+    Command {
+        name: "panic",
+        usage: "panic",
+        run: panic,
+    },
+];
+
+// Parse the argument at `index`, naming it in the error
+fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let value = args.get(index).ok_or(format!("missing {}", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {}: {}", name, value))
+}
+
+fn help(_: &mut Game, _: &[&str]) -> Result<String, String> {
+    let usages: Vec<&str> = COMMANDS.iter().map(|c| c.usage).collect();
+    Ok(usages.join(", "))
+}
+
+fn spawn(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    if args.first() != Some(&"asteroid") {
+        return Err(String::from("can only spawn asteroid"));
+    }
+    let radius: f32 = arg(args, 1, "radius")?;
+    let position = Vec2::new(arg(args, 2, "x")?, arg(args, 3, "y")?);
+    let angle = game.rng.gen_range(0.0, std::f32::consts::TAU);
+    game.materialize(&[AsteroidTemplate {
+        position,
+        velocity: Vec2::from_angle(angle) * game.balance.asteroid_speed * game.size_scale,
+        radius,
+    }]);
+    Ok(format!("Spawned asteroid {}", game.asteroid_counter))
+}
+
+fn give(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let kind = match args.first() {
+        Some(&"repair") => PowerUpKind::Repair,
+        Some(&"ore") => PowerUpKind::Ore,
+        Some(&"focus") => {
+            game.focus = 1.0;
+            return Ok(String::from("Focus meter filled"));
+        }
+        Some(other) => return Err(format!("nothing called {}", other)),
+        None => return Err(String::from("missing item")),
+    };
+    // Dropped on the ship so it's picked up next tick
+    game.power_ups
+        .push(PowerUp::new(kind, game.player.position, Vec2::ZERO));
+    Ok(format!("Gave {}", args[0]))
+}
+
+fn set(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    match args.first() {
+        Some(&"health") => {
+            let health: usize = arg(args, 1, "health")?;
+            game.player.health = health.clamp(1, game.player.max_health);
+            Ok(format!("Health set to {}", game.player.health))
+        }
+        Some(&"score") => {
+            game.score = arg(args, 1, "score")?;
+            Ok(format!("Score set to {}", game.score))
+        }
+        Some(&"stroke") => {
+            let scale: f32 = arg(args, 1, "stroke scale")?;
+            game.settings.stroke_scale = scale.max(0.1);
+            Ok(format!(
+                "Stroke scale set to {}",
+                game.settings.stroke_scale
+            ))
+        }
+        Some(other) => Err(format!("can't set {}", other)),
+        None => Err(String::from("missing property")),
+    }
+}
+
+fn wave(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    if game.mode != GameMode::Waves {
+        return Err(String::from("only available in Waves mode"));
+    }
+    let wave: u32 = arg(args, 0, "wave")?;
+    if wave == 0 {
+        return Err(String::from("waves start at 1"));
+    }
+    game.asteroids.clear();
+    game.reentries.clear();
+    game.next_wave.clear();
+    game.wave_clear = Countdown::default();
+    game.wave = wave;
+    let plan = game.plan_wave(wave);
+    game.start_wave(&plan);
+    Ok(format!("Started wave {}", wave))
+}
+
+fn timescale(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let scale: f32 = arg(args, 0, "multiplier")?;
+    if scale.is_nan() || scale <= 0.0 {
+        return Err(String::from("multiplier must be positive"));
+    }
+    game.console_time_scale = scale;
+    Ok(format!("Time scale set to {}", scale))
+}
+
+fn seed(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let seed: u64 = arg(args, 0, "seed")?;
+    game.reset(seed);
+    Ok(format!("Restarted with seed {}", seed))
+}
+
+fn kill(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    if args.first() != Some(&"all") {
+        return Err(String::from("can only kill all"));
+    }
+    let count = game.asteroids.len();
+    game.asteroids_destroyed += count as u32;
+    game.asteroids.clear();
+    Ok(format!("Destroyed {} asteroids", count))
+}
+
+fn storm(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    game.settings.storm = match args.first() {
+        Some(&"on") => true,
+        Some(&"off") => false,
+        _ => return Err(String::from("expected on or off")),
+    };
+    game.resize(game.width, game.height);
+    Ok(format!(
+        "Storm {}, {} asteroids",
+        args[0], game.max_asteroids
+    ))
+}
+
+fn stats(game: &mut Game, _: &[&str]) -> Result<String, String> {
+    Ok(format!(
+        "seed={} frame={} elapsed={:.1} asteroids={} lasers={} score={} rng={}",
+        game.seed,
+        game.frame,
+        game.elapsed,
+        game.asteroids.len(),
+        game.lasers.len(),
+        game.score(),
+        game.rng.draws()
+    ))
+}
+
+// Crashes the game on purpose, to try out the crash report
+fn panic(_: &mut Game, _: &[&str]) -> Result<String, String> {
+    panic!("panic requested from the developer console");
+}
+
+// Developer console for poking at the live game. Typed text never reaches
+// gameplay, the app stops updating the screen while it's open.
+pub struct Console {
+    input: String,
+    output: VecDeque<String>,
+    history: Vec<String>,
+    // Entry being shown while browsing the history, None for a fresh line
+    history_cursor: Option<usize>,
+}
+impl Console {
+    const OUTPUT_LINES: usize = 12;
+
+    pub fn new() -> Console {
+        Console {
+            input: String::new(),
+            output: VecDeque::new(),
+            history: vec![],
+            history_cursor: None,
+        }
+    }
+
+    // Forget text typed while the console was closed
+    pub fn open(&mut self) {
+        keys::clear_queue();
+    }
+
+    // Returns true if a command ran and changed the game
+    pub fn update(&mut self, game: &mut Game) -> bool {
+        // The queue pops the most recent character first
+        let mut typed = vec![];
+        while let Some(c) = keys::next_char() {
+            typed.push(c);
+        }
+        for c in typed.into_iter().rev() {
+            // The toggle key types a character too
+            if !c.is_control() && c != '`' && c != '~' {
+                self.input.push(c);
+            }
+        }
+
+        if keys::is_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if keys::is_pressed(KeyCode::Up) {
+            self.browse_history(-1);
+        }
+        if keys::is_pressed(KeyCode::Down) {
+            self.browse_history(1);
+        }
+        if keys::is_pressed(KeyCode::Tab) {
+            self.complete();
+        }
+        if keys::is_pressed(KeyCode::Enter) {
+            return self.submit(game);
+        }
+        false
+    }
+
+    fn browse_history(&mut self, step: isize) {
+        if self.history.is_empty() {
+            return;
+        }
+        let cursor = match self.history_cursor {
+            Some(cursor) => cursor as isize + step,
+            None if step < 0 => self.history.len() as isize - 1,
+            None => return,
+        };
+        if cursor >= self.history.len() as isize {
+            self.history_cursor = None;
+            self.input.clear();
+        } else {
+            let cursor = cursor.max(0) as usize;
+            self.history_cursor = Some(cursor);
+            self.input = self.history[cursor].clone();
+        }
+    }
+
+    // Complete the command name, listing the candidates if there's more than one
+    fn complete(&mut self) {
+        if self.input.contains(' ') {
+            return;
+        }
+        let matches: Vec<&str> = COMMANDS
+            .iter()
+            .map(|c| c.name)
+            .filter(|name| name.starts_with(self.input.as_str()))
+            .collect();
+        match matches.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            _ => self.print(matches.join("  ")),
+        }
+    }
+
+    fn submit(&mut self, game: &mut Game) -> bool {
+        let line = std::mem::take(&mut self.input);
+        self.history_cursor = None;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            return false;
+        };
+        self.history.push(line.clone());
+        self.print(format!("> {}", line));
+
+        let Some(command) = COMMANDS.iter().find(|c| c.name == *name) else {
+            self.print(format!("Unknown command {}, try help", name));
+            return false;
+        };
+        match (command.run)(game, args) {
+            Ok(message) => {
+                self.print(message);
+                true
+            }
+            Err(e) => {
+                self.print(format!("{} (usage: {})", e, command.usage));
+                false
+            }
+        }
+    }
+
+    fn print(&mut self, line: String) {
+        self.output.push_back(line);
+        if self.output.len() > Self::OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+
+    pub fn render(&self) {
+        let line_height = 20.0;
+        let height = line_height * (Self::OUTPUT_LINES as f32 + 1.0) + 12.0;
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        for (i, line) in self.output.iter().enumerate() {
+            draw_text(line, 8.0, line_height * (i as f32 + 1.0), 20.0, LIGHTGRAY);
+        }
+        // Blinking cursor after the input line
+        let cursor = if get_time() % 1.0 < 0.5 { "_" } else { "" };
+        draw_text(
+            &format!("] {}{}", self.input, cursor),
+            8.0,
+            height - 10.0,
+            20.0,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::bindings::{Bindings, GameAction};
+use crate::keys;
+use crate::{draw_text_centered_at, prompt, ui};
+use macroquad::prelude::*;
+
+// Key reference drawn over the game while Tab is held. It only looks at the
+// keyboard, so play carries on underneath and no input is taken away from it.
+const KEY: KeyCode = KeyCode::Tab;
+
+pub fn is_held(bindings: &Bindings) -> bool {
+    // Someone who bound Tab to an action gets the action instead
+    keys::is_down(KEY) && !bindings.is_bound(KEY)
+}
+
+// Steering keys as a cluster, then everything else listed below it. The panel
+// grows with the number of other actions. Strafing is only listed once unlocked.
+pub fn render(bindings: &Bindings, strafe: bool) {
+    let (screen_w, screen_h) = (screen_width(), screen_height());
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_w,
+        screen_h,
+        Color::new(0.0, 0.0, 0.0, 0.35),
+    );
+
+    let others: Vec<GameAction> = GameAction::ALL
+        .into_iter()
+        .filter(|a| !a.is_movement())
+        .filter(|a| strafe || !matches!(a, GameAction::StrafeLeft | GameAction::StrafeRight))
+        .collect();
+    let row = ui(34.0);
+    let cluster_height = ui(170.0);
+    // Title, the cluster, the other actions and Pause
+    let height = ui(60.0) + cluster_height + row * (others.len() + 1) as f32 + ui(10.0);
+    let width = ui(520.0);
+    let (x, y) = ((screen_w - width) / 2.0, (screen_h - height) / 2.0);
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 1.0, GRAY);
+    let center_x = screen_w / 2.0;
+    draw_text_centered_at("Controls", center_x, y + ui(40.0), 32, WHITE);
+
+    let key = |action: GameAction, x: f32, y: f32| {
+        let glyph = format!("{{{}}}", action.name());
+        prompt::draw_prompt_centered_at(&glyph, x, y, 28, WHITE, bindings);
+    };
+    let label = |text: &str, x: f32, y: f32| {
+        draw_text_centered_at(text, x, y, 20, LIGHTGRAY);
+    };
+    let top_row = y + ui(110.0);
+    let bottom_row = top_row + ui(45.0);
+    let spread = ui(70.0);
+    key(GameAction::Forward, center_x, top_row);
+    label(
+        GameAction::Forward.description(),
+        center_x,
+        top_row - ui(32.0),
+    );
+    key(GameAction::RotateLeft, center_x - spread, bottom_row);
+    key(GameAction::Backward, center_x, bottom_row);
+    key(GameAction::RotateRight, center_x + spread, bottom_row);
+    label(
+        GameAction::Backward.description(),
+        center_x,
+        bottom_row + ui(30.0),
+    );
+    // Side labels sit outside the cluster, pushed out by half their width
+    for (action, side) in [
+        (GameAction::RotateLeft, -1.0),
+        (GameAction::RotateRight, 1.0),
+    ] {
+        let text = action.description();
+        let half = measure_text(text, None, ui(20.0) as u16, 1.0).width / 2.0;
+        label(
+            text,
+            center_x + side * (spread + ui(40.0) + half),
+            bottom_row,
+        );
+    }
+
+    let mut line_y = y + ui(60.0) + cluster_height;
+    for action in others {
+        let line = format!("{{{}}} {}", action.name(), action.description());
+        prompt::draw_prompt_centered_at(&line, center_x, line_y, 24, WHITE, bindings);
+        line_y += row;
+    }
+    prompt::draw_prompt_centered_at("{Back} Pause", center_x, line_y, 24, WHITE, bindings);
+}
@@ -0,0 +1,148 @@
+// Crash reports. A panic hook writes what was going on to a file in the data
+// directory, and the next launch picks it up and salvages the run it cut short
+// into the history, marked as crashed.
+use crate::persist::{self, Document};
+use crate::profile::RunSummary;
+use crate::GameMode;
+use macroquad::miniquad;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fs,
+    panic::PanicHookInfo,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+const REPORT_NAME: &str = "crash.txt";
+// Most recent event log lines kept for the report
+const LOG_LINES: usize = 200;
+const SAVE_FLUSH: Duration = Duration::from_secs(1);
+
+// The run in progress as of the last frame, copied so keeping it current
+// costs nothing
+#[derive(Clone, Copy)]
+pub struct RunState {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub score: u32,
+    pub elapsed: f32,
+    pub frame: u64,
+    pub asteroids_destroyed: u32,
+    pub escaped: u32,
+    pub wave: u32,
+    pub weekly: bool,
+    pub asteroids: usize,
+    pub lasers: usize,
+    pub particles: usize,
+}
+
+thread_local! {
+    static RUN: Cell<Option<RunState>> = const { Cell::new(None) };
+    static LOG: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// Set while a report is being written, so a panic in the hook can't start another
+static WRITING: AtomicBool = AtomicBool::new(false);
+
+// Called every frame, with None outside of a run that would be recorded
+pub fn note_run(run: Option<RunState>) {
+    RUN.with(|r| r.set(run));
+}
+
+pub fn note_log(line: &str) {
+    LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if log.len() == LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(String::from(line));
+    });
+}
+
+// Keeps the default message on stderr and writes the report after it
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if WRITING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Saves already queued, like the profile, shouldn't go down with the game
+        persist::flush(SAVE_FLUSH);
+        match persist::write_dump(REPORT_NAME, &report(info)) {
+            Ok(path) => eprintln!("Crash report saved to {}", path.display()),
+            Err(e) => eprintln!("Couldn't save a crash report: {}", e),
+        }
+    }));
+}
+
+// Everything is read without borrowing anything that might still be held
+// where the panic happened
+fn report(info: &PanicHookInfo) -> Document {
+    let mut report = Document::default();
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(|s| s.as_str()))
+        .unwrap_or("(no message)");
+    report.set("message", message.replace('\n', " "));
+    if let Some(location) = info.location() {
+        report.set("location", location);
+    }
+    report.set("version", env!("CARGO_PKG_VERSION"));
+    report.set("os", std::env::consts::OS);
+
+    if let Some(run) = RUN.try_with(Cell::get).ok().flatten() {
+        report.set("mode", format!("{:?}", run.mode));
+        report.set("seed", run.seed);
+        report.set("elapsed", format!("{:.1}", run.elapsed));
+        report.set("frame", run.frame);
+        report.set(
+            "entities",
+            format!(
+                "asteroids:{} lasers:{} particles:{}",
+                run.asteroids, run.lasers, run.particles
+            ),
+        );
+        let summary = RunSummary {
+            ended_at: miniquad::date::now(),
+            mode: format!("{:?}", run.mode),
+            seed: run.seed,
+            score: run.score,
+            won: false,
+            duration: run.elapsed,
+            asteroids_destroyed: run.asteroids_destroyed,
+            escaped: run.escaped,
+            wave: run.wave,
+            weekly: run.weekly,
+            crashed: true,
+            ..RunSummary::default()
+        };
+        report.set("run", summary.to_field());
+    }
+    let _ = LOG.try_with(|log| {
+        if let Ok(log) = log.try_borrow() {
+            for (i, line) in log.iter().enumerate() {
+                report.set(&format!("log.{}", i), line);
+            }
+        }
+    });
+    report
+}
+
+// A report left by the last session, moved aside under its time so it's only
+// picked up once, with the run it salvaged if it crashed mid-run
+pub fn take_report() -> Option<(PathBuf, Option<RunSummary>)> {
+    let report = persist::read_dump(REPORT_NAME)?;
+    let from = persist::data_dir().join(REPORT_NAME);
+    let to = persist::data_dir().join(format!("crash-{}.txt", miniquad::date::now() as u64));
+    if let Err(e) = fs::rename(&from, &to) {
+        eprintln!("Failed to move {}: {}", from.display(), e);
+        return None;
+    }
+    let run = report.get("run").and_then(RunSummary::from_field);
+    Some((to, run))
+}
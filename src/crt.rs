@@ -0,0 +1,154 @@
+use crate::settings::Settings;
+use macroquad::miniquad::{UniformDesc, UniformType};
+use macroquad::prelude::*;
+
+// CRT look applied to the finished frame: the frame is drawn into `target`
+// and then onto the screen through a shader. When the setting is off none of
+// this exists and everything draws straight to the screen.
+pub struct CrtFilter {
+    material: Material,
+    target: RenderTarget,
+    size: (u32, u32),
+}
+impl CrtFilter {
+    // Fails if the shader doesn't compile on this backend
+    pub fn new(settings: &Settings, width: f32, height: f32) -> Result<CrtFilter, String> {
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: VERTEX_SHADER,
+                fragment: FRAGMENT_SHADER,
+            },
+            MaterialParams {
+                uniforms: vec![
+                    UniformDesc::new("Scanlines", UniformType::Float1),
+                    UniformDesc::new("Curvature", UniformType::Float1),
+                    UniformDesc::new("Vignette", UniformType::Float1),
+                    UniformDesc::new("Lines", UniformType::Float1),
+                ],
+                ..Default::default()
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        material.set_uniform("Scanlines", settings.crt_scanlines);
+        material.set_uniform("Curvature", settings.crt_curvature);
+        material.set_uniform("Vignette", settings.crt_vignette);
+
+        let size = (width as u32, height as u32);
+        Ok(CrtFilter {
+            material,
+            target: Self::create_target(size),
+            size,
+        })
+    }
+
+    fn create_target(size: (u32, u32)) -> RenderTarget {
+        let target = render_target(size.0.max(1), size.1.max(1));
+        target.texture.set_filter(FilterMode::Nearest);
+        target
+    }
+
+    // The target has to match the window, so it's recreated when that changes size
+    pub fn resize(&mut self, width: f32, height: f32) {
+        let size = (width as u32, height as u32);
+        if size != self.size {
+            self.size = size;
+            self.target = Self::create_target(size);
+        }
+    }
+
+    pub fn target(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    // Screen coordinates, drawing into the target instead of the screen
+    pub fn camera(&self) -> Camera2D {
+        let size = Vec2::new(self.size.0 as f32, self.size.1 as f32);
+        Camera2D {
+            target: size / 2.0,
+            // Positive y zoom keeps the texture upright when it's drawn back to the screen
+            zoom: Vec2::new(2.0 / size.x, 2.0 / size.y),
+            render_target: Some(self.target.clone()),
+            ..Default::default()
+        }
+    }
+
+    pub fn begin(&self) {
+        set_camera(&self.camera());
+        clear_background(BLACK);
+    }
+
+    // Draw what's in the target to the screen through the filter
+    pub fn present(&self) {
+        set_default_camera();
+        // About one scanline every 3 pixels
+        self.material.set_uniform("Lines", self.size.1 as f32 / 3.0);
+        gl_use_material(&self.material);
+        draw_texture_ex(
+            &self.target.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(Vec2::new(screen_width(), screen_height())),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+
+    // The unfiltered frame so far, top row first
+    pub fn capture(&self) -> Image {
+        unsafe {
+            get_internal_gl().flush();
+        }
+        self.target.texture.get_texture_data()
+    }
+}
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 100
+precision mediump float;
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+uniform float Scanlines;
+uniform float Curvature;
+uniform float Vignette;
+uniform float Lines;
+
+void main() {
+    // Barrel distortion, sampling further out toward the edges
+    vec2 centered = uv * 2.0 - 1.0;
+    centered *= 1.0 + Curvature * dot(centered, centered);
+    vec2 curved = centered * 0.5 + 0.5;
+    if (curved.x < 0.0 || curved.x > 1.0 || curved.y < 0.0 || curved.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec3 res = texture2D(Texture, curved).rgb * color.rgb;
+    float scanline = 1.0 - Scanlines * (0.5 + 0.5 * cos(curved.y * Lines * 6.2832));
+    float edge = 16.0 * curved.x * curved.y * (1.0 - curved.x) * (1.0 - curved.y);
+    float vignette = mix(1.0, clamp(pow(edge, 0.3), 0.0, 1.0), Vignette);
+    gl_FragColor = vec4(res * scanline * vignette, 1.0);
+}
+";
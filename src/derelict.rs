@@ -0,0 +1,136 @@
+// Derelict satellites that now and then drift across the field. They're
+// harmless to the ship: shooting one is worth a few points, staying close to
+// it long enough to scan it is worth more and turns up a line of lore.
+use crate::stroke;
+use macroquad::prelude::*;
+
+// Lore by id, shown once per profile in this order as derelicts are scanned
+const LORE: [(&str, &str); 6] = [
+    (
+        "relay",
+        "Relay 7, last log: \"Field density still rising. Requesting evac.\"",
+    ),
+    (
+        "survey",
+        "Survey drone memory: 14,000 rocks catalogued, all of them moving",
+    ),
+    (
+        "miner",
+        "Mining claim beacon, still broadcasting for a crew that isn't coming",
+    ),
+    (
+        "lifeboat",
+        "Lifeboat, empty. The launch clamps were released from outside",
+    ),
+    (
+        "probe",
+        "Probe 3 found where the asteroids come from. The rest of the file is corrupt",
+    ),
+    (
+        "station",
+        "Station core: \"To whoever scans this - the field isn't natural\"",
+    ),
+];
+
+// Id and text of the first lore line not in `seen`, none once they've all been shown
+pub fn next_lore(seen: &[String]) -> Option<(&'static str, &'static str)> {
+    LORE.into_iter()
+        .find(|(id, _)| !seen.iter().any(|s| s == id))
+}
+
+#[derive(Clone)]
+pub struct Derelict {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub radius: f32,
+    rotation: f32,
+    // Radians per second
+    spin: f32,
+    // Seconds the ship has spent in scanning range, and whether a scan finished
+    pub scan_time: f32,
+    pub scanned: bool,
+}
+impl Derelict {
+    pub fn new(position: Vec2, velocity: Vec2, radius: f32, spin: f32) -> Derelict {
+        Derelict {
+            position,
+            velocity,
+            radius,
+            rotation: 0.0,
+            spin,
+            scan_time: 0.0,
+            scanned: false,
+        }
+    }
+
+    pub fn tick(&mut self, frame_time: f32) {
+        self.position += self.velocity * frame_time;
+        self.rotation += self.spin * frame_time;
+    }
+
+    // Fully past an edge and still heading away, so it's crossed the field
+    pub fn has_left(&self, width: f32, height: f32) -> bool {
+        let p = self.position;
+        let outside = p.x < -self.radius
+            || p.y < -self.radius
+            || p.x > width + self.radius
+            || p.y > height + self.radius;
+        let center = Vec2::new(width / 2.0, height / 2.0);
+        outside && (p - center).dot(self.velocity) > 0.0
+    }
+
+    // Boxy body with a solar panel on each side and an antenna, plus the
+    // scanning range and progress until it's been scanned
+    pub fn render(&self, color: Color, scan_range: f32, scan_duration: f32) {
+        // Segments in units of the radius, before rotating
+        const SEGMENTS: [(Vec2, Vec2); 17] = [
+            // Body
+            (vec2(-0.35, -0.25), vec2(0.35, -0.25)),
+            (vec2(0.35, -0.25), vec2(0.35, 0.25)),
+            (vec2(0.35, 0.25), vec2(-0.35, 0.25)),
+            (vec2(-0.35, 0.25), vec2(-0.35, -0.25)),
+            // Struts
+            (vec2(-0.55, 0.0), vec2(-0.35, 0.0)),
+            (vec2(0.35, 0.0), vec2(0.55, 0.0)),
+            // Panels, each with a divider
+            (vec2(-1.0, -0.2), vec2(-0.55, -0.2)),
+            (vec2(-1.0, 0.2), vec2(-0.55, 0.2)),
+            (vec2(-1.0, -0.2), vec2(-1.0, 0.2)),
+            (vec2(-0.55, -0.2), vec2(-0.55, 0.2)),
+            (vec2(-0.775, -0.2), vec2(-0.775, 0.2)),
+            (vec2(0.55, -0.2), vec2(1.0, -0.2)),
+            (vec2(0.55, 0.2), vec2(1.0, 0.2)),
+            (vec2(0.55, -0.2), vec2(0.55, 0.2)),
+            (vec2(1.0, -0.2), vec2(1.0, 0.2)),
+            (vec2(0.775, -0.2), vec2(0.775, 0.2)),
+            // Antenna
+            (vec2(0.0, -0.25), vec2(0.1, -0.6)),
+        ];
+        let turn = Vec2::from_angle(self.rotation);
+        let place = |v: Vec2| self.position + turn.rotate(v * self.radius);
+        for (start, end) in SEGMENTS {
+            let (start, end) = (place(start), place(end));
+            draw_line(start.x, start.y, end.x, end.y, stroke(1.0), color);
+        }
+
+        if self.scanned {
+            return;
+        }
+        let (x, y) = (self.position.x, self.position.y);
+        let faint = Color { a: 0.2, ..SKYBLUE };
+        draw_circle_lines(x, y, scan_range, stroke(1.0), faint);
+        if self.scan_time > 0.0 {
+            let progress = (self.scan_time / scan_duration).min(1.0);
+            draw_arc(
+                x,
+                y,
+                32,
+                self.radius + 8.0,
+                270.0,
+                stroke(2.0),
+                360.0 * progress,
+                SKYBLUE,
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Spawn director: instead of topping the field up to a fixed count, it earns
+// a difficulty budget over time and spends it on what to send in next, so the
+// pressure comes in varied bursts with lulls between them. It only decides,
+// the game carries its purchases out.
+use crate::balance::Balance;
+use crate::rng::Rng;
+use crate::SizeClass;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Purchase {
+    Asteroid(SizeClass),
+    GoldRush,
+}
+impl Purchase {
+    const ALL: [Purchase; 5] = [
+        Purchase::Asteroid(SizeClass::Small),
+        Purchase::Asteroid(SizeClass::Medium),
+        Purchase::Asteroid(SizeClass::Large),
+        Purchase::Asteroid(SizeClass::Huge),
+        Purchase::GoldRush,
+    ];
+
+    // Budget cost, relative chance of being picked and seconds before the same
+    // kind of purchase can be made again, from the balance data
+    fn terms(self, balance: &Balance) -> (f32, f32, f32) {
+        match self {
+            Purchase::Asteroid(size) => {
+                let i = SizeClass::ALL.iter().position(|s| *s == size).unwrap_or(0);
+                (
+                    balance.director_asteroid_costs[i],
+                    balance.size_weights[i],
+                    balance.director_asteroid_cooldown,
+                )
+            }
+            Purchase::GoldRush => (
+                balance.director_gold_rush_cost,
+                balance.director_gold_rush_weight,
+                balance.director_gold_rush_cooldown,
+            ),
+        }
+    }
+
+    // Cooldowns are kept per kind, all asteroid sizes share one
+    fn slot(self) -> usize {
+        match self {
+            Purchase::Asteroid(_) => 0,
+            Purchase::GoldRush => 1,
+        }
+    }
+}
+
+// What the game allows the director to buy right now, like room on the field
+pub struct Allowed {
+    pub asteroid: bool,
+    pub gold_rush: bool,
+}
+
+#[derive(Clone)]
+pub struct SpawnDirector {
+    pub budget: f32,
+    // Its own stream so its rolls don't shift the rest of the run's
+    rng: Rng,
+    cooldowns: [f32; 2],
+    pub last: Option<Purchase>,
+}
+impl SpawnDirector {
+    pub fn new(seed: u64) -> SpawnDirector {
+        SpawnDirector {
+            budget: 0.0,
+            rng: Rng::new(seed ^ 0x5eed_d12e_c702),
+            cooldowns: [0.0; 2],
+            last: None,
+        }
+    }
+
+    // Earn budget for `frame_time` seconds, `elapsed` into the run, and make
+    // at most one purchase from what's affordable, allowed and off cooldown
+    pub fn tick(
+        &mut self,
+        frame_time: f32,
+        elapsed: f32,
+        balance: &Balance,
+        allowed: Allowed,
+    ) -> Option<Purchase> {
+        let rate = balance.director_rate + balance.director_rate_per_minute * elapsed / 60.0;
+        self.budget = (self.budget + rate * frame_time).min(balance.director_budget_max);
+        for cooldown in self.cooldowns.iter_mut() {
+            *cooldown = (*cooldown - frame_time).max(0.0);
+        }
+
+        let options: Vec<(Purchase, f32)> = Purchase::ALL
+            .into_iter()
+            .filter(|p| match p {
+                Purchase::Asteroid(_) => allowed.asteroid,
+                Purchase::GoldRush => allowed.gold_rush,
+            })
+            .filter(|p| self.cooldowns[p.slot()] <= 0.0)
+            .filter_map(|p| {
+                let (cost, weight, _) = p.terms(balance);
+                (cost <= self.budget && weight > 0.0).then_some((p, weight))
+            })
+            .collect();
+        let total: f32 = options.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut pick = self.rng.gen_range(0.0, total);
+        let purchase = options
+            .iter()
+            .find(|(_, weight)| {
+                pick -= weight;
+                pick < 0.0
+            })
+            .or(options.last())
+            .map(|(p, _)| *p)?;
+
+        let (cost, _, cooldown) = purchase.terms(balance);
+        self.budget -= cost;
+        self.cooldowns[purchase.slot()] = cooldown;
+        self.last = Some(purchase);
+        Some(purchase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    fn anything() -> Allowed {
+        Allowed {
+            asteroid: true,
+            gold_rush: true,
+        }
+    }
+
+    #[test]
+    fn purchases_never_cost_more_than_the_budget() {
+        let balance = Balance::default();
+        let mut director = SpawnDirector::new(3);
+        let mut bought = 0;
+        for frame in 0..60 * 600 {
+            let elapsed = frame as f32 * FRAME;
+            let rate = balance.director_rate + balance.director_rate_per_minute * elapsed / 60.0;
+            let available = (director.budget + rate * FRAME).min(balance.director_budget_max);
+            if let Some(purchase) = director.tick(FRAME, elapsed, &balance, anything()) {
+                let (cost, _, _) = purchase.terms(&balance);
+                assert!(
+                    cost <= available,
+                    "{:?} for {} out of {}",
+                    purchase,
+                    cost,
+                    available
+                );
+                bought += 1;
+            }
+            assert!(director.budget >= 0.0);
+            assert!(director.budget <= balance.director_budget_max);
+        }
+        assert!(bought > 100);
+    }
+
+    #[test]
+    fn nothing_is_bought_when_nothing_is_allowed() {
+        let balance = Balance::default();
+        let mut director = SpawnDirector::new(3);
+        director.budget = balance.director_budget_max;
+        for _ in 0..600 {
+            let allowed = Allowed {
+                asteroid: false,
+                gold_rush: false,
+            };
+            assert_eq!(director.tick(FRAME, 0.0, &balance, allowed), None);
+        }
+        assert_eq!(director.budget, balance.director_budget_max);
+    }
+
+    #[test]
+    fn each_kind_waits_out_its_own_cooldown() {
+        let balance = Balance::default();
+        let mut director = SpawnDirector::new(3);
+        let asteroids_only = || Allowed {
+            asteroid: true,
+            gold_rush: false,
+        };
+        let mut last_asteroid: Option<f32> = None;
+        let mut asteroids = 0;
+        for frame in 0..60 * 60 {
+            let now = frame as f32 * FRAME;
+            // Kept topped up, so only the cooldown holds it back
+            director.budget = balance.director_budget_max;
+            if let Some(Purchase::Asteroid(_)) =
+                director.tick(FRAME, now, &balance, asteroids_only())
+            {
+                if let Some(last) = last_asteroid {
+                    assert!(now - last >= balance.director_asteroid_cooldown - 1e-3);
+                }
+                last_asteroid = Some(now);
+                asteroids += 1;
+            }
+        }
+        assert!(asteroids > 100);
+
+        // An asteroid on cooldown doesn't hold up a gold rush, and that one then waits its own
+        director.budget = balance.director_budget_max;
+        director.cooldowns = [balance.director_asteroid_cooldown, 0.0];
+        assert_eq!(
+            director.tick(0.0, 0.0, &balance, anything()),
+            Some(Purchase::GoldRush)
+        );
+        let mut gold_rushes = 0;
+        for _ in 0..(balance.director_gold_rush_cooldown / FRAME) as usize - 1 {
+            director.budget = balance.director_budget_max;
+            if director.tick(FRAME, 0.0, &balance, anything()) == Some(Purchase::GoldRush) {
+                gold_rushes += 1;
+            }
+        }
+        assert_eq!(gold_rushes, 0);
+    }
+}
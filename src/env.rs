@@ -0,0 +1,194 @@
+// Headless stepping for agents: discrete actions in, the score and whether the
+// run is done out, on a fixed-size field with a fixed time step. Nothing is
+// drawn and the same seed always plays out the same way.
+
+use crate::rng::Rng;
+use crate::settings::{BoundaryMode, Settings};
+use crate::{Game, GameMode, Input};
+use macroquad::prelude::*;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Idle,
+    Forward,
+    Backward,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    ForwardFire,
+    RotateLeftFire,
+    RotateRightFire,
+}
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::Idle,
+        Action::Forward,
+        Action::Backward,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Fire,
+        Action::ForwardFire,
+        Action::RotateLeftFire,
+        Action::RotateRightFire,
+    ];
+
+    fn input(self) -> Input {
+        let fire = matches!(
+            self,
+            Action::Fire | Action::ForwardFire | Action::RotateLeftFire | Action::RotateRightFire
+        );
+        Input {
+            forward: matches!(self, Action::Forward | Action::ForwardFire),
+            backward: self == Action::Backward,
+            rotate_left: matches!(self, Action::RotateLeft | Action::RotateLeftFire),
+            rotate_right: matches!(self, Action::RotateRight | Action::RotateRightFire),
+            fire,
+            ..Input::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EnvConfig {
+    pub mode: GameMode,
+    // Walls by default, in the open field an agent learns to fly away and hide
+    pub boundary: BoundaryMode,
+    pub width: f32,
+    pub height: f32,
+    // Steps before a run is cut off as done, however it's going
+    pub max_steps: u32,
+    // Seconds simulated per step
+    pub frame_time: f32,
+}
+impl Default for EnvConfig {
+    fn default() -> EnvConfig {
+        EnvConfig {
+            mode: GameMode::Classic,
+            boundary: BoundaryMode::Walls,
+            width: 1280.0,
+            height: 720.0,
+            max_steps: 60 * 60 * 5,
+            frame_time: 1.0 / 60.0,
+        }
+    }
+}
+
+// What a step leaves behind. Observations for agents outside the game wait
+// for the lib and bin split, see todo.txt.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Observation {
+    pub score: f32,
+    // The run is over, or it was cut off at `EnvConfig::max_steps`
+    pub done: bool,
+}
+
+pub struct Env {
+    game: Game,
+    config: EnvConfig,
+    seed: u64,
+    steps: u32,
+}
+impl Env {
+    pub fn new(seed: u64, config: EnvConfig) -> Env {
+        // Defaults rather than the player's saved settings, with cosmetic effects
+        // tied to the seed and no background to build
+        let settings = Settings {
+            deterministic_effects: true,
+            log_events: false,
+            star_density: 0.0,
+            boundary: config.boundary,
+            ..Settings::default()
+        };
+        let mut game = Game::with_size(seed, settings, config.width, config.height);
+        game.mode = config.mode;
+        let mut env = Env {
+            game,
+            config,
+            seed,
+            steps: 0,
+        };
+        env.reset();
+        env
+    }
+
+    // Start over on the same seed
+    pub fn reset(&mut self) -> Observation {
+        self.game.reset(self.seed);
+        self.steps = 0;
+        self.observe()
+    }
+
+    // Start over on a new seed
+    pub fn reseed(&mut self, seed: u64) -> Observation {
+        self.seed = seed;
+        self.reset()
+    }
+
+    // A finished run stays finished until it's reset
+    pub fn step(&mut self, action: Action) -> Observation {
+        if self.is_done() {
+            return self.observe();
+        }
+        self.steps += 1;
+        self.game.tick(self.config.frame_time, &action.input());
+        // Drained so a long session doesn't collect them
+        self.game.events.clear();
+        self.observe()
+    }
+
+    fn is_done(&self) -> bool {
+        self.game.is_over() || self.steps >= self.config.max_steps
+    }
+
+    fn observe(&self) -> Observation {
+        Observation {
+            score: self.game.score,
+            done: self.is_done(),
+        }
+    }
+}
+
+// Random agent for `steps` steps, printing the spread of final scores and the
+// simulation speed
+pub fn run_random_agent(steps: u64, seed: u64) {
+    let mut env = Env::new(seed, EnvConfig::default());
+    let mut rng = Rng::new(seed ^ 0xa076_1d64_78bd_642f);
+    let mut scores = vec![];
+    let mut unfinished = None;
+    let start = Instant::now();
+    for _ in 0..steps {
+        let action = Action::ALL[rng.next_u32() as usize % Action::ALL.len()];
+        let observation = env.step(action);
+        if observation.done {
+            scores.push(observation.score);
+            env.reseed(rng.next_u32() as u64);
+            unfinished = None;
+        } else {
+            unfinished = Some(observation.score);
+        }
+    }
+    // The run cut short by the step count counts too
+    scores.extend(unfinished);
+    let elapsed = start.elapsed().as_secs_f32();
+
+    println!(
+        "{} steps in {:.2}s ({:.0} steps/s)",
+        steps,
+        elapsed,
+        steps as f32 / elapsed
+    );
+    if scores.is_empty() {
+        return;
+    }
+    scores.sort_by(f32::total_cmp);
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    println!(
+        "{} runs, score min {:.0} median {:.0} mean {:.1} max {:.0}",
+        scores.len(),
+        scores[0],
+        scores[scores.len() / 2],
+        mean,
+        scores[scores.len() - 1]
+    );
+}
@@ -0,0 +1,125 @@
+use crate::SizeClass;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+// Notable things that happen during a run, reported by the game for the HUD feed
+#[derive(Clone, Copy)]
+pub enum GameEvent {
+    AsteroidDestroyed { size: SizeClass, points: f32 },
+    ShipHit { health: usize },
+    Repaired,
+    WaveStarted(u32),
+    // A broken asteroid came apart into its pieces
+    AsteroidSplit,
+    DerelictDestroyed { points: f32 },
+    DerelictScanned { points: f32 },
+}
+
+// Text and color for an event, or None if it isn't worth a line
+fn format(event: GameEvent) -> Option<(String, Color)> {
+    match event {
+        GameEvent::AsteroidDestroyed { size, points } => {
+            // Small ones go too fast to be interesting
+            if size == SizeClass::Small {
+                return None;
+            }
+            let name = format!("{:?}", size).to_uppercase();
+            Some((format!("Destroyed {} asteroid +{}", name, points), WHITE))
+        }
+        GameEvent::ShipHit { health } => Some((format!("Hull hit, {} left", health), RED)),
+        GameEvent::Repaired => Some((String::from("Hull repaired"), GREEN)),
+        GameEvent::WaveStarted(wave) => Some((format!("Wave {} started", wave), SKYBLUE)),
+        GameEvent::AsteroidSplit => None,
+        GameEvent::DerelictDestroyed { points } => {
+            Some((format!("Derelict destroyed +{}", points), ORANGE))
+        }
+        GameEvent::DerelictScanned { points } => {
+            Some((format!("Derelict scanned +{}", points), SKYBLUE))
+        }
+    }
+}
+
+#[derive(Clone)]
+struct FeedEntry {
+    text: String,
+    color: Color,
+    // Seconds since it was shown
+    age: f32,
+}
+
+// Ticker of recent events in the top right. Bursts wait in `pending` and are
+// let in one at a time, pushing the oldest line out early if it's full.
+#[derive(Clone, Default)]
+pub struct EventFeed {
+    shown: VecDeque<FeedEntry>,
+    pending: VecDeque<FeedEntry>,
+    // Seconds until the next pending entry can be shown
+    next_in: f32,
+    // Every event pushed since they were last taken, worth a line or not
+    reported: Vec<GameEvent>,
+}
+impl EventFeed {
+    const MAX_SHOWN: usize = 4;
+    const MAX_PENDING: usize = 8;
+    const LIFETIME: f32 = 3.0;
+    const SLIDE_DURATION: f32 = 0.2;
+    const FADE_DURATION: f32 = 0.5;
+    const INTERVAL: f32 = 0.15;
+
+    pub fn push(&mut self, event: GameEvent) {
+        self.reported.push(event);
+        let Some((text, color)) = format(event) else {
+            return;
+        };
+        if self.pending.len() == Self::MAX_PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(FeedEntry {
+            text,
+            color,
+            age: 0.0,
+        });
+    }
+
+    pub fn take_reported(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.reported)
+    }
+
+    pub fn tick(&mut self, frame_time: f32) {
+        for entry in self.shown.iter_mut() {
+            entry.age += frame_time;
+        }
+        self.shown.retain(|e| e.age < Self::LIFETIME);
+
+        self.next_in -= frame_time;
+        if self.pending.is_empty() || self.next_in > 0.0 {
+            return;
+        }
+        if self.shown.len() == Self::MAX_SHOWN {
+            // Start fading the oldest line to make room
+            let oldest = &mut self.shown[0];
+            oldest.age = oldest.age.max(Self::LIFETIME - Self::FADE_DURATION);
+            return;
+        }
+        self.shown.extend(self.pending.pop_front());
+        self.next_in = Self::INTERVAL;
+    }
+
+    // Right-aligned to `right`, newest line at the bottom
+    pub fn render(&self, right: f32, top: f32) {
+        let font_size = crate::ui(20.0);
+        for (i, entry) in self.shown.iter().enumerate() {
+            let slide = (entry.age / Self::SLIDE_DURATION).min(1.0);
+            let fade = ((Self::LIFETIME - entry.age) / Self::FADE_DURATION).clamp(0.0, 1.0);
+            let width = measure_text(&entry.text, None, font_size as u16, 1.0).width;
+            // Slides in from past the right edge
+            let x = right - width + (1.0 - slide) * (width + 20.0);
+            let y = top + i as f32 * font_size * 1.2;
+            let color = Color {
+                a: entry.color.a * fade,
+                ..entry.color
+            };
+            draw_text(&entry.text, x, y, font_size, color);
+        }
+    }
+}
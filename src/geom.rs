@@ -0,0 +1,418 @@
+use macroquad::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+pub fn distance(a: Vec2, b: Vec2) -> f32 {
+    distance_sq(a, b).sqrt()
+}
+
+pub fn distance_sq(a: Vec2, b: Vec2) -> f32 {
+    (b - a).length_squared()
+}
+
+// Wrap an angle in radians into [-PI, PI)
+pub fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+// Rotate `point` by `angle` radians around `center`
+pub fn rotate_around(point: Vec2, center: Vec2, angle: f32) -> Vec2 {
+    center + Vec2::from_angle(angle).rotate(point - center)
+}
+
+pub fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_sq = segment.length_squared();
+    if length_sq == 0.0 {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_sq).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+// Whether any part of the segment from `start` to `end` is inside the circle
+pub fn segment_circle_intersect(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> bool {
+    distance_sq(closest_point_on_segment(center, start, end), center) < radius * radius
+}
+
+// Points on an edge count as inside. A triangle with no area only contains
+// points lying on its edges.
+pub fn point_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    if (b - a).perp_dot(c - a) == 0.0 {
+        return [(a, b), (b, c), (c, a)].iter().any(|&(start, end)| {
+            distance_sq(closest_point_on_segment(point, start, end), point) == 0.0
+        });
+    }
+
+    // The point is inside when it's on the same side of all three edges
+    let d1 = (b - a).perp_dot(point - a);
+    let d2 = (c - b).perp_dot(point - b);
+    let d3 = (a - c).perp_dot(point - c);
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+// Where a circle touches an arc of `arc_radius` around `center` spanning
+// `half_angle` radians either side of `facing`, as the arc's outward normal at
+// the contact. The circle can reach a little past the arc's ends by its own size.
+pub fn circle_hits_arc(
+    circle: Vec2,
+    radius: f32,
+    center: Vec2,
+    arc_radius: f32,
+    facing: f32,
+    half_angle: f32,
+) -> Option<Vec2> {
+    let offset = circle - center;
+    let distance = offset.length();
+    if distance == 0.0 || (distance - arc_radius).abs() > radius {
+        return None;
+    }
+    let slack = (radius / distance).min(1.0).asin();
+    let angle = wrap_angle(offset.to_angle() - facing);
+    (angle.abs() <= half_angle + slack).then(|| offset / distance)
+}
+
+// Mirror `v` about a surface with the given normal, which doesn't need to be normalized
+pub fn reflect(v: Vec2, normal: Vec2) -> Vec2 {
+    let n = normal.normalize_or_zero();
+    v - 2.0 * v.dot(n) * n
+}
+
+// Interpolate from angle `a` to `b` (radians) the short way around the circle
+pub fn angle_lerp_shortest(a: f32, b: f32, t: f32) -> f32 {
+    a + wrap_angle(b - a) * t
+}
+
+// Turn `velocity` toward `target_direction` by at most `max_turn` radians, keeping its speed
+pub fn steer_towards(velocity: Vec2, target_direction: Vec2, max_turn: f32) -> Vec2 {
+    let speed = velocity.length();
+    if speed == 0.0 || target_direction == Vec2::ZERO {
+        return velocity;
+    }
+    let current = velocity.to_angle();
+    let turn = wrap_angle(target_direction.to_angle() - current).clamp(-max_turn, max_turn);
+    Vec2::from_angle(current + turn) * speed
+}
+
+// Shortest displacement from `a` to `b` on a field that wraps around at its edges
+pub fn torus_delta(a: Vec2, b: Vec2, width: f32, height: f32) -> Vec2 {
+    let d = b - a;
+    Vec2::new(
+        d.x - width * (d.x / width).round(),
+        d.y - height * (d.y / height).round(),
+    )
+}
+
+pub fn torus_distance(a: Vec2, b: Vec2, width: f32, height: f32) -> f32 {
+    torus_delta(a, b, width, height).length()
+}
+
+// Offsets to draw a shape at so it shows on both sides of any wrapping edge it
+// overlaps, starting with the shape itself. Corners get up to 4 copies.
+pub fn wrap_offsets(position: Vec2, radius: f32, width: f32, height: f32) -> Vec<Vec2> {
+    let mut xs = vec![0.0];
+    if position.x - radius < 0.0 {
+        xs.push(width);
+    } else if position.x + radius > width {
+        xs.push(-width);
+    }
+    let mut ys = vec![0.0];
+    if position.y - radius < 0.0 {
+        ys.push(height);
+    } else if position.y + radius > height {
+        ys.push(-height);
+    }
+
+    ys.iter()
+        .flat_map(|&y| xs.iter().map(move |&x| Vec2::new(x, y)))
+        .collect()
+}
+
+// Seconds until a circle of `radius` starting at `center` and moving at `velocity`
+// first touches `point`, 0 if it already covers it, or None if it doesn't
+// within `horizon`
+pub fn earliest_approach(
+    point: Vec2,
+    center: Vec2,
+    velocity: Vec2,
+    radius: f32,
+    horizon: f32,
+) -> Option<f32> {
+    let offset = center - point;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    // Smallest t with |offset + velocity * t| = radius
+    let a = velocity.length_squared();
+    let b = 2.0 * offset.dot(velocity);
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || b >= 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (t <= horizon).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < EPSILON
+    }
+
+    fn close_vec(a: Vec2, b: Vec2) -> bool {
+        close(a.x, b.x) && close(a.y, b.y)
+    }
+
+    #[test]
+    fn distances() {
+        assert!(close(distance(vec2(0.0, 0.0), vec2(3.0, 4.0)), 5.0));
+        assert!(close(distance_sq(vec2(1.0, 1.0), vec2(4.0, 5.0)), 25.0));
+        // Identical points
+        let p = vec2(7.0, -2.0);
+        assert_eq!(distance(p, p), 0.0);
+        assert_eq!(distance_sq(p, p), 0.0);
+    }
+
+    #[test]
+    fn wrap_angle_stays_in_range() {
+        assert!(close(wrap_angle(0.0), 0.0));
+        assert!(close(wrap_angle(TAU), 0.0));
+        assert!(close(wrap_angle(3.0 * PI / 2.0), -PI / 2.0));
+        assert!(close(wrap_angle(-3.0 * PI / 2.0), PI / 2.0));
+        // The range is half open, so both ends of it land on -PI
+        assert!(close(wrap_angle(PI), -PI));
+        assert!(close(wrap_angle(-PI), -PI));
+        for i in -20..=20 {
+            let wrapped = wrap_angle(i as f32 * 0.7);
+            assert!((-PI..PI).contains(&wrapped), "{} -> {}", i, wrapped);
+        }
+    }
+
+    #[test]
+    fn rotate_around_center() {
+        let center = vec2(1.0, 1.0);
+        let rotated = rotate_around(vec2(2.0, 1.0), center, PI / 2.0);
+        assert!(close_vec(rotated, vec2(1.0, 2.0)));
+        assert!(close_vec(
+            rotate_around(vec2(2.0, 1.0), center, TAU),
+            vec2(2.0, 1.0)
+        ));
+        // The center itself doesn't move
+        assert!(close_vec(rotate_around(center, center, 1.3), center));
+    }
+
+    #[test]
+    fn closest_point_on_segment_clamps_to_ends() {
+        let (start, end) = (vec2(0.0, 0.0), vec2(10.0, 0.0));
+        assert!(close_vec(
+            closest_point_on_segment(vec2(5.0, 3.0), start, end),
+            vec2(5.0, 0.0)
+        ));
+        assert!(close_vec(
+            closest_point_on_segment(vec2(-5.0, 3.0), start, end),
+            start
+        ));
+        assert!(close_vec(
+            closest_point_on_segment(vec2(15.0, -3.0), start, end),
+            end
+        ));
+    }
+
+    #[test]
+    fn closest_point_on_zero_length_segment() {
+        let p = vec2(2.0, 2.0);
+        assert_eq!(closest_point_on_segment(vec2(9.0, 9.0), p, p), p);
+    }
+
+    #[test]
+    fn segment_circle_intersection() {
+        let (start, end) = (vec2(-10.0, 0.0), vec2(10.0, 0.0));
+        assert!(segment_circle_intersect(start, end, vec2(0.0, 4.0), 5.0));
+        assert!(!segment_circle_intersect(start, end, vec2(0.0, 6.0), 5.0));
+        // Past the end of the segment
+        assert!(!segment_circle_intersect(start, end, vec2(16.0, 0.0), 5.0));
+        // Touching exactly doesn't count
+        assert!(!segment_circle_intersect(start, end, vec2(0.0, 5.0), 5.0));
+    }
+
+    #[test]
+    fn zero_length_segment_against_circle() {
+        let p = vec2(3.0, 0.0);
+        assert!(segment_circle_intersect(p, p, Vec2::ZERO, 5.0));
+        assert!(!segment_circle_intersect(p, p, Vec2::ZERO, 2.0));
+    }
+
+    #[test]
+    fn point_in_triangle_inside_outside_and_edges() {
+        let (a, b, c) = (vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0));
+        assert!(point_in_triangle(vec2(2.0, 2.0), a, b, c));
+        assert!(!point_in_triangle(vec2(8.0, 8.0), a, b, c));
+        assert!(point_in_triangle(vec2(5.0, 0.0), a, b, c));
+        assert!(point_in_triangle(a, a, b, c));
+        // Winding doesn't matter
+        assert!(point_in_triangle(vec2(2.0, 2.0), a, c, b));
+    }
+
+    #[test]
+    fn point_in_degenerate_triangle() {
+        // Collinear corners: only points on the line count
+        let (a, b, c) = (vec2(0.0, 0.0), vec2(5.0, 0.0), vec2(10.0, 0.0));
+        assert!(point_in_triangle(vec2(7.0, 0.0), a, b, c));
+        assert!(!point_in_triangle(vec2(7.0, 1.0), a, b, c));
+        // All three corners in one place
+        let p = vec2(1.0, 1.0);
+        assert!(point_in_triangle(p, p, p, p));
+        assert!(!point_in_triangle(vec2(1.0, 2.0), p, p, p));
+    }
+
+    #[test]
+    fn circle_hits_arc_in_front_only() {
+        let center = Vec2::ZERO;
+        // Right on the arc straight ahead
+        let normal = circle_hits_arc(vec2(50.0, 0.0), 5.0, center, 50.0, 0.0, 0.5);
+        assert!(close_vec(normal.unwrap(), vec2(1.0, 0.0)));
+        // Behind the ship
+        assert!(circle_hits_arc(vec2(-50.0, 0.0), 5.0, center, 50.0, 0.0, 0.5).is_none());
+        // Too far in or out
+        assert!(circle_hits_arc(vec2(30.0, 0.0), 5.0, center, 50.0, 0.0, 0.5).is_none());
+        assert!(circle_hits_arc(vec2(70.0, 0.0), 5.0, center, 50.0, 0.0, 0.5).is_none());
+        // Facing across the -PI/PI seam
+        assert!(circle_hits_arc(vec2(-50.0, 0.0), 5.0, center, 50.0, PI, 0.5).is_some());
+        assert!(circle_hits_arc(vec2(-50.0, 0.0), 5.0, center, 50.0, -PI, 0.5).is_some());
+    }
+
+    #[test]
+    fn circle_on_arc_center_misses() {
+        assert!(circle_hits_arc(Vec2::ZERO, 100.0, Vec2::ZERO, 50.0, 0.0, PI).is_none());
+    }
+
+    #[test]
+    fn reflect_off_normal() {
+        assert!(close_vec(
+            reflect(vec2(1.0, -1.0), vec2(0.0, 1.0)),
+            vec2(1.0, 1.0)
+        ));
+        // The normal doesn't need to be unit length
+        assert!(close_vec(
+            reflect(vec2(1.0, -1.0), vec2(0.0, 20.0)),
+            vec2(1.0, 1.0)
+        ));
+        // Moving along the surface is unchanged
+        assert!(close_vec(
+            reflect(vec2(3.0, 0.0), vec2(0.0, 1.0)),
+            vec2(3.0, 0.0)
+        ));
+        // A zero normal leaves it alone
+        assert_eq!(reflect(vec2(1.0, 2.0), Vec2::ZERO), vec2(1.0, 2.0));
+    }
+
+    #[test]
+    fn angle_lerp_goes_the_short_way() {
+        assert!(close(angle_lerp_shortest(0.0, 1.0, 0.5), 0.5));
+        assert!(close(angle_lerp_shortest(0.3, 1.0, 0.0), 0.3));
+        assert!(close(angle_lerp_shortest(0.3, 1.0, 1.0), 1.0));
+        // From just under PI to just over -PI crosses the seam, not zero
+        let a = PI - 0.1;
+        let b = -PI + 0.1;
+        let halfway = angle_lerp_shortest(a, b, 0.5);
+        assert!(close(wrap_angle(halfway), -PI) || close(wrap_angle(halfway), PI));
+        let end = angle_lerp_shortest(a, b, 1.0);
+        assert!(close(wrap_angle(end), b));
+        // Identical angles stay put
+        assert!(close(angle_lerp_shortest(2.0, 2.0, 0.7), 2.0));
+    }
+
+    #[test]
+    fn steer_towards_limits_the_turn() {
+        let velocity = vec2(10.0, 0.0);
+        let steered = steer_towards(velocity, vec2(0.0, 1.0), 0.1);
+        assert!(close(steered.length(), 10.0));
+        assert!(close(steered.to_angle(), 0.1));
+        // Small enough turns land exactly on the target
+        let steered = steer_towards(velocity, vec2(0.0, -3.0), PI);
+        assert!(close_vec(steered, vec2(0.0, -10.0)));
+        // Turning to face straight behind, across the seam
+        let steered = steer_towards(vec2(-10.0, 0.1), vec2(-1.0, -0.1), 0.5);
+        assert!(close(steered.length(), vec2(-10.0, 0.1).length()));
+        assert!(steered.y < 0.0);
+    }
+
+    #[test]
+    fn steer_towards_degenerate_inputs() {
+        assert_eq!(steer_towards(Vec2::ZERO, vec2(1.0, 0.0), 1.0), Vec2::ZERO);
+        assert_eq!(
+            steer_towards(vec2(1.0, 2.0), Vec2::ZERO, 1.0),
+            vec2(1.0, 2.0)
+        );
+        assert!(close_vec(
+            steer_towards(vec2(1.0, 2.0), vec2(0.0, 1.0), 0.0),
+            vec2(1.0, 2.0)
+        ));
+    }
+
+    #[test]
+    fn torus_takes_the_short_way_round() {
+        let (width, height) = (100.0, 50.0);
+        let delta = torus_delta(vec2(95.0, 5.0), vec2(5.0, 45.0), width, height);
+        assert!(close_vec(delta, vec2(10.0, -10.0)));
+        assert!(close(
+            torus_distance(vec2(95.0, 5.0), vec2(5.0, 45.0), width, height),
+            200f32.sqrt()
+        ));
+        // Nearby points don't wrap
+        let delta = torus_delta(vec2(10.0, 10.0), vec2(20.0, 5.0), width, height);
+        assert!(close_vec(delta, vec2(10.0, -5.0)));
+        let p = vec2(30.0, 30.0);
+        assert_eq!(torus_distance(p, p, width, height), 0.0);
+    }
+
+    #[test]
+    fn wrap_offsets_copy_across_edges() {
+        let (width, height) = (100.0, 100.0);
+        assert_eq!(
+            wrap_offsets(vec2(50.0, 50.0), 10.0, width, height),
+            vec![Vec2::ZERO]
+        );
+        assert_eq!(
+            wrap_offsets(vec2(5.0, 50.0), 10.0, width, height),
+            vec![Vec2::ZERO, vec2(width, 0.0)]
+        );
+        assert_eq!(
+            wrap_offsets(vec2(50.0, 95.0), 10.0, width, height),
+            vec![Vec2::ZERO, vec2(0.0, -height)]
+        );
+        let corner = wrap_offsets(vec2(95.0, 5.0), 10.0, width, height);
+        assert_eq!(corner.len(), 4);
+        assert!(corner.contains(&vec2(-width, height)));
+    }
+
+    #[test]
+    fn earliest_approach_times() {
+        // Moving straight at the point: touches once the gap closes to the radius
+        let t = earliest_approach(Vec2::ZERO, vec2(100.0, 0.0), vec2(-10.0, 0.0), 10.0, 20.0);
+        assert!(close(t.unwrap(), 9.0));
+        // Already covering it
+        assert_eq!(
+            earliest_approach(Vec2::ZERO, vec2(5.0, 0.0), Vec2::ZERO, 10.0, 1.0),
+            Some(0.0)
+        );
+        // Moving away, standing still, passing wide, or arriving after the horizon
+        assert!(
+            earliest_approach(Vec2::ZERO, vec2(100.0, 0.0), vec2(10.0, 0.0), 10.0, 20.0).is_none()
+        );
+        assert!(earliest_approach(Vec2::ZERO, vec2(100.0, 0.0), Vec2::ZERO, 10.0, 20.0).is_none());
+        assert!(
+            earliest_approach(Vec2::ZERO, vec2(100.0, 50.0), vec2(-10.0, 0.0), 10.0, 20.0)
+                .is_none()
+        );
+        assert!(
+            earliest_approach(Vec2::ZERO, vec2(100.0, 0.0), vec2(-10.0, 0.0), 10.0, 5.0).is_none()
+        );
+    }
+}
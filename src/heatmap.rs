@@ -0,0 +1,170 @@
+// Where the ship spent a run and where it was hit, for the results and the
+// History screen. Positions are fractions of the field so runs played at
+// different window sizes line up on the same grid.
+use crate::timer::RepeatingTimer;
+use macroquad::prelude::*;
+
+const COLUMNS: usize = 32;
+const ROWS: usize = 18;
+// Seconds between samples of the ship's position
+const SAMPLE_INTERVAL: f32 = 0.25;
+
+#[derive(Clone, Default)]
+pub struct Heatmap {
+    // Samples per cell, row by row, empty until the first sample
+    cells: Vec<u16>,
+    sample: RepeatingTimer,
+    // Where each hit was taken, and the fatal one if the ship was destroyed
+    hits: Vec<Vec2>,
+    death: Option<Vec2>,
+}
+impl Heatmap {
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.hits.is_empty() && self.death.is_none()
+    }
+
+    // Sample `at`, a fraction of the field, every `SAMPLE_INTERVAL`
+    pub fn tick(&mut self, frame_time: f32, at: Vec2) {
+        let samples = self.sample.tick(frame_time, SAMPLE_INTERVAL);
+        if samples == 0 {
+            return;
+        }
+        if self.cells.is_empty() {
+            self.cells = vec![0; COLUMNS * ROWS];
+        }
+        let column = ((at.x * COLUMNS as f32) as usize).min(COLUMNS - 1);
+        let row = ((at.y * ROWS as f32) as usize).min(ROWS - 1);
+        let cell = &mut self.cells[row * COLUMNS + column];
+        *cell = cell.saturating_add(samples as u16);
+    }
+
+    pub fn mark_hit(&mut self, at: Vec2, fatal: bool) {
+        if fatal {
+            self.death = Some(at);
+        } else {
+            self.hits.push(at);
+        }
+    }
+
+    // "cells;hits;death" for the run history. Cells are counts separated by
+    // `.` with runs of empty ones written as `z` and their number, positions
+    // are thousandths of the field as `x-y`.
+    pub fn to_field(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut cells: Vec<String> = vec![];
+        let mut empty = 0;
+        for &count in &self.cells {
+            if count == 0 {
+                empty += 1;
+                continue;
+            }
+            if empty > 0 {
+                cells.push(format!("z{}", empty));
+                empty = 0;
+            }
+            cells.push(count.to_string());
+        }
+        if empty > 0 {
+            cells.push(format!("z{}", empty));
+        }
+        let position = |p: &Vec2| {
+            format!(
+                "{}-{}",
+                (p.x.clamp(0.0, 1.0) * 1000.0) as u32,
+                (p.y.clamp(0.0, 1.0) * 1000.0) as u32
+            )
+        };
+        let hits: Vec<String> = self.hits.iter().map(position).collect();
+        format!(
+            "{};{};{}",
+            cells.join("."),
+            hits.join("."),
+            self.death.as_ref().map(position).unwrap_or_default()
+        )
+    }
+
+    // Empty if the field is missing or malformed, like for runs from before heatmaps
+    pub fn from_field(field: &str) -> Heatmap {
+        Self::parse(field).unwrap_or_default()
+    }
+
+    fn parse(field: &str) -> Option<Heatmap> {
+        let mut parts = field.split(';');
+        let mut cells = vec![];
+        for entry in parts.next()?.split('.').filter(|e| !e.is_empty()) {
+            match entry.strip_prefix('z') {
+                Some(empty) => cells.resize(cells.len() + empty.parse::<usize>().ok()?, 0),
+                None => cells.push(entry.parse().ok()?),
+            }
+        }
+        if !cells.is_empty() && cells.len() != COLUMNS * ROWS {
+            return None;
+        }
+        let position = |text: &str| -> Option<Vec2> {
+            let (x, y) = text.split_once('-')?;
+            Some(Vec2::new(
+                x.parse::<f32>().ok()? / 1000.0,
+                y.parse::<f32>().ok()? / 1000.0,
+            ))
+        };
+        let hits = parts
+            .next()?
+            .split('.')
+            .filter(|h| !h.is_empty())
+            .map(position)
+            .collect::<Option<Vec<Vec2>>>()?;
+        let death = parts.next().and_then(position);
+        Some(Heatmap {
+            cells,
+            sample: RepeatingTimer::default(),
+            hits,
+            death,
+        })
+    }
+
+    // Thumbnail of the field filling `area`: cells from blue for a short stay
+    // to red for the longest, hits as orange crosses and the death in red
+    pub fn render(&self, area: Rect) {
+        draw_rectangle(
+            area.x,
+            area.y,
+            area.w,
+            area.h,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        draw_rectangle_lines(area.x, area.y, area.w, area.h, 1.0, GRAY);
+        let (cell_w, cell_h) = (area.w / COLUMNS as f32, area.h / ROWS as f32);
+        let most = self.cells.iter().copied().max().unwrap_or(0).max(1) as f32;
+        for (i, &count) in self.cells.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let t = count as f32 / most;
+            let color = Color::new(t, 0.2 * (1.0 - t), 1.0 - t, 0.25 + 0.5 * t);
+            let (column, row) = (i % COLUMNS, i / COLUMNS);
+            draw_rectangle(
+                area.x + column as f32 * cell_w,
+                area.y + row as f32 * cell_h,
+                cell_w,
+                cell_h,
+                color,
+            );
+        }
+
+        let place = |p: Vec2| vec2(area.x + p.x * area.w, area.y + p.y * area.h);
+        let cross = |p: Vec2, size: f32, color: Color| {
+            draw_line(p.x - size, p.y - size, p.x + size, p.y + size, 1.5, color);
+            draw_line(p.x - size, p.y + size, p.x + size, p.y - size, 1.5, color);
+        };
+        for &hit in &self.hits {
+            cross(place(hit), 3.0, ORANGE);
+        }
+        if let Some(death) = self.death {
+            let p = place(death);
+            cross(p, 5.0, RED);
+            draw_circle_lines(p.x, p.y, 8.0, 1.5, RED);
+        }
+    }
+}
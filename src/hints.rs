@@ -0,0 +1,104 @@
+// One-time tips for a player's first few runs, each shown the first time its
+// situation comes up and never again. Only one is on screen at a time, the
+// rest wait their turn.
+use crate::feed::GameEvent;
+use crate::toast::Toast;
+use crate::Game;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Hint {
+    Drift,
+    Split,
+    HitGrace,
+    Strafe,
+}
+impl Hint {
+    const ALL: [Hint; 4] = [Hint::Drift, Hint::Split, Hint::HitGrace, Hint::Strafe];
+
+    // Stored in the profile once shown
+    pub fn id(self) -> &'static str {
+        match self {
+            Hint::Drift => "drift",
+            Hint::Split => "split",
+            Hint::HitGrace => "hit_grace",
+            Hint::Strafe => "strafe",
+        }
+    }
+
+    fn text(self) -> &'static str {
+        match self {
+            Hint::Drift => "You drift! Thrust opposite your motion to slow down",
+            Hint::Split => "Big asteroids split - finish the pieces",
+            Hint::HitGrace => "Nothing can hit you for a moment after a hit - use it to get clear",
+            Hint::Strafe => {
+                "Strafe thrusters fitted - slide sideways without turning (hold Tab for keys)"
+            }
+        }
+    }
+
+    // Whether the hint's situation came up this frame, from the game state and
+    // the events it reported
+    fn is_triggered(self, game: &Game, events: &[GameEvent]) -> bool {
+        match self {
+            Hint::Drift => game.ship_speed > game.player_speed * 0.75,
+            Hint::Split => events.iter().any(|e| matches!(e, GameEvent::AsteroidSplit)),
+            Hint::HitGrace => events
+                .iter()
+                .any(|e| matches!(e, GameEvent::ShipHit { health } if *health > 0)),
+            Hint::Strafe => game.strafe_thrusters,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Hints {
+    pending: VecDeque<Hint>,
+    showing: Option<(Hint, Toast)>,
+}
+impl Hints {
+    // Seconds each hint stays up
+    const DURATION: f32 = 4.0;
+
+    // Queue every hint triggered this frame that hasn't been shown before
+    pub fn check(&mut self, game: &Game, events: &[GameEvent], shown: &[String]) {
+        for hint in Hint::ALL {
+            let queued = self.pending.contains(&hint)
+                || self.showing.as_ref().is_some_and(|(h, _)| *h == hint);
+            if !queued && !shown.iter().any(|id| id == hint.id()) && hint.is_triggered(game, events)
+            {
+                self.pending.push_back(hint);
+            }
+        }
+    }
+
+    // Returns a hint that just came up, for the caller to record as shown
+    pub fn tick(&mut self, frame_time: f32) -> Option<Hint> {
+        if let Some((_, toast)) = &mut self.showing {
+            toast.tick(frame_time);
+            if toast.is_done() {
+                self.showing = None;
+            }
+        }
+        if self.showing.is_some() {
+            return None;
+        }
+        let hint = self.pending.pop_front()?;
+        self.showing = Some((hint, Toast::new(hint.text(), 24, Self::DURATION)));
+        Some(hint)
+    }
+
+    // Low on the screen, out of the way of the announcements in the middle
+    pub fn render(&self) {
+        if let Some((_, toast)) = &self.showing {
+            toast.render(screen_height() * 0.8);
+        }
+    }
+
+    // Dropped when a run ends, hints still waiting get another chance later
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.showing = None;
+    }
+}
@@ -0,0 +1,229 @@
+use crate::bindings::{Bindings, UiAction};
+use crate::keys;
+use crate::profile::RunSummary;
+use crate::{draw_text_h_centered, draw_text_h_centered_color, prompt, ui, weekly};
+use macroquad::prelude::*;
+
+pub enum HistoryAction {
+    Close,
+    // Play the selected run's mode and seed again
+    Retry(RunSummary),
+}
+
+// Scrollable list of past runs off the title screen, newest first, with a
+// detail view for the selected one
+pub struct HistoryBrowser {
+    // Index into the list as shown, so 0 is the newest run
+    selected: usize,
+    // First row on screen
+    scroll: usize,
+    detail: bool,
+}
+impl HistoryBrowser {
+    const TOP: f32 = 140.0;
+    const ROW_HEIGHT: f32 = 32.0;
+    // List column headers and their offsets at 1x. Columns are drawn separately
+    // so they line up without a monospace font.
+    const COLUMNS: [(&'static str, f32); 5] = [
+        ("Date", 0.0),
+        ("Mode", 200.0),
+        ("Score", 340.0),
+        ("Result", 460.0),
+        ("Time", 580.0),
+    ];
+
+    pub fn new() -> HistoryBrowser {
+        HistoryBrowser {
+            selected: 0,
+            scroll: 0,
+            detail: false,
+        }
+    }
+
+    // Rows that fit between the header and the prompt at the bottom
+    fn visible_rows() -> usize {
+        let height = screen_height() - ui(Self::TOP) - ui(80.0);
+        ((height / ui(Self::ROW_HEIGHT)) as usize).max(1)
+    }
+
+    fn run(runs: &[RunSummary], index: usize) -> &RunSummary {
+        &runs[runs.len() - 1 - index]
+    }
+
+    fn can_retry(run: &RunSummary) -> bool {
+        !run.weekly && run.mode != "Versus"
+    }
+
+    pub fn update(&mut self, runs: &[RunSummary]) -> Option<HistoryAction> {
+        if self.detail {
+            let run = Self::run(runs, self.selected);
+            if UiAction::Back.is_pressed() {
+                self.detail = false;
+            } else if keys::is_pressed(KeyCode::R) && Self::can_retry(run) {
+                return Some(HistoryAction::Retry(run.clone()));
+            }
+            return None;
+        }
+
+        if UiAction::Back.is_pressed() {
+            return Some(HistoryAction::Close);
+        }
+        if runs.is_empty() {
+            return None;
+        }
+        let rows = Self::visible_rows();
+        let last = runs.len() - 1;
+        if UiAction::NavUp.is_pressed() {
+            self.selected = self.selected.saturating_sub(1);
+        } else if UiAction::NavDown.is_pressed() {
+            self.selected = (self.selected + 1).min(last);
+        } else if keys::is_pressed(KeyCode::PageUp) {
+            self.selected = self.selected.saturating_sub(rows);
+        } else if keys::is_pressed(KeyCode::PageDown) {
+            self.selected = (self.selected + rows).min(last);
+        } else if UiAction::Confirm.is_pressed() {
+            self.detail = true;
+        }
+        // Keep the selection on screen
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+        None
+    }
+
+    pub fn render(&self, runs: &[RunSummary], bindings: &Bindings) {
+        draw_text_h_centered("Run History", ui(60.0), 40);
+        if runs.is_empty() {
+            draw_text_h_centered_color("No runs yet", screen_height() / 2.0, 28, GRAY);
+        } else if self.detail {
+            self.render_detail(Self::run(runs, self.selected));
+        } else {
+            self.render_list(runs);
+        }
+
+        let hint = if self.detail {
+            if Self::can_retry(Self::run(runs, self.selected)) {
+                "{R} to retry this seed, {Back} to the list"
+            } else {
+                "{Back} to the list"
+            }
+        } else {
+            "{NavUp} / {NavDown} to choose, {Confirm} for details, {Back} to return"
+        };
+        prompt::draw_prompt_centered_at(
+            hint,
+            screen_width() / 2.0,
+            screen_height() - ui(30.0),
+            20,
+            GRAY,
+            bindings,
+        );
+    }
+
+    fn render_list(&self, runs: &[RunSummary]) {
+        let rows = Self::visible_rows();
+        let end = (self.scroll + rows).min(runs.len());
+        let x = (screen_width() / 2.0 - ui(380.0)).max(10.0);
+        for (header, column_x) in Self::COLUMNS {
+            draw_text(
+                header,
+                x + ui(column_x),
+                ui(Self::TOP - 20.0),
+                ui(20.0),
+                GRAY,
+            );
+        }
+        for (row, index) in (self.scroll..end).enumerate() {
+            let run = Self::run(runs, index);
+            let y = ui(Self::TOP + 10.0) + row as f32 * ui(Self::ROW_HEIGHT);
+            let color = if index == self.selected {
+                YELLOW
+            } else {
+                WHITE
+            };
+            let cells = [
+                format_date(run.ended_at),
+                mode_label(run),
+                run.score.to_string(),
+                String::from(outcome(run)),
+                format_duration(run.duration),
+            ];
+            for ((_, column_x), text) in Self::COLUMNS.iter().zip(cells) {
+                draw_text(&text, x + ui(*column_x), y, ui(22.0), color);
+            }
+        }
+        draw_text_h_centered_color(
+            &format!("{}-{} of {}", self.scroll + 1, end, runs.len()),
+            screen_height() - ui(60.0),
+            16,
+            GRAY,
+        );
+    }
+
+    fn render_detail(&self, run: &RunSummary) {
+        let mut lines = vec![
+            format!("{} - {}", mode_label(run), outcome(run)),
+            format!("Ended {} UTC", format_date(run.ended_at)),
+            format!("Score: {}", run.score),
+            format!("Time played: {}", format_duration(run.duration)),
+            format!("Asteroids destroyed: {}", run.asteroids_destroyed),
+            format!("Escaped asteroids: {}", run.escaped),
+        ];
+        if run.mode == "Waves" {
+            lines.push(format!("Reached wave {}", run.wave));
+        }
+        lines.push(format!("Seed {}", run.seed));
+        let top = ui(Self::TOP);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text_h_centered(line, top + i as f32 * ui(36.0), 28);
+        }
+        if !run.heatmap.is_empty() {
+            let (w, h) = (ui(320.0), ui(180.0));
+            let y = top + lines.len() as f32 * ui(36.0);
+            run.heatmap
+                .render(Rect::new((screen_width() - w) / 2.0, y, w, h));
+        }
+    }
+}
+
+fn mode_label(run: &RunSummary) -> String {
+    if run.weekly {
+        String::from("Weekly")
+    } else {
+        run.mode.clone()
+    }
+}
+
+fn outcome(run: &RunSummary) -> &'static str {
+    if run.crashed {
+        "Crashed"
+    } else if run.abandoned {
+        "Abandoned"
+    } else if run.won {
+        "Won"
+    } else {
+        "Lost"
+    }
+}
+
+// "2026-10-16 14:05" in UTC
+pub fn format_date(timestamp: f64) -> String {
+    let seconds = timestamp as i64;
+    let (year, month, day) = weekly::civil_date(seconds.div_euclid(86400));
+    let minutes = seconds.rem_euclid(86400) / 60;
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
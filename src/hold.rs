@@ -0,0 +1,51 @@
+use crate::ui;
+use macroquad::prelude::*;
+
+// Hold-to-confirm for actions that would be costly to set off by accident. The
+// key has to stay down for the whole duration, and letting go early drains the
+// progress back at the same rate instead of dropping it at once.
+pub struct HoldToConfirm {
+    // Seconds the key has to be held
+    duration: f32,
+    held: f32,
+}
+impl HoldToConfirm {
+    pub fn new(duration: f32) -> HoldToConfirm {
+        HoldToConfirm {
+            duration,
+            held: 0.0,
+        }
+    }
+
+    // True on the frame the hold completes, after which it starts over
+    pub fn update(&mut self, down: bool, frame_time: f32) -> bool {
+        if down {
+            self.held += frame_time;
+        } else {
+            self.held = (self.held - frame_time).max(0.0);
+        }
+        if self.held >= self.duration {
+            self.held = 0.0;
+            return true;
+        }
+        false
+    }
+
+    pub fn progress(&self) -> f32 {
+        (self.held / self.duration).min(1.0)
+    }
+
+    // Ring filling clockwise from the top around (x, y), with `label` under it,
+    // only while there's progress
+    pub fn render(&self, label: &str, x: f32, y: f32) {
+        if self.held <= 0.0 {
+            return;
+        }
+        let radius = ui(14.0);
+        draw_circle_lines(x, y, radius, 1.0, GRAY);
+        draw_arc(x, y, 32, radius, 270.0, 3.0, 360.0 * self.progress(), WHITE);
+        let size = ui(18.0);
+        let width = measure_text(label, None, size as u16, 1.0).width;
+        draw_text(label, x - width / 2.0, y + radius + ui(20.0), size, WHITE);
+    }
+}
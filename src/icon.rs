@@ -0,0 +1,66 @@
+// Window and taskbar icon: the ship's hull outline rasterized at each size the
+// platform asks for. Platforms without window icons just ignore it.
+use crate::{geom, Ship};
+use macroquad::miniquad::conf::Icon;
+use macroquad::prelude::*;
+
+// Samples per pixel along each axis, for smooth edges
+const SUPERSAMPLE: usize = 4;
+
+pub fn ship_icon() -> Icon {
+    Icon {
+        small: rasterize::<{ 16 * 16 * 4 }>(16),
+        medium: rasterize::<{ 32 * 32 * 4 }>(32),
+        big: rasterize::<{ 64 * 64 * 4 }>(64),
+    }
+}
+
+// White outline on transparent, nose up and filling the square with a small margin
+fn rasterize<const BYTES: usize>(size: usize) -> [u8; BYTES] {
+    let hull = hull_in_square(size as f32);
+    // Thicker than the in-game line so it still reads at 16px
+    let thickness = (size as f32 / 12.0).max(1.5);
+    let mut pixels = [0; BYTES];
+    for y in 0..size {
+        for x in 0..size {
+            let mut covered = 0;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let point = Vec2::new(
+                        x as f32 + (sx as f32 + 0.5) / SUPERSAMPLE as f32,
+                        y as f32 + (sy as f32 + 0.5) / SUPERSAMPLE as f32,
+                    );
+                    if on_outline(point, &hull, thickness) {
+                        covered += 1;
+                    }
+                }
+            }
+            let alpha = covered * 255 / (SUPERSAMPLE * SUPERSAMPLE);
+            let i = (y * size + x) * 4;
+            pixels[i..i + 4].copy_from_slice(&[255, 255, 255, alpha as u8]);
+        }
+    }
+    pixels
+}
+
+// The hull turned to face up, scaled and centered in a `size` square
+fn hull_in_square(size: f32) -> [Vec2; 3] {
+    let center = Ship::HULL.iter().sum::<Vec2>() / 3.0;
+    let turned = Ship::HULL.map(|v| geom::rotate_around(v, center, -std::f32::consts::FRAC_PI_2));
+    let min = turned.iter().fold(Vec2::MAX, |m, v| m.min(*v));
+    let max = turned.iter().fold(Vec2::MIN, |m, v| m.max(*v));
+    let margin = size / 16.0;
+    let scale = (size - 2.0 * margin) / (max - min).max_element();
+    let offset = Vec2::splat(size / 2.0) - (min + max) / 2.0 * scale;
+    turned.map(|v| v * scale + offset)
+}
+
+fn on_outline(point: Vec2, hull: &[Vec2; 3], thickness: f32) -> bool {
+    if !geom::point_in_triangle(point, hull[0], hull[1], hull[2]) {
+        return false;
+    }
+    (0..3).any(|i| {
+        let edge = geom::closest_point_on_segment(point, hull[i], hull[(i + 1) % 3]);
+        geom::distance(point, edge) <= thickness
+    })
+}
@@ -0,0 +1,178 @@
+// Invariants checked after every tick in debug builds. Broken ones are printed
+// with the entity involved and the game panics, so a bad state is caught on
+// the frame it appears instead of whenever it finally shows on screen.
+use crate::persist::{self, Document};
+use crate::{Game, GameMode, SizeClass};
+use std::{collections::HashSet, fmt};
+
+impl Game {
+    pub(crate) fn check_integrity(&self, score_before: f32) {
+        let violations = self.integrity_violations(score_before);
+        if violations.is_empty() {
+            return;
+        }
+        for violation in &violations {
+            eprintln!("Integrity violation at frame {}: {}", self.frame, violation);
+        }
+        if self.settings.integrity_dump {
+            let name = format!("integrity-frame-{}.txt", self.frame);
+            match persist::write_dump(&name, &self.state_document()) {
+                Ok(path) => eprintln!("Game state written to {}", path.display()),
+                Err(e) => eprintln!("Failed to write {}: {}", name, e),
+            }
+        }
+        panic!(
+            "{} integrity violation(s) at frame {}",
+            violations.len(),
+            self.frame
+        );
+    }
+
+    fn integrity_violations(&self, score_before: f32) -> Vec<String> {
+        let mut violations = vec![];
+        // Labels are only formatted for a violation, so a clean frame allocates nothing
+        let mut finite = |what: fmt::Arguments, values: &[f32]| {
+            if !values.iter().all(|v| v.is_finite()) {
+                violations.push(format!("{} is not finite: {:?}", what, values));
+            }
+        };
+        let ship = &self.player;
+        finite(
+            format_args!("ship"),
+            &[ship.position.x, ship.position.y, ship.rotation],
+        );
+        for a in self
+            .asteroids
+            .iter()
+            .chain(self.reentries.iter().map(|(_, a)| a))
+        {
+            finite(
+                format_args!("asteroid {}", a.id),
+                &[
+                    a.position.x,
+                    a.position.y,
+                    a.velocity.x,
+                    a.velocity.y,
+                    a.radius,
+                ],
+            );
+        }
+        for (i, l) in self.lasers.iter().enumerate() {
+            finite(
+                format_args!("laser {}", i),
+                &[l.position.x, l.position.y, l.velocity.x, l.velocity.y],
+            );
+        }
+        for (i, p) in self.power_ups.iter().enumerate() {
+            finite(
+                format_args!("power-up {}", i),
+                &[p.position.x, p.position.y, p.velocity.x, p.velocity.y],
+            );
+        }
+        for (i, p) in self.particles.iter().enumerate() {
+            finite(
+                format_args!("particle {}", i),
+                &[p.position.x, p.position.y, p.velocity.x, p.velocity.y],
+            );
+        }
+
+        // Splits and mining chips never go below the smallest band before the
+        // rock is removed, but the console can spawn any size
+        if !self.console_used {
+            let min = SizeClass::Small.band().0 * self.size_scale;
+            let max = SizeClass::Huge.band().1 * self.size_scale;
+            for a in &self.asteroids {
+                if a.radius < min * 0.999 || a.radius > max * 1.001 {
+                    violations.push(format!(
+                        "asteroid {} has radius {} outside {}..{}",
+                        a.id, a.radius, min, max
+                    ));
+                }
+            }
+        }
+
+        let mut ids = HashSet::with_capacity(self.asteroids.len() + self.reentries.len());
+        for a in self
+            .asteroids
+            .iter()
+            .chain(self.reentries.iter().map(|(_, a)| a))
+        {
+            if !ids.insert(a.id) {
+                violations.push(format!("asteroid id {} is used twice", a.id));
+            }
+        }
+
+        // Pressure decays the score, everywhere else it only grows within a run
+        if self.score < score_before && self.mode != GameMode::Pressure {
+            violations.push(format!(
+                "score went down from {} to {} in {:?}",
+                score_before, self.score, self.mode
+            ));
+        }
+
+        let cap = if self.settings.storm {
+            Self::STORM_MAX_PARTICLES
+        } else {
+            Self::MAX_PARTICLES
+        } + Self::DEATH_PARTICLES;
+        if self.particles.len() > cap {
+            violations.push(format!(
+                "{} particles alive, the cap is {}",
+                self.particles.len(),
+                cap
+            ));
+        }
+        violations
+    }
+
+    // Simulation state in the same `key=value` form as the saved files
+    fn state_document(&self) -> Document {
+        let mut document = Document::default();
+        document.set("frame", self.frame);
+        document.set("mode", format!("{:?}", self.mode));
+        document.set("seed", self.seed);
+        document.set("elapsed", self.elapsed);
+        document.set("score", self.score);
+        document.set("wave", self.wave);
+        document.set(
+            "ship",
+            format!(
+                "{}:{}:{}:{}",
+                self.player.position.x,
+                self.player.position.y,
+                self.player.rotation,
+                self.player.health
+            ),
+        );
+        for (i, a) in self.asteroids.iter().enumerate() {
+            document.set(
+                &format!("asteroid.{}", i),
+                format!(
+                    "{}:{}:{}:{}:{}:{}:{}",
+                    a.id,
+                    a.position.x,
+                    a.position.y,
+                    a.velocity.x,
+                    a.velocity.y,
+                    a.radius,
+                    a.health
+                ),
+            );
+        }
+        for (i, (delay, a)) in self.reentries.iter().enumerate() {
+            document.set(&format!("reentry.{}", i), format!("{}:{}", delay, a.id));
+        }
+        for (i, l) in self.lasers.iter().enumerate() {
+            document.set(
+                &format!("laser.{}", i),
+                format!(
+                    "{}:{}:{}:{}",
+                    l.position.x, l.position.y, l.velocity.x, l.velocity.y
+                ),
+            );
+        }
+        document.set("power_ups", self.power_ups.len());
+        document.set("particles", self.particles.len());
+        document
+    }
+}
@@ -0,0 +1,133 @@
+// Keyboard and mouse as the app reads them. Everything goes through here
+// rather than to macroquad directly, so tests can script the keys for their
+// own thread and drive the whole app headless, without a window to read.
+use macroquad::prelude::*;
+
+pub fn is_down(key: KeyCode) -> bool {
+    #[cfg(test)]
+    if let Some(down) = script::with(|s| s.down.contains(&key)) {
+        return down;
+    }
+    is_key_down(key)
+}
+
+// Went down this frame
+pub fn is_pressed(key: KeyCode) -> bool {
+    #[cfg(test)]
+    if let Some(pressed) = script::with(|s| s.pressed.contains(&key)) {
+        return pressed;
+    }
+    is_key_pressed(key)
+}
+
+// Whether any key went down this frame
+pub fn any_pressed() -> bool {
+    #[cfg(test)]
+    if let Some(any) = script::with(|s| !s.pressed.is_empty()) {
+        return any;
+    }
+    get_last_key_pressed().is_some()
+}
+
+// Typed characters, most recent first like macroquad's queue. Scripts only
+// press keys, they don't type.
+pub fn next_char() -> Option<char> {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return None;
+    }
+    get_char_pressed()
+}
+
+// Forget keys and characters that haven't been read yet
+pub fn clear_queue() {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return;
+    }
+    clear_input_queue();
+}
+
+// Nor do they have a mouse: it stays in the corner with nothing pressed
+pub fn is_mouse_pressed(button: MouseButton) -> bool {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return false;
+    }
+    is_mouse_button_pressed(button)
+}
+
+pub fn is_mouse_released(button: MouseButton) -> bool {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return false;
+    }
+    is_mouse_button_released(button)
+}
+
+pub fn mouse() -> Vec2 {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return Vec2::ZERO;
+    }
+    Vec2::from(mouse_position())
+}
+
+pub fn wheel() -> f32 {
+    #[cfg(test)]
+    if script::with(|_| ()).is_some() {
+        return 0.0;
+    }
+    mouse_wheel().1
+}
+
+// Keys for the calling test's thread, set between frames
+#[cfg(test)]
+pub mod script {
+    use macroquad::prelude::KeyCode;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    pub struct Script {
+        pub(super) down: Vec<KeyCode>,
+        pub(super) pressed: Vec<KeyCode>,
+    }
+
+    thread_local! {
+        static SCRIPT: RefCell<Option<Script>> = const { RefCell::new(None) };
+    }
+
+    pub(super) fn with<R>(f: impl FnOnce(&mut Script) -> R) -> Option<R> {
+        SCRIPT.with(|s| s.borrow_mut().as_mut().map(f))
+    }
+
+    // From now on the thread reads scripted keys, starting with none down
+    pub fn start() {
+        SCRIPT.with(|s| *s.borrow_mut() = Some(Script::default()));
+    }
+
+    // Down from the next frame until released
+    pub fn hold(key: KeyCode) {
+        with(|s| {
+            if !s.down.contains(&key) {
+                s.down.push(key);
+                s.pressed.push(key);
+            }
+        });
+    }
+
+    pub fn release(key: KeyCode) {
+        with(|s| s.down.retain(|&k| k != key));
+    }
+
+    // Down for the next frame only
+    pub fn tap(key: KeyCode) {
+        hold(key);
+        with(|s| s.down.retain(|&k| k != key));
+    }
+
+    // Called after each frame, so presses only count for the one they were made in
+    pub fn end_frame() {
+        with(|s| s.pressed.clear());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::crash;
+use macroquad::prelude::*;
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+// Structured debug events, one `key=value` line each. Written to `--log-file`
+// if given, with the most recent lines kept for the in-game console.
+pub struct EventLog {
+    file: Option<BufWriter<File>>,
+    recent: VecDeque<String>,
+}
+impl EventLog {
+    const RECENT_LINES: usize = 20;
+
+    pub fn new(path: Option<&str>) -> EventLog {
+        let file = path.and_then(|path| match File::create(path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("Couldn't create log file {}: {}", path, err);
+                None
+            }
+        });
+        EventLog {
+            file,
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn write(&mut self, line: String) {
+        crash::note_log(&line);
+        if let Some(file) = self.file.as_mut() {
+            if let Err(err) = writeln!(file, "{}", line) {
+                eprintln!("Couldn't write to log file, closing it: {}", err);
+                self.file = None;
+            }
+        }
+        self.recent.push_back(line);
+        if self.recent.len() > Self::RECENT_LINES {
+            self.recent.pop_front();
+        }
+    }
+
+    // Called once a frame so the file is complete even if the window is closed abruptly
+    pub fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+
+    pub fn render(&self) {
+        let line_height = 16.0;
+        let height = line_height * Self::RECENT_LINES as f32 + 8.0;
+        let y = screen_height() - height;
+        draw_rectangle(
+            0.0,
+            y,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        for (i, line) in self.recent.iter().enumerate() {
+            draw_text(line, 8.0, y + line_height * (i as f32 + 1.0), 16.0, GREEN);
+        }
+    }
+}
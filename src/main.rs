@@ -1,75 +1,394 @@
+use assets::AssetStore;
+use balance::Balance;
+use banner::Banner;
+use bindings::{Bindings, GameAction, UiAction};
+use console::Console;
+use crash::RunState;
+use crt::CrtFilter;
+use derelict::Derelict;
+use director::{Allowed, Purchase, SpawnDirector};
+use feed::{EventFeed, GameEvent};
+use heatmap::Heatmap;
+use hints::Hints;
+use history::{HistoryAction, HistoryBrowser};
+use hold::HoldToConfirm;
+use log::EventLog;
 use macroquad::prelude::*;
 use macroquad::window::Conf;
-use rand::gen_range;
-use std::{cmp, collections::HashSet, vec};
+use music::MusicMix;
+use pacing::FramePacer;
+use photo::PhotoMode;
+use profile::{Profile, RunSummary, ScoreRecord};
+use records::RecordsBrowser;
+use replay::{Replay, ReplayPlayer};
+use rng::{fresh_seed, Rng};
+use settings::{AsteroidBehavior, BoundaryMode, DisplayMode, HudDetail, Settings, TitlePanel};
+use starfield::Starfield;
+use std::{
+    cell::{Cell, Ref, RefCell},
+    cmp,
+    collections::VecDeque,
+    time::Duration,
+    vec,
+};
+use text_entry::{EntryResult, TextEntry};
+use timer::{Cooldown, Countdown, RepeatingTimer};
+use toast::Toast;
+use transition::Transition;
+use unlocks::{PALETTES, UNLOCKABLES};
+use weekly::Challenge;
+
+#[cfg(feature = "alloc-audit")]
+mod alloc_audit;
+mod assets;
+mod balance;
+mod banner;
+mod bindings;
+mod collision;
+mod console;
+mod controls;
+mod crash;
+mod crt;
+mod derelict;
+mod director;
+mod env;
+mod feed;
+mod geom;
+mod heatmap;
+mod hints;
+mod history;
+mod hold;
+mod icon;
+#[cfg(debug_assertions)]
+mod integrity;
+mod keys;
+mod log;
+mod music;
+#[cfg(feature = "overlay")]
+mod overlay;
+mod pacing;
+mod persist;
+mod photo;
+mod profile;
+mod prompt;
+mod records;
+mod replay;
+mod rng;
+mod settings;
+#[cfg(debug_assertions)]
+mod snapshot;
+mod starfield;
+mod text_entry;
+mod timer;
+mod toast;
+mod transition;
+mod unlocks;
+mod weekly;
+
+thread_local! {
+    // UI text multiplier from the accessibility setting, set by the app every frame
+    static TEXT_SCALE: Cell<f32> = const { Cell::new(1.0) };
+    // Line weight multiplier for the play field, also set every frame
+    static STROKE_SCALE: Cell<f32> = const { Cell::new(1.0) };
+}
+
+// A world line thickness at 1080p, scaled up so strokes keep their weight on bigger screens
+fn stroke(thickness: f32) -> f32 {
+    thickness * STROKE_SCALE.get()
+}
+
+// A UI length scaled along with the text, for font sizes and the gaps between lines
+fn ui(length: f32) -> f32 {
+    length * TEXT_SCALE.get()
+}
+
+// Scaled font size for text `width` wide at `font_size`, shrunk to fit the screen if needed
+fn fit_font_size(width: f32, font_size: u16) -> u16 {
+    let scaled = ui(font_size as f32);
+    let max_width = screen_width() - 20.0;
+    let fitted = if width * TEXT_SCALE.get() > max_width {
+        scaled * max_width / (width * TEXT_SCALE.get())
+    } else {
+        scaled
+    };
+    fitted as u16
+}
 
 fn draw_text_h_centered(text: &str, y: f32, font_size: u16) {
+    draw_text_h_centered_color(text, y, font_size, WHITE);
+}
+
+fn draw_text_h_centered_color(text: &str, y: f32, font_size: u16, color: Color) {
+    draw_text_centered_at(text, screen_width() / 2.0, y, font_size, color);
+}
+
+fn draw_text_centered_at(text: &str, center_x: f32, y: f32, font_size: u16, color: Color) {
+    let width = measure_text(text, None, font_size, 1.0).width;
+    let font_size = fit_font_size(width, font_size);
     let text_dimensions = measure_text(text, None, font_size, 1.0);
-    let x = (screen_width() - text_dimensions.width) / 2.0;
-    draw_text(text, x, y, font_size as f32, WHITE);
+    let x = center_x - text_dimensions.width / 2.0;
+    draw_text(text, x, y, font_size as f32, color);
+}
+
+// Queue a debug event tagged with the game's frame index, if event logging is on
+macro_rules! log_event {
+    ($game:expr, $($arg:tt)*) => {
+        if $game.settings.log_events {
+            let line = format!("frame={} {}", $game.frame, format_args!($($arg)*));
+            $game.events.push(line);
+        }
+    };
 }
 
-fn distance(p1: &Vec2, p2: &Vec2) -> f32 {
-    ((p2.x - p1.x).powf(2.0) + (p2.y - p1.y).powf(2.0)).sqrt()
+#[derive(Clone, Copy, Default)]
+struct Input {
+    forward: bool,
+    backward: bool,
+    rotate_left: bool,
+    rotate_right: bool,
+    fire: bool,
+    focus: bool,
+    strafe_left: bool,
+    strafe_right: bool,
+    // Asteroid thrown by the attacker in versus mode this frame
+    launch: Option<Launch>,
+}
+impl Input {
+    fn from_keyboard(bindings: &Bindings) -> Input {
+        Input {
+            forward: bindings.is_down(GameAction::Forward),
+            backward: bindings.is_down(GameAction::Backward),
+            rotate_left: bindings.is_down(GameAction::RotateLeft),
+            rotate_right: bindings.is_down(GameAction::RotateRight),
+            fire: bindings.is_down(GameAction::Fire),
+            focus: bindings.is_down(GameAction::Focus),
+            strafe_left: bindings.is_down(GameAction::StrafeLeft),
+            strafe_right: bindings.is_down(GameAction::StrafeRight),
+            launch: None,
+        }
+    }
+}
+
+// A versus throw, dragged from a point near a screen edge to where the mouse was released
+#[derive(Clone, Copy)]
+struct Launch {
+    from: Vec2,
+    to: Vec2,
+    // Index into `Game::LAUNCH_SIZES`
+    size: usize,
 }
 
+#[derive(Clone)]
 struct Ship {
     position: Vec2,
     health: usize,
-    iframes: u32,
+    max_health: usize,
+    // Hits do nothing while this runs, after spawning and after each hit
+    invulnerable: Countdown,
     // Rotation in radians
     rotation: f32,
+    // Degrees per second it turns at before any modifiers
+    turn_rate: f32,
+    // Whether dropping to 1 health still triggers the last-hit warning this life
+    last_hit_armed: bool,
 }
 impl Ship {
+    // Hull outline relative to `position` before rotating, facing along +x
+    const HULL: [Vec2; 3] = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(45.0, -15.0),
+        Vec2::new(0.0, -30.0),
+    ];
+    // Seconds of invulnerability after spawning and after taking a hit
+    const SPAWN_GRACE: f32 = 2.0;
+    const HIT_GRACE: f32 = 0.5;
+    // Farthest the hull reaches from its center
+    const REACH: f32 = 30.0;
+    // Turn rate in degrees per second it starts with, and the most it can reach
+    const BASE_TURN_RATE: f32 = 250.0;
+    const MAX_TURN_RATE: f32 = 500.0;
+
     fn new(x: f32, y: f32) -> Ship {
         let rotation_degrees: f32 = 270.0;
         Ship {
             position: Vec2::new(x, y),
             health: 5,
-            iframes: 120,
+            max_health: 5,
+            invulnerable: Countdown::new(Self::SPAWN_GRACE),
             rotation: rotation_degrees.to_radians(),
+            turn_rate: Self::BASE_TURN_RATE,
+            last_hit_armed: true,
         }
     }
 
-    fn render(&self) {
+    fn render(&self, color: Color, settings: &Settings) {
+        if self.health == 0 {
+            return;
+        }
         let vertices = self.vertices();
-        if self.health > 0 {
-            draw_triangle_lines(vertices[0], vertices[1], vertices[2], 1.0, WHITE)
+        if settings.high_visibility_ship {
+            draw_triangle(
+                vertices[0],
+                vertices[1],
+                vertices[2],
+                Color { a: 0.3, ..color },
+            );
+            draw_triangle_lines(vertices[0], vertices[1], vertices[2], stroke(3.0), color);
+        } else {
+            draw_triangle_lines(vertices[0], vertices[1], vertices[2], stroke(1.5), color);
+        }
+        if settings.heading_line {
+            let nose = self.muzzle_position();
+            let end = nose + Vec2::from_angle(self.rotation) * 20.0;
+            let faded = Color { a: 0.6, ..color };
+            draw_line(nose.x, nose.y, end.x, end.y, stroke(1.0), faded);
         }
     }
 
-    fn take_hit(&mut self) {
-        if self.iframes == 0 && self.health > 0 {
+    // Degrees per second a ship with `base` turn rate actually turns at.
+    // Multipliers on the rate all meet here, capped so stacking them can't spin
+    // the ship out of control.
+    fn effective_turn_rate(base: f32, modifiers: &[f32]) -> f32 {
+        (base * modifiers.iter().product::<f32>()).clamp(0.0, Self::MAX_TURN_RATE)
+    }
+
+    // Returns whether the hit did damage
+    fn take_hit(&mut self) -> bool {
+        if !self.invulnerable.is_running() && self.health > 0 {
             self.health -= 1;
-            self.iframes = 30;
+            self.invulnerable.start(Self::HIT_GRACE);
+            return true;
         }
+        false
     }
 
-    fn vertices(&self) -> Vec<Vec2> {
-        let x1 = self.position.x;
-        let y1 = self.position.y;
-        let x2 = self.position.x + 45.0;
-        let y2 = self.position.y - 15.0;
-        let x3 = self.position.x;
-        let y3 = self.position.y - 30.0;
+    fn center(&self) -> Vec2 {
+        let vertices = self.vertices();
+        (vertices[0] + vertices[1] + vertices[2]) / 3.0
+    }
 
-        let center = Vec2::new((x1 + x2 + x3) / 3.0, (y1 + y2 + y3) / 3.0);
+    fn vertices(&self) -> [Vec2; 3] {
+        let center = self.position + Self::HULL.iter().sum::<Vec2>() / 3.0;
+        Self::HULL.map(|vertex| geom::rotate_around(self.position + vertex, center, self.rotation))
+    }
 
-        vec![Vec2::new(x1, y1), Vec2::new(x2, y2), Vec2::new(x3, y3)]
-            .iter()
-            .map(|&vertex| {
-                // translate the point so it's relative to the origin
-                let x = vertex.x - center.x;
-                let y = vertex.y - center.y;
-                // apply rotation matrix
-                let rotated = Vec2::new(
-                    x * self.rotation.cos() - y * self.rotation.sin(),
-                    x * self.rotation.sin() + y * self.rotation.cos(),
-                );
-                // translate back to original location
-                rotated + center
-            })
-            .collect()
+    // Forward-most point of the hull, where lasers leave the ship
+    fn muzzle_position(&self) -> Vec2 {
+        let center = self.position + Self::HULL.iter().sum::<Vec2>() / 3.0;
+        let nose = Self::HULL
+            .into_iter()
+            .max_by(|a, b| a.x.total_cmp(&b.x))
+            .unwrap();
+        geom::rotate_around(self.position + nose, center, self.rotation)
+    }
+}
+
+// One edge of the destroyed ship's hull, tumbling away
+#[derive(Clone)]
+struct Debris {
+    position: Vec2,
+    // Half the edge, from its midpoint
+    half: Vec2,
+    velocity: Vec2,
+    // Radians per second
+    spin: f32,
+    life: Countdown,
+}
+impl Debris {
+    const LIFETIME: f32 = 2.5;
+
+    fn tick(&mut self, frame_time: f32) {
+        self.position += self.velocity * frame_time;
+        self.half = Vec2::from_angle(self.spin * frame_time).rotate(self.half);
+        self.life.tick(frame_time);
+    }
+
+    fn render(&self, color: Color) {
+        let (start, end) = (self.position - self.half, self.position + self.half);
+        let alpha = (self.life.remaining() / Self::LIFETIME).clamp(0.0, 1.0);
+        draw_line(
+            start.x,
+            start.y,
+            end.x,
+            end.y,
+            stroke(1.0),
+            Color { a: alpha, ..color },
+        );
+    }
+}
+
+// Cosmetic spark that doesn't affect the simulation
+#[derive(Clone)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    life: Countdown,
+    lifetime: f32,
+    // Part of the ship's death explosion, which the results screen waits on
+    death: bool,
+    // Smoke is drawn in its own dim color and swells from `size` as it fades,
+    // where sparks shrink away in the foreground color
+    smoke: bool,
+    size: f32,
+}
+impl Particle {
+    const LIFETIME: f32 = 0.25;
+    const SMOKE_LIFETIME: f32 = 2.5;
+    const SMOKE: Color = Color::new(0.45, 0.45, 0.5, 0.35);
+
+    fn new(position: Vec2, velocity: Vec2) -> Particle {
+        Particle {
+            position,
+            velocity,
+            life: Countdown::new(Self::LIFETIME),
+            lifetime: Self::LIFETIME,
+            death: false,
+            smoke: false,
+            size: 1.5,
+        }
+    }
+
+    fn smoke(position: Vec2, velocity: Vec2, size: f32) -> Particle {
+        Particle {
+            life: Countdown::new(Self::SMOKE_LIFETIME),
+            lifetime: Self::SMOKE_LIFETIME,
+            smoke: true,
+            size,
+            ..Particle::new(position, velocity)
+        }
+    }
+
+    // Smoke lasts ten times as long as a spark, so it takes up two places in the cap
+    fn weight(&self) -> usize {
+        if self.smoke {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn tick(&mut self, frame_time: f32) {
+        self.position += self.velocity * frame_time;
+        self.life.tick(frame_time);
+    }
+
+    fn render(&self, color: Color) {
+        let alpha = (self.life.remaining() / self.lifetime).clamp(0.0, 1.0);
+        if self.smoke {
+            let radius = self.size * (2.0 - alpha);
+            let color = Color {
+                a: Self::SMOKE.a * alpha,
+                ..Self::SMOKE
+            };
+            draw_circle(self.position.x, self.position.y, radius, color);
+            return;
+        }
+        draw_circle(
+            self.position.x,
+            self.position.y,
+            stroke(self.size),
+            Color { a: alpha, ..color },
+        );
     }
 }
 
@@ -88,16 +407,16 @@ impl Laser {
         }
     }
 
-    fn render(&self) {
+    fn render(&self, color: Color) {
         let length = 10.0;
-        let angle = self.velocity.y.atan2(self.velocity.x);
+        let tail = self.position + self.velocity.normalize_or_zero() * length;
         draw_line(
             self.position.x,
             self.position.y,
-            self.position.x + length * angle.cos(),
-            self.position.y + length * angle.sin(),
-            1.0,
-            WHITE,
+            tail.x,
+            tail.y,
+            stroke(2.0),
+            color,
         )
     }
 
@@ -105,6 +424,170 @@ impl Laser {
         self.position.x += self.velocity.x * frame_time;
         self.position.y += self.velocity.y * frame_time;
     }
+
+    fn is_offscreen(&self, width: f32, height: f32) -> bool {
+        self.position.x < 0.0
+            || self.position.y < 0.0
+            || self.position.x > width
+            || self.position.y > height
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PowerUpKind {
+    // Restores 1 health
+    Repair,
+    // Chipped off asteroids in Mining mode
+    Ore,
+    // Dropped by destroyed asteroids with the ammo economy on, restoring this much ammo
+    Salvage(u32),
+    // Arc in front of the ship that bounces asteroids away for a while
+    Deflector,
+}
+
+#[derive(Clone)]
+struct PowerUp {
+    kind: PowerUpKind,
+    position: Vec2,
+    velocity: Vec2,
+    // Runs until it disappears if not picked up
+    life: Countdown,
+}
+impl PowerUp {
+    const RADIUS: f32 = 12.0;
+
+    fn new(kind: PowerUpKind, position: Vec2, velocity: Vec2) -> PowerUp {
+        PowerUp {
+            kind,
+            position,
+            velocity,
+            life: Countdown::new(10.0),
+        }
+    }
+
+    fn render(&self) {
+        // Blink during the last couple of seconds
+        let remaining = self.life.remaining();
+        if remaining < 2.0 && (remaining * 8.0) as i32 % 2 == 1 {
+            return;
+        }
+        let (x, y) = (self.position.x, self.position.y);
+        match self.kind {
+            PowerUpKind::Ore => draw_poly_lines(x, y, 4, 5.0, 0.0, stroke(1.0), GOLD),
+            PowerUpKind::Deflector => {
+                draw_circle_lines(x, y, Self::RADIUS, stroke(1.0), SKYBLUE);
+                draw_arc(x, y, 12, 6.0, 210.0, stroke(2.0), 120.0, SKYBLUE);
+            }
+            PowerUpKind::Salvage(_) => {
+                draw_rectangle_lines(x - 7.0, y - 5.0, 14.0, 10.0, stroke(1.0), ORANGE);
+                draw_line(x - 4.0, y, x + 4.0, y, stroke(1.0), ORANGE);
+            }
+            PowerUpKind::Repair => {
+                draw_circle_lines(x, y, Self::RADIUS, stroke(1.0), GREEN);
+                draw_rectangle(x - 2.0, y - 7.0, 4.0, 14.0, GREEN);
+                draw_rectangle(x - 7.0, y - 2.0, 14.0, 4.0, GREEN);
+            }
+        }
+    }
+
+    fn tick(&mut self, frame_time: f32) {
+        self.position += self.velocity * frame_time;
+        self.life.tick(frame_time);
+    }
+}
+
+// Asteroid size bands by radius at 1080p. Anything that depends on how big an
+// asteroid is asks its class instead of comparing radii.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SizeClass {
+    Small,
+    Medium,
+    Large,
+    Huge,
+}
+impl SizeClass {
+    const ALL: [SizeClass; 4] = [
+        SizeClass::Small,
+        SizeClass::Medium,
+        SizeClass::Large,
+        SizeClass::Huge,
+    ];
+
+    // Radius range at 1080p, each class taking radii above the one before it
+    fn band(self) -> (f32, f32) {
+        match self {
+            SizeClass::Small => (10.0, 20.0),
+            SizeClass::Medium => (20.0, 40.0),
+            SizeClass::Large => (40.0, 60.0),
+            SizeClass::Huge => (60.0, 100.0),
+        }
+    }
+
+    // Class of an asteroid with `radius` at 1080p, anything past the bands is Huge.
+    // Scaling a radius to the screen and back can leave it a hair past the top
+    // of its band, which still counts as inside.
+    fn of(radius: f32) -> SizeClass {
+        Self::ALL
+            .into_iter()
+            .find(|class| radius <= class.band().1 * (1.0 + 1e-6))
+            .unwrap_or(SizeClass::Huge)
+    }
+
+    // Pick a class with chances proportional to `weights`, Small to Huge
+    fn roll(rng: &mut Rng, weights: &[f32; 4]) -> SizeClass {
+        let total: f32 = weights.iter().sum();
+        let mut pick = rng.gen_range(0.0, total);
+        for (class, weight) in Self::ALL.into_iter().zip(weights) {
+            if pick < *weight {
+                return class;
+            }
+            pick -= weight;
+        }
+        // Rounding can leave the roll just past the end, give it to the last class with a chance
+        Self::ALL
+            .into_iter()
+            .zip(weights)
+            .rfind(|(_, weight)| **weight > 0.0)
+            .map_or(SizeClass::Small, |(class, _)| class)
+    }
+
+    fn base_health(self, balance: &Balance) -> u32 {
+        match self {
+            SizeClass::Huge => balance.huge_asteroid_health,
+            _ => 1,
+        }
+    }
+
+    fn splits(self) -> bool {
+        self != SizeClass::Small
+    }
+
+    // Mining rocks crumble once they're chipped down to this
+    fn crumbles(self) -> bool {
+        self == SizeClass::Small
+    }
+
+    // Most and fewest interior cracks drawn
+    fn crack_range(self) -> (u32, u32) {
+        match self {
+            SizeClass::Small => (0, 0),
+            SizeClass::Medium | SizeClass::Large => (1, 2),
+            SizeClass::Huge => (2, 4),
+        }
+    }
+
+    // Hit-stop when one is destroyed, so big kills land harder
+    fn hitstop(self) -> f32 {
+        match self {
+            SizeClass::Huge => 0.05,
+            _ => 0.0,
+        }
+    }
+
+    // Big rocks leave smoke hanging where they shattered
+    fn smokes(self) -> bool {
+        self == SizeClass::Huge
+    }
 }
 
 #[derive(Clone)]
@@ -113,9 +596,16 @@ struct Asteroid {
     position: Vec2,
     velocity: Vec2,
     radius: f32,
+    // Follows `radius`, set by `with_detail` and whenever the radius changes
+    size: SizeClass,
     rotation: f32,
     health: u32,
     num_sides: u8,
+    // Cracks drawn between pairs of outline vertices, one lost per hit survived
+    interior: Vec<(u8, u8)>,
+    // Seconds until a killed asteroid shatters into its pieces. It keeps
+    // drifting meanwhile but nothing collides with it any more.
+    breaking: Option<f32>,
 }
 impl Asteroid {
     fn new(x_pos: f32, y_pos: f32, x_vel: f32, y_vel: f32, radius: f32, id: u32) -> Asteroid {
@@ -124,22 +614,198 @@ impl Asteroid {
             position: Vec2::new(x_pos, y_pos),
             velocity: Vec2::new(x_vel, y_vel),
             radius,
+            size: SizeClass::of(radius),
             rotation: 0.0,
             health: 1,
             num_sides: 8,
+            interior: vec![],
+            breaking: None,
         }
     }
 
-    fn render(&self) {
+    const BREAK_DURATION: f32 = 0.15;
+
+    // Size class, health and cracks by size. The cracks come from the asteroid's
+    // own RNG stream so rolling them doesn't shift what spawns next.
+    fn with_detail(mut self, balance: &Balance, size_scale: f32, run_seed: u64) -> Asteroid {
+        let mut rng = Rng::new(run_seed ^ (self.id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        self.size = SizeClass::of(self.radius / size_scale);
+        self.health = self.size.base_health(balance);
+        let (fewest, most) = self.size.crack_range();
+        let count = if most > 0 {
+            fewest + rng.next_u32() % (most - fewest + 1)
+        } else {
+            0
+        };
+        let sides = self.num_sides as u32;
+        for _ in 0..count {
+            let start = rng.next_u32() % sides;
+            // Skip the neighbours so the line crosses the inside
+            let end = (start + 2 + rng.next_u32() % (sides - 3)) % sides;
+            self.interior.push((start as u8, end as u8));
+        }
+        self
+    }
+
+    // Outline vertex in world space, matching what `draw_poly_lines` draws
+    fn vertex(&self, index: u8) -> Vec2 {
+        let angle = index as f32 * std::f32::consts::TAU / self.num_sides as f32
+            + self.rotation.to_radians();
+        self.position + Vec2::from_angle(angle) * self.radius
+    }
+
+    // Below these on-screen radii asteroids are drawn as a point or a plain circle
+    const POINT_LOD_RADIUS: f32 = 2.0;
+    const CIRCLE_LOD_RADIUS: f32 = 5.0;
+
+    fn is_offscreen(&self, width: f32, height: f32) -> bool {
+        self.position.x > width + self.radius
+            || self.position.y > height + self.radius
+            || self.position.x < -self.radius
+            || self.position.y < -self.radius
+    }
+
+    // Move an asteroid that left the field to just past the opposite edge,
+    // keeping its heading, and return how far it moved
+    fn wrap(&mut self, width: f32, height: f32) -> Vec2 {
+        let before = self.position;
+        let radius = self.radius;
+        if self.position.x > width + radius {
+            self.position.x = -radius;
+        } else if self.position.x < -radius {
+            self.position.x = width + radius;
+        }
+        if self.position.y > height + radius {
+            self.position.y = -radius;
+        } else if self.position.y < -radius {
+            self.position.y = height + radius;
+        }
+        self.position - before
+    }
+
+    // Reflect off the edges of the field when moving into them, returning the
+    // contact point and the inward normal of the wall hit
+    fn bounce_off_walls(
+        &mut self,
+        width: f32,
+        height: f32,
+        restitution: f32,
+    ) -> Option<(Vec2, Vec2)> {
+        let mut hit = None;
+        if self.position.x - self.radius < 0.0 && self.velocity.x < 0.0 {
+            self.velocity.x *= -restitution;
+            hit = Some((Vec2::new(0.0, self.position.y), Vec2::X));
+        } else if self.position.x + self.radius > width && self.velocity.x > 0.0 {
+            self.velocity.x *= -restitution;
+            hit = Some((Vec2::new(width, self.position.y), Vec2::NEG_X));
+        }
+        if self.position.y - self.radius < 0.0 && self.velocity.y < 0.0 {
+            self.velocity.y *= -restitution;
+            hit = Some((Vec2::new(self.position.x, 0.0), Vec2::Y));
+        } else if self.position.y + self.radius > height && self.velocity.y > 0.0 {
+            self.velocity.y *= -restitution;
+            hit = Some((Vec2::new(self.position.x, height), Vec2::NEG_Y));
+        }
+        hit
+    }
+
+    // Whether any part of the asteroid is inside `view`
+    fn is_visible(&self, view: Rect) -> bool {
+        self.position.x + self.radius >= view.x
+            && self.position.x - self.radius <= view.x + view.w
+            && self.position.y + self.radius >= view.y
+            && self.position.y - self.radius <= view.y + view.h
+    }
+
+    // Just off the background, so filled asteroids read as solid without standing out
+    const FILL: Color = Color::new(0.05, 0.05, 0.07, 1.0);
+
+    // Pick a level of detail by on-screen size, returning whether it was simplified.
+    // Simplified ones are too small for the fill to matter.
+    fn render_lod(&self, color: Color, zoom: f32, fill: bool) -> bool {
+        let screen_radius = self.radius * zoom;
+        if screen_radius < Self::POINT_LOD_RADIUS {
+            draw_rectangle(self.position.x, self.position.y, 1.0, 1.0, color);
+            true
+        } else if screen_radius < Self::CIRCLE_LOD_RADIUS {
+            let (x, y) = (self.position.x, self.position.y);
+            draw_circle_lines(x, y, self.radius, stroke(1.0), color);
+            true
+        } else {
+            self.render(color, fill);
+            false
+        }
+    }
+
+    // Outlines get heavier with size so big rocks don't look like wireframes
+    fn outline_weight(&self) -> f32 {
+        (self.radius / 10.0).sqrt().clamp(1.0, 3.0)
+    }
+
+    fn render(&self, color: Color, fill: bool) {
+        if fill {
+            draw_poly(
+                self.position.x,
+                self.position.y,
+                self.num_sides,
+                self.radius,
+                self.rotation,
+                Color {
+                    a: color.a,
+                    ..Self::FILL
+                },
+            );
+        }
         draw_poly_lines(
             self.position.x,
             self.position.y,
             self.num_sides,
             self.radius,
             self.rotation,
-            1.0,
-            WHITE,
+            stroke(self.outline_weight()),
+            color,
         );
+        let crack_color = Color { a: 0.6, ..color };
+        for &(start, end) in &self.interior {
+            let (start, end) = (self.vertex(start), self.vertex(end));
+            draw_line(start.x, start.y, end.x, end.y, stroke(1.0), crack_color);
+        }
+        if let Some(remaining) = self.breaking {
+            // Flickers on and off every few hundredths of a second
+            if ((remaining / 0.03) as u32).is_multiple_of(2) {
+                self.render_fractures(color);
+            }
+        }
+    }
+
+    // Two or three jagged lines through the center, picked by id so the same
+    // asteroid always breaks the same way
+    fn render_fractures(&self, color: Color) {
+        let count = 2 + self.id % 2;
+        let offset = (self.id % 7) as f32 / 7.0;
+        for i in 0..count {
+            let angle = self.rotation.to_radians()
+                + (i as f32 + offset) * std::f32::consts::PI / count as f32;
+            let along = Vec2::from_angle(angle) * self.radius;
+            let across = along.perp() * 0.15;
+            let points = [
+                self.position - along,
+                self.position - along * 0.5 + across,
+                self.position,
+                self.position + along * 0.5 - across,
+                self.position + along,
+            ];
+            for pair in points.windows(2) {
+                draw_line(
+                    pair[0].x,
+                    pair[0].y,
+                    pair[1].x,
+                    pair[1].y,
+                    stroke(1.5),
+                    color,
+                );
+            }
+        }
     }
 
     fn tick(&mut self, frame_time: f32) {
@@ -152,10 +818,280 @@ impl Asteroid {
         if self.health > 0 {
             self.health -= 1;
         }
+        if self.health > 0 {
+            self.interior.pop();
+        }
+    }
+
+    // Closest distance to `point` along the asteroid's current straight-line path
+    fn closest_approach(&self, point: Vec2) -> f32 {
+        let speed_sq = self.velocity.length_squared();
+        if speed_sq == 0.0 {
+            return geom::distance(self.position, point);
+        }
+        let t = ((point - self.position).dot(self.velocity) / speed_sq).max(0.0);
+        geom::distance(self.position + self.velocity * t, point)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GameMode {
+    // First to 100 points
+    Classic,
+    // Score decays over time and a target score has to be held to win
+    Pressure,
+    // Clear fixed waves, escaped asteroids come back around from the opposite edge
+    Waves,
+    // Two players on one keyboard: one flies the ship, the other throws asteroids with the mouse
+    Versus,
+    // Lasers chip ore off asteroids instead of splitting them, collect enough before time runs out
+    Mining,
+}
+impl GameMode {
+    const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::Pressure,
+        GameMode::Waves,
+        GameMode::Versus,
+        GameMode::Mining,
+    ];
+
+    fn from_name(name: &str) -> Option<GameMode> {
+        Self::ALL.into_iter().find(|m| format!("{:?}", m) == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PausePolicy {
+    Unlimited,
+    // Pauses and total paused seconds allowed before the run stops counting
+    // toward rankings. Resuming counts down so pausing can't buffer inputs.
+    Limited { pauses: u32, seconds: f32 },
+}
+impl PausePolicy {
+    const RANKED: PausePolicy = PausePolicy::Limited {
+        pauses: 3,
+        seconds: 30.0,
+    };
+    const RESUME_COUNTDOWN: f32 = 1.0;
+}
+
+// Asteroid rolled ahead of spawning, so a wave can be previewed before it arrives
+#[derive(Clone, Copy)]
+struct AsteroidTemplate {
+    position: Vec2,
+    velocity: Vec2,
+    radius: f32,
+}
+
+// Per-frame counts from the render pass, shown in the debug overlay
+#[derive(Clone, Copy, Default)]
+struct RenderStats {
+    // Meshes drawn for the starfield, at most one per layer
+    background_draw_calls: u32,
+    // Shape draw calls issued for asteroids
+    asteroid_draw_calls: u32,
+    asteroids_culled: u32,
+    // Asteroids drawn with a cheaper level of detail
+    asteroids_simplified: u32,
+}
+
+// HUD text that's only formatted again when the value it shows changes
+#[derive(Clone, Default)]
+struct CachedText {
+    value: Cell<Option<u32>>,
+    text: RefCell<String>,
+}
+impl CachedText {
+    fn get(&self, value: u32, format: impl FnOnce() -> String) -> Ref<'_, String> {
+        if self.value.get() != Some(value) {
+            self.value.set(Some(value));
+            *self.text.borrow_mut() = format();
+        }
+        self.text.borrow()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AsteroidRemovalReason {
+    // Drifted off the screen
+    Escaped,
+    // Rammed the ship, which takes the damage
+    HitPlayer,
+    // Counts as a kill. Rocks that split are left breaking apart and come back
+    // as Shattered once they're done.
+    Destroyed(KilledBy),
+    // Finished breaking apart and splits into its pieces
+    Shattered,
+}
+
+// What a destroyed asteroid is credited to, which decides what it's worth
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KilledBy {
+    // Scores, rolls for drops and builds focus
+    Laser,
+    // Scores the plain points
+    Deflector,
+    // Crumbled after its ore was chipped off, already paid out in ore
+    Mining,
+}
+
+// Asteroid to take out at the end of the tick, collected while checking collisions
+#[derive(Clone, Copy)]
+struct AsteroidRemoval {
+    id: u32,
+    reason: AsteroidRemovalReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LaserRemovalReason {
+    Hit { asteroid: u32 },
+    Derelict,
+    Offscreen,
+}
+
+#[derive(Clone, Copy)]
+struct LaserRemoval {
+    id: u32,
+    reason: LaserRemovalReason,
+}
+
+// Brief arrow shown where an asteroid left the screen
+#[derive(Clone)]
+struct EscapeMarker {
+    position: Vec2,
+    direction: Vec2,
+    life: Countdown,
+}
+impl EscapeMarker {
+    const DURATION: f32 = 1.0;
+
+    fn render(&self) {
+        let color = Color::new(1.0, 1.0, 1.0, self.life.remaining() / Self::DURATION);
+        let tip = self.position;
+        let back = tip - self.direction * 14.0;
+        let side = self.direction.perp() * 6.0;
+        draw_triangle_lines(tip, back + side, back - side, stroke(1.0), color);
+    }
+}
+
+// Ripple spreading from where something hit a wall
+#[derive(Clone)]
+struct WallRipple {
+    position: Vec2,
+    // Pointing into the field
+    normal: Vec2,
+    life: Countdown,
+}
+impl WallRipple {
+    const DURATION: f32 = 0.5;
+
+    fn new(position: Vec2, normal: Vec2) -> WallRipple {
+        WallRipple {
+            position,
+            normal,
+            life: Countdown::new(Self::DURATION),
+        }
+    }
+
+    fn render(&self, color: Color) {
+        let progress = 1.0 - self.life.remaining() / Self::DURATION;
+        let radius = 6.0 + 34.0 * progress;
+        // Half circle opening into the field
+        let rotation = self.normal.to_angle().to_degrees() - 90.0;
+        draw_arc(
+            self.position.x,
+            self.position.y,
+            24,
+            radius,
+            rotation,
+            stroke(1.5),
+            180.0,
+            Color {
+                a: 1.0 - progress,
+                ..color
+            },
+        );
+    }
+}
+
+// Ring of asteroids about to appear around the ship, shown as chevrons
+#[derive(Clone)]
+struct RingAmbush {
+    plan: Vec<AsteroidTemplate>,
+    warning: Countdown,
+}
+impl RingAmbush {
+    // Blinking chevrons pointing the way each asteroid will come in
+    fn render(&self, time: f32) {
+        let alpha = 0.5 + 0.5 * (time * 12.0).sin().abs();
+        let color = Color::new(1.0, 0.3, 0.2, alpha);
+        for t in &self.plan {
+            let forward = t.velocity.normalize_or_zero() * t.radius * 0.6;
+            let side = forward.perp() * 0.8;
+            let tip = t.position + forward;
+            for wing in [t.position - side, t.position + side] {
+                draw_line(wing.x, wing.y, tip.x, tip.y, stroke(2.0), color);
+            }
+            draw_circle_lines(t.position.x, t.position.y, t.radius, stroke(1.0), color);
+        }
+    }
+}
+
+// Temporary zone where kills are worth more, opened periodically in Wave mode
+#[derive(Clone)]
+struct GoldRush {
+    position: Vec2,
+    radius: f32,
+    life: Countdown,
+    duration: f32,
+}
+impl GoldRush {
+    fn contains(&self, point: Vec2) -> bool {
+        geom::distance_sq(self.position, point) < self.radius * self.radius
+    }
+
+    // Dashed rotating circle with an arc counting down the time left
+    fn render(&self, time: f32) {
+        let dashes = 24;
+        let dash_degrees = 360.0 / dashes as f32;
+        for i in 0..dashes {
+            let rotation = time * 20.0 + i as f32 * dash_degrees;
+            draw_arc(
+                self.position.x,
+                self.position.y,
+                4,
+                self.radius,
+                rotation,
+                stroke(1.0),
+                dash_degrees / 2.0,
+                GOLD,
+            );
+        }
+        let progress = self.life.remaining() / self.duration;
+        draw_arc(
+            self.position.x,
+            self.position.y,
+            48,
+            self.radius + 6.0,
+            270.0,
+            stroke(2.0),
+            360.0 * progress,
+            GOLD,
+        );
     }
 }
 
+#[derive(Clone)]
 struct Game {
+    settings: Settings,
+    balance: Balance,
+    mode: GameMode,
+    seed: u64,
+    rng: Rng,
+    // Separate from `rng` so cosmetic effects never change the simulation. Lives
+    // on the game so replays clone it and draw the same particles.
+    effects_rng: Rng,
     width: f32,
     height: f32,
     center: Vec2,
@@ -164,19 +1100,210 @@ struct Game {
     asteroids: Vec<Asteroid>,
     asteroid_counter: u32,
     max_asteroids: usize,
+    // Multiplier for asteroid sizes and speeds relative to a 1080p screen
+    size_scale: f32,
     lasers: Vec<Laser>,
+    power_ups: Vec<PowerUp>,
+    escape_markers: Vec<EscapeMarker>,
+    // Asteroids that left the screen without being destroyed this run
+    escaped_count: u32,
+    asteroids_destroyed: u32,
+    render_stats: Cell<RenderStats>,
+    score_text: CachedText,
+    health_text: CachedText,
+    // Time survived without damage, counting toward field repair
+    field_repair: RepeatingTimer,
     laser_counter: u32,
+    // Lasers fired this run and how many of them hit an asteroid
+    shots_fired: u32,
+    shots_hit: u32,
+    // Ammo economy: lasers left, progress toward the next regenerated one, ammo
+    // picked up as salvage this run and seconds left flashing an empty magazine
+    ammo: u32,
+    ammo_regen: RepeatingTimer,
+    ammo_salvaged: u32,
+    dry_fire_flash: Countdown,
+    // The Deflector arc, and the ship's drift from recoiling off it
+    deflector: Countdown,
+    ship_knockback: Vec2,
+    // Seconds between shots, and the wait before the next one
     laser_cooldown: f32,
-    laser_cooldown_remaining: f32,
-    score: u32,
+    fire_cooldown: Cooldown,
+    // Fractional so it can decay smoothly, displayed rounded down
+    score: f32,
+    // Smoothed points gained per second, negative while decay is winning
+    score_trend: f32,
+    // Seconds the Pressure target has been held continuously
+    hold_time: f32,
+    win_score: u32,
+    // Within this many points of `win_score` spawning guarantees a reachable target
+    endgame_margin: u32,
+    // Simulation seconds elapsed this run
+    elapsed: f32,
+    // Time the simulation stays frozen after a big impact
+    hitstop: Countdown,
+    // Simulation speed multiplier and the real time until it returns to 1
+    time_scale: f32,
+    slow_motion: Countdown,
+    toasts: Vec<Toast>,
+    feed: EventFeed,
+    // Wave mode: the current wave and the next one, rolled when this one is cleared
+    wave: u32,
+    next_wave: Vec<AsteroidTemplate>,
+    // Time left on the "Wave N cleared" screen
+    wave_clear: Countdown,
+    // Current wave's start time, starting asteroid count and whether the ship was damaged in it
+    wave_started_at: f32,
+    wave_asteroid_count: usize,
+    wave_damage_taken: bool,
+    // Bonuses awarded for the last cleared wave, shown on the clear screen
+    wave_bonuses: Vec<(&'static str, f32)>,
+    // Seconds each wave took to clear this run and how many were cleared without damage
+    wave_clear_times: Vec<f32>,
+    perfect_waves: u32,
+    // Escaped asteroids and the seconds until they come back from the opposite edge
+    reentries: Vec<(f32, Asteroid)>,
+    // Ticks simulated this run, used to line up logged events
+    frame: u64,
+    // Debug events from the last tick, drained into the log by the app
+    events: Vec<String>,
+    // Versus: which player (1 or 2) is flying, swapped for rematches
+    versus_defender: u8,
+    attacker_budget: f32,
+    attacker_cooldown: Cooldown,
+    // Wait for the next gold rush to open, rolled from the run's RNG
+    next_event_in: Cooldown,
+    gold_rush: Option<GoldRush>,
+    // Decides what to spawn when `Settings::director` is on
+    director: SpawnDirector,
+    // Wait for the next derelict to drift in, and the one crossing now
+    next_derelict_in: Cooldown,
+    derelict: Option<Derelict>,
+    // Wait for the next ring ambush to be due, and the one being warned about
+    next_ring_in: Cooldown,
+    ring: Option<RingAmbush>,
+    // `elapsed` when the last gold rush or ring ambush ended, to space them out
+    last_major_event: f32,
+    // Focus meter from 0 to 1, filled by kills and drained while slowing time
+    focus: f32,
+    focus_active: bool,
+    // Off once the run is over, leaving only the world simulating behind the results
+    simulate_player: bool,
+    // Mining mode: ore picked up this run
+    ore: u32,
+    // Weekly challenge rules this run is played under, kept across restarts
+    challenge: Option<Challenge>,
+    // Wave a practice run starts from, kept across restarts. Practice runs play
+    // the same but aren't recorded.
+    practice_wave: Option<u32>,
+    particles: Vec<Particle>,
+    debris: Vec<Debris>,
+    // Particles and debris the death explosion started with
+    death_effects_spawned: usize,
+    wall_ripples: Vec<WallRipple>,
+    // Where the ship flew and was hit this run, for the results
+    heatmap: Heatmap,
+    // Distance the ship covered per second last tick
+    ship_speed: f32,
+    // Whether the ship was against a wall last tick, so sliding along one only ripples once
+    ship_on_wall: bool,
+    // Strafe thrusters are unlocked in the profile, set by the app for each run.
+    // `strafe` is the side they pushed the ship to last tick: -1 left, 1 right, 0 neither.
+    strafe_thrusters: bool,
+    strafe: f32,
+    // Flash at the ship's nose after firing
+    muzzle_flash: Countdown,
+    // Set from the developer console, kept across runs
+    console_time_scale: f32,
+    // The developer console changed this run, so it isn't recorded
+    console_used: bool,
+    // Restarted on the previous run's seed, so its layout was already known
+    retried_seed: bool,
+    // Pauses and seconds spent paused this run, and whether that went over the
+    // pause policy's budget so the run no longer counts toward rankings
+    pauses_used: u32,
+    pause_time_used: f32,
+    pause_forfeited: bool,
+    starfield: Starfield,
 }
 impl Game {
-    fn new() -> Game {
-        let width = screen_width();
-        let height = screen_height();
+    const MAX_HITSTOP: f32 = 0.1;
+    // Focus can only be started with at least this much of the meter
+    const FOCUS_MIN_TO_START: f32 = 0.5;
+    // Radius (at 1080p) a Mining laser hit takes off an asteroid, and the size it crumbles below
+    const MINING_CHIP: f32 = 4.0;
+    // Smallest play field simulated, the window can be smaller or even 0x0 when minimized
+    const MIN_WIDTH: f32 = 320.0;
+    const MIN_HEIGHT: f32 = 240.0;
+    const WAVE_CLEAR_DURATION: f32 = 3.0;
+    const REENTRY_DELAY: f32 = 1.5;
+    // Storm sandbox asteroid count and size mix, all small enough to weave through
+    const STORM_ASTEROIDS: usize = 500;
+    const STORM_SIZE_WEIGHTS: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
+    // Half-angle in degrees of the laser spread at full speed
+    const MAX_LASER_SPREAD: f32 = 4.0;
+    // Cosmetic particles alive at once, normally and in a storm
+    const MAX_PARTICLES: usize = 256;
+    const STORM_MAX_PARTICLES: usize = 2048;
+    // Sparks in the ship's death explosion, on top of the normal cap
+    const DEATH_PARTICLES: usize = 48;
+    // Sparks thrown out when a breaking asteroid shatters
+    const SHATTER_PARTICLES: usize = 12;
+    const RESULTS_SLIDE_DURATION: f32 = 0.4;
+    // Timed runs count down big for this many seconds at the end, and hold
+    // the last frame this long before the results
+    const FINAL_SECONDS: f32 = 10.0;
+    const TIMEOUT_FREEZE: f32 = 0.5;
+    // Trajectory lanes: asteroids within this distance of the ship at 1080p,
+    // at most this many of them, projected this many seconds ahead in dots
+    const LANE_RANGE: f32 = 400.0;
+    const LANE_MAX: usize = 12;
+    const LANE_HORIZON: f32 = 1.5;
+    const LANE_DOTS: usize = 15;
+    // Deflector arc radius around the ship's center and half its angle
+    const DEFLECTOR_RADIUS: f32 = 42.0;
+    const DEFLECTOR_HALF_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+    // Share of the recoil speed lost per second
+    const KNOCKBACK_DAMPING: f32 = 4.0;
+    // Seconds the magazine flashes after firing with no ammo
+    const DRY_FIRE_FLASH: f32 = 0.25;
+    // Seconds the muzzle flash lasts, two ticks at 60fps
+    const MUZZLE_FLASH: f32 = 2.0 / 60.0;
+    // Side thrust as a share of the main thrust
+    const STRAFE_THRUST: f32 = 0.4;
+    // Walls mode: asteroids enter at least this many radii from a corner, and the
+    // ship counts as cornered within this many pixels of one
+    const CORNER_EXCLUSION: f32 = 1.5;
+    const CORNER_OCCUPIED_RANGE: f32 = 120.0;
+    // Ship spawn search: grid spacing and rings around the center at 1080p, how far
+    // ahead asteroid paths are projected, the room the ship needs around it and
+    // the lowest safety score accepted
+    const SPAWN_GRID_STEP: f32 = 120.0;
+    const SPAWN_GRID_RINGS: i32 = 3;
+    const SPAWN_HORIZON: f32 = 2.0;
+    const SPAWN_CLEARANCE: f32 = 30.0;
+    const SPAWN_MIN_SAFETY: f32 = 1.0;
+    // Seconds between the end of one gold rush or ring ambush and the start of the next
+    const MAJOR_EVENT_GAP: f32 = 10.0;
+    // Radius (at 1080p) and budget cost of each asteroid size the attacker can throw
+    const LAUNCH_SIZES: [(f32, f32); 3] = [(15.0, 1.0), (35.0, 2.0), (70.0, 4.0)];
+    // How close to a screen edge a throw has to start
+    const LAUNCH_EDGE_MARGIN: f32 = 80.0;
+
+    // Field of a fixed size, the window's once it has settled or a headless one
+    fn with_size(seed: u64, settings: Settings, width: f32, height: f32) -> Game {
+        let width = width.max(Self::MIN_WIDTH);
+        let height = height.max(Self::MIN_HEIGHT);
         let center = Vec2::new(width / 2.0, height / 2.0);
+        let effects_seed = Self::effects_seed(&settings, seed);
 
         let mut game = Game {
+            settings,
+            balance: Balance::default(),
+            mode: GameMode::Classic,
+            seed,
+            rng: Rng::new(seed),
+            effects_rng: Rng::new(effects_seed),
             width,
             height,
             center,
@@ -184,353 +1311,4855 @@ impl Game {
             player_speed: 300.0,
             asteroids: vec![],
             asteroid_counter: 0,
-            max_asteroids: 20,
+            max_asteroids: 0,
+            size_scale: 1.0,
             lasers: vec![],
+            power_ups: vec![],
+            escape_markers: vec![],
+            escaped_count: 0,
+            asteroids_destroyed: 0,
+            render_stats: Cell::new(RenderStats::default()),
+            score_text: CachedText::default(),
+            health_text: CachedText::default(),
+            field_repair: RepeatingTimer::default(),
             laser_counter: 0,
+            shots_fired: 0,
+            shots_hit: 0,
+            ammo: 0,
+            ammo_regen: RepeatingTimer::default(),
+            ammo_salvaged: 0,
+            dry_fire_flash: Countdown::default(),
+            deflector: Countdown::default(),
+            ship_knockback: Vec2::ZERO,
             laser_cooldown: 0.2,
-            laser_cooldown_remaining: 0.0,
-            score: 0,
+            fire_cooldown: Cooldown::default(),
+            score: 0.0,
+            score_trend: 0.0,
+            hold_time: 0.0,
+            win_score: 100,
+            endgame_margin: 5,
+            elapsed: 0.0,
+            hitstop: Countdown::default(),
+            time_scale: 1.0,
+            slow_motion: Countdown::default(),
+            toasts: vec![],
+            feed: EventFeed::default(),
+            wave: 1,
+            next_wave: vec![],
+            wave_clear: Countdown::default(),
+            wave_started_at: 0.0,
+            wave_asteroid_count: 0,
+            wave_damage_taken: false,
+            wave_bonuses: vec![],
+            wave_clear_times: vec![],
+            perfect_waves: 0,
+            reentries: vec![],
+            frame: 0,
+            events: vec![],
+            versus_defender: 1,
+            attacker_budget: 0.0,
+            attacker_cooldown: Cooldown::default(),
+            next_event_in: Cooldown::default(),
+            gold_rush: None,
+            director: SpawnDirector::new(seed),
+            next_derelict_in: Cooldown::default(),
+            derelict: None,
+            next_ring_in: Cooldown::default(),
+            ring: None,
+            last_major_event: 0.0,
+            focus: 0.0,
+            focus_active: false,
+            simulate_player: true,
+            ore: 0,
+            challenge: None,
+            practice_wave: None,
+            particles: vec![],
+            debris: vec![],
+            death_effects_spawned: 0,
+            wall_ripples: vec![],
+            heatmap: Heatmap::default(),
+            ship_speed: 0.0,
+            ship_on_wall: false,
+            strafe_thrusters: false,
+            strafe: 0.0,
+            muzzle_flash: Countdown::default(),
+            console_time_scale: 1.0,
+            console_used: false,
+            retried_seed: false,
+            pauses_used: 0,
+            pause_time_used: 0.0,
+            pause_forfeited: false,
+            starfield: Starfield::default(),
         };
+        game.resize(width, height);
         game.generate_asteroids();
         game
     }
 
-    fn reset(&mut self) {
-        let width = screen_width();
-        let height = screen_height();
-        let center = Vec2::new(width / 2.0, height / 2.0);
+    fn reset(&mut self, seed: u64) {
+        let center = self.center;
+
+        // Undo the previous run's challenge rules
+        self.balance = Balance::default();
 
+        self.seed = seed;
+        self.rng = Rng::new(seed);
+        self.effects_rng = Rng::new(Self::effects_seed(&self.settings, seed));
+        self.fire_cooldown = Cooldown::default();
         self.asteroids = vec![];
-        self.generate_asteroids();
         self.lasers = vec![];
+        self.power_ups = vec![];
+        self.escape_markers = vec![];
+        self.escaped_count = 0;
+        self.asteroids_destroyed = 0;
+        self.field_repair.reset();
         self.player = Ship::new(center.x, center.y);
-        self.score = 0;
-    }
-
+        self.score_trend = 0.0;
+        self.hold_time = 0.0;
+        self.elapsed = 0.0;
+        self.hitstop = Countdown::default();
+        self.time_scale = 1.0;
+        self.slow_motion = Countdown::default();
+        self.toasts = vec![];
+        self.feed = EventFeed::default();
+        self.wave = self.practice_wave.unwrap_or(1);
+        self.score = self.practice_start_score();
+        self.next_wave = vec![];
+        self.wave_clear = Countdown::default();
+        self.wave_bonuses = vec![];
+        self.wave_clear_times = vec![];
+        self.perfect_waves = 0;
+        self.reentries = vec![];
+        self.frame = 0;
+        self.events = vec![];
+        self.attacker_budget = self.balance.versus_budget_max;
+        self.attacker_cooldown = Cooldown::default();
+        self.gold_rush = None;
+        self.schedule_gold_rush();
+        self.director = SpawnDirector::new(seed);
+        self.derelict = None;
+        self.schedule_derelict();
+        self.ring = None;
+        self.schedule_ring();
+        self.last_major_event = 0.0;
+        self.focus = 0.0;
+        self.focus_active = false;
+        self.simulate_player = true;
+        self.ore = 0;
+        self.particles = vec![];
+        self.debris = vec![];
+        self.death_effects_spawned = 0;
+        self.wall_ripples = vec![];
+        self.ship_on_wall = false;
+        self.ship_speed = 0.0;
+        self.strafe = 0.0;
+        self.heatmap = Heatmap::default();
+        self.muzzle_flash = Countdown::default();
+        self.console_used = false;
+        self.retried_seed = false;
+        self.shots_fired = 0;
+        self.shots_hit = 0;
+        self.ammo = self.balance.ammo_start;
+        self.ammo_regen.reset();
+        self.ammo_salvaged = 0;
+        self.dry_fire_flash = Countdown::default();
+        self.deflector = Countdown::default();
+        self.ship_knockback = Vec2::ZERO;
+        self.pauses_used = 0;
+        self.pause_time_used = 0.0;
+        self.pause_forfeited = false;
+        self.starfield = self.build_starfield();
+        if let Some(challenge) = self.challenge.clone() {
+            for modifier in challenge.modifiers {
+                modifier.apply(self);
+            }
+            // Picks up a changed asteroid density
+            self.resize(self.width, self.height);
+        }
+        log_event!(self, "reset seed={} mode={:?}", seed, self.mode);
+
+        if self.mode == GameMode::Waves {
+            let plan = self.plan_wave(self.wave);
+            self.start_wave(&plan);
+        } else {
+            self.generate_asteroids();
+        }
+        if let Some(point) = self.spawn_point() {
+            self.player.position += point - self.player.center();
+        }
+    }
+
+    // Candidate ship positions with their safety scores: the center first, then
+    // square rings of grid points around it that fit in the field
+    fn spawn_candidates(&self) -> Vec<(Vec2, f32)> {
+        let step = Self::SPAWN_GRID_STEP * self.size_scale;
+        let mut offsets = vec![(0, 0)];
+        for ring in 1..=Self::SPAWN_GRID_RINGS {
+            for x in -ring..=ring {
+                for y in -ring..=ring {
+                    if x.abs().max(y.abs()) == ring {
+                        offsets.push((x, y));
+                    }
+                }
+            }
+        }
+        let margin = step / 2.0;
+        offsets
+            .into_iter()
+            .map(|(x, y)| self.center + Vec2::new(x as f32, y as f32) * step)
+            .filter(|p| {
+                p.x >= margin
+                    && p.y >= margin
+                    && p.x <= self.width - margin
+                    && p.y <= self.height - margin
+            })
+            .map(|p| (p, self.spawn_safety(p)))
+            .collect()
+    }
+
+    // Seconds until the first asteroid on its current course would reach the
+    // ship at `position`, up to the horizon, plus up to half a second for being
+    // away from the edges
+    fn spawn_safety(&self, position: Vec2) -> f32 {
+        let clearance = Self::SPAWN_CLEARANCE * self.size_scale;
+        let time = self
+            .asteroids
+            .iter()
+            .filter_map(|a| {
+                geom::earliest_approach(
+                    position,
+                    a.position,
+                    a.velocity,
+                    a.radius + clearance,
+                    Self::SPAWN_HORIZON,
+                )
+            })
+            .fold(Self::SPAWN_HORIZON, f32::min);
+        let edge = position
+            .x
+            .min(position.y)
+            .min(self.width - position.x)
+            .min(self.height - position.y);
+        time + 0.5 * (edge / self.center.min_element()).min(1.0)
+    }
+
+    // Safest candidate, earlier ones winning ties, or None when even that one is
+    // too dangerous and spawning should wait a moment
+    fn spawn_point(&self) -> Option<Vec2> {
+        let mut best: Option<(Vec2, f32)> = None;
+        for (position, score) in self.spawn_candidates() {
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((position, score));
+            }
+        }
+        best.filter(|(_, score)| *score >= Self::SPAWN_MIN_SAFETY)
+            .map(|(position, _)| position)
+    }
+
+    // Spawn candidates shaded from red (about to be hit) to green, the pick circled
+    fn render_spawn_candidates(&self) {
+        let best_possible = Self::SPAWN_HORIZON + 0.5;
+        for (position, score) in self.spawn_candidates() {
+            let t = (score / best_possible).clamp(0.0, 1.0);
+            let color = Color::new(1.0 - t, t, 0.0, 0.8);
+            draw_circle(position.x, position.y, 4.0, color);
+        }
+        if let Some(point) = self.spawn_point() {
+            draw_circle_lines(point.x, point.y, 10.0, 1.0, WHITE);
+        }
+    }
+
+    // Seed for the effects RNG, tied to the run's seed only with deterministic effects on
+    fn effects_seed(settings: &Settings, seed: u64) -> u64 {
+        if settings.deterministic_effects {
+            // Derived rather than equal, so the two streams don't mirror each other
+            seed ^ 0x9e37_79b9_7f4a_7c15
+        } else {
+            fresh_seed()
+        }
+    }
+
+    // Room left under the particle cap, with smoke counted double
+    fn particle_room(&self) -> usize {
+        let cap = if self.settings.storm {
+            Self::STORM_MAX_PARTICLES
+        } else {
+            Self::MAX_PARTICLES
+        };
+        let used: usize = self.particles.iter().map(Particle::weight).sum();
+        cap.saturating_sub(used)
+    }
+
+    // Sparks fanning out from `position` within `spread` radians of `direction`
+    fn spawn_burst(&mut self, position: Vec2, direction: Vec2, count: usize, spread: f32) {
+        for _ in 0..count.min(self.particle_room()) {
+            let angle = self.effects_rng.gen_range(-spread, spread);
+            let speed = self.effects_rng.gen_range(100.0, 200.0);
+            let velocity = Vec2::from_angle(angle).rotate(direction) * speed;
+            self.particles.push(Particle::new(position, velocity));
+        }
+    }
+
+    // Slow gray puffs of about `base_size` drifting out of `position` that
+    // linger after the sparks are gone. Reduced motion leaves them out.
+    fn spawn_smoke(&mut self, position: Vec2, count: usize, base_size: f32) {
+        if self.settings.reduced_motion {
+            return;
+        }
+        for _ in 0..count.min(self.particle_room() / 2) {
+            let angle = self.effects_rng.gen_range(0.0, std::f32::consts::TAU);
+            let speed = self.effects_rng.gen_range(5.0, 25.0);
+            let offset = Vec2::from_angle(angle) * self.effects_rng.gen_range(0.0, base_size);
+            let size = base_size * self.effects_rng.gen_range(0.6, 1.0);
+            self.particles.push(Particle::smoke(
+                position + offset,
+                Vec2::from_angle(angle) * speed,
+                size,
+            ));
+        }
+    }
+
+    // Break the ship apart into its hull edges and a cloud of slow sparks
+    fn spawn_death_effect(&mut self) {
+        let vertices = self.player.vertices();
+        let center = self.player.center();
+        for i in 0..vertices.len() {
+            let (start, end) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            let midpoint = (start + end) / 2.0;
+            let speed = self.effects_rng.gen_range(20.0, 60.0);
+            self.debris.push(Debris {
+                position: midpoint,
+                half: (end - start) / 2.0,
+                velocity: (midpoint - center).normalize_or_zero() * speed,
+                spin: self.effects_rng.gen_range(-3.0, 3.0),
+                life: Countdown::new(Debris::LIFETIME),
+            });
+        }
+        for _ in 0..Self::DEATH_PARTICLES {
+            let angle = self.effects_rng.gen_range(0.0, std::f32::consts::TAU);
+            let speed = self.effects_rng.gen_range(20.0, 120.0);
+            let lifetime = self.effects_rng.gen_range(1.0, 2.5);
+            self.particles.push(Particle {
+                position: center,
+                velocity: Vec2::from_angle(angle) * speed,
+                life: Countdown::new(lifetime),
+                lifetime,
+                death: true,
+                ..Particle::new(center, Vec2::ZERO)
+            });
+        }
+        self.death_effects_spawned = vertices.len() + Self::DEATH_PARTICLES;
+        let count = self.effects_rng.gen_range(6.0, 10.99) as usize;
+        self.spawn_smoke(center, count, 10.0 * self.size_scale);
+    }
+
+    // Count down breaking asteroids and shatter the finished ones into two halves
+    fn tick_breaking(&mut self, frame_time: f32) {
+        for a in self.asteroids.iter_mut() {
+            if let Some(remaining) = &mut a.breaking {
+                *remaining -= frame_time;
+            }
+        }
+        let shattered = self
+            .asteroids
+            .iter()
+            .filter(|a| a.breaking.is_some_and(|r| r <= 0.0))
+            .map(|a| AsteroidRemoval {
+                id: a.id,
+                reason: AsteroidRemovalReason::Shattered,
+            })
+            .collect();
+        self.apply_asteroid_removals(shattered);
+    }
+
+    // Take out everything collected over the tick in id order, so the pieces,
+    // reentries, scores and drops come out the same whatever order the
+    // collisions were found in. An asteroid removed twice keeps the last reason
+    // given. Returns the hit-stop the removals call for.
+    fn apply_asteroid_removals(&mut self, mut removals: Vec<AsteroidRemoval>) -> f32 {
+        removals.sort_by_key(|r| r.id);
+        removals.reverse();
+        removals.dedup_by_key(|r| r.id);
+        removals.reverse();
+
+        let mut removed = vec![];
+        for removal in &removals {
+            if let Some(i) = self.asteroids.iter().position(|a| a.id == removal.id) {
+                log_event!(self, "remove id={} reason={:?}", removal.id, removal.reason);
+                let breaking = matches!(removal.reason, AsteroidRemovalReason::Destroyed(_))
+                    && self.asteroids[i].breaking.is_some();
+                let a = if breaking {
+                    self.asteroids[i].clone()
+                } else {
+                    self.asteroids.remove(i)
+                };
+                removed.push((a, removal.reason));
+            }
+        }
+        let mut hitstop: f32 = 0.0;
+        for (a, reason) in removed {
+            match reason {
+                AsteroidRemovalReason::Escaped if self.mode == GameMode::Waves => {
+                    self.queue_reentry(a)
+                }
+                AsteroidRemovalReason::Escaped => self.record_escape(a.position, a.velocity),
+                AsteroidRemovalReason::Shattered => self.shatter(a),
+                AsteroidRemovalReason::HitPlayer => {
+                    let damaged = self.player.take_hit();
+                    log_event!(self, "ship_hit asteroid={} damaged={}", a.id, damaged);
+                    if damaged {
+                        hitstop = hitstop.max(0.08);
+                        self.wave_damage_taken = true;
+                        self.feed.push(GameEvent::ShipHit {
+                            health: self.player.health,
+                        });
+                        if self.player.health == 0 {
+                            self.spawn_death_effect();
+                        }
+                    }
+                }
+                AsteroidRemovalReason::Destroyed(KilledBy::Laser) => {
+                    hitstop = hitstop.max(a.size.hitstop());
+                    self.roll_drops(&a);
+                    let points = match &self.gold_rush {
+                        Some(zone) if zone.contains(a.position) => {
+                            self.balance.gold_rush_multiplier
+                        }
+                        _ => 1.0,
+                    };
+                    self.credit_kill(&a, points);
+                    self.focus = (self.focus + self.balance.focus_per_kill).min(1.0);
+                }
+                AsteroidRemovalReason::Destroyed(KilledBy::Deflector) => {
+                    self.credit_kill(&a, 1.0);
+                }
+                AsteroidRemovalReason::Destroyed(KilledBy::Mining) => {
+                    self.asteroids_destroyed += 1;
+                }
+            }
+        }
+        hitstop
+    }
+
+    fn credit_kill(&mut self, a: &Asteroid, points: f32) {
+        self.score += points;
+        self.feed.push(GameEvent::AsteroidDestroyed {
+            size: a.size,
+            points,
+        });
+        self.asteroids_destroyed += 1;
+    }
+
+    // Power-ups left behind by a rock shot down
+    fn roll_drops(&mut self, a: &Asteroid) {
+        // One roll for both, a Deflector on the band just past Repair's
+        let drop = self.rng.gen_range(0.0, 1.0);
+        let repair = self.balance.repair_drop_chance;
+        let kind = if drop < repair {
+            Some(PowerUpKind::Repair)
+        } else if drop < repair + self.balance.deflector_drop_chance {
+            Some(PowerUpKind::Deflector)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            self.power_ups
+                .push(PowerUp::new(kind, a.position, a.velocity * 0.25));
+        }
+        // Only rolled with the economy on, so other runs play out as before
+        if self.settings.ammo && self.rng.gen_range(0.0, 1.0) < self.balance.salvage_drop_chance {
+            let (min, max) = self.balance.salvage_ammo;
+            let amount = min + self.rng.next_u32() % (max - min + 1);
+            self.power_ups.push(PowerUp::new(
+                PowerUpKind::Salvage(amount),
+                a.position,
+                a.velocity * 0.25,
+            ));
+        }
+    }
+
+    // Passive trickle of ammo while the magazine isn't full
+    fn tick_ammo_regen(&mut self, frame_time: f32) {
+        if self.ammo >= self.balance.ammo_max {
+            self.ammo_regen.reset();
+            return;
+        }
+        let regenerated = self
+            .ammo_regen
+            .tick(frame_time, self.balance.ammo_regen_interval);
+        self.ammo = (self.ammo + regenerated).min(self.balance.ammo_max);
+    }
+
+    // Runs with the console used, in the storm sandbox or practising a wave
+    // don't count toward the profile
+    fn is_recorded(&self) -> bool {
+        !self.console_used && !self.settings.storm && self.practice_wave.is_none()
+    }
+
+    // The run as it stands, for the history
+    fn summary(&self) -> RunSummary {
+        RunSummary {
+            ended_at: miniquad::date::now(),
+            mode: format!("{:?}", self.mode),
+            seed: self.seed,
+            score: self.score(),
+            won: self.has_won(),
+            duration: self.elapsed,
+            asteroids_destroyed: self.asteroids_destroyed,
+            escaped: self.escaped_count,
+            wave: self.wave,
+            weekly: self.challenge.is_some(),
+            heatmap: self.heatmap.clone(),
+            crashed: false,
+            abandoned: false,
+        }
+    }
+
+    // Copy of the run so far for a crash report
+    fn run_state(&self) -> RunState {
+        RunState {
+            mode: self.mode,
+            seed: self.seed,
+            score: self.score(),
+            elapsed: self.elapsed,
+            frame: self.frame,
+            asteroids_destroyed: self.asteroids_destroyed,
+            escaped: self.escaped_count,
+            wave: self.wave,
+            weekly: self.challenge.is_some(),
+            asteroids: self.asteroids.len(),
+            lasers: self.lasers.len(),
+            particles: self.particles.len(),
+        }
+    }
+
+    // Share of this run's shots that hit, None before the first one
+    fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| self.shots_hit as f32 / self.shots_fired as f32)
+    }
+
+    // Split a rock that finished breaking into two halves and a burst of dust
+    fn shatter(&mut self, a: Asteroid) {
+        self.feed.push(GameEvent::AsteroidSplit);
+        // A rock can drift past the edge while it breaks. Its halves start back
+        // inside, or they'd count as escapes the moment they appear.
+        let origin = a.position.clamp(Vec2::ZERO, vec2(self.width, self.height));
+        for side in [-1.0, 1.0] {
+            self.asteroid_counter += 1;
+            let piece = Asteroid::new(
+                origin.x,
+                origin.y,
+                side * a.velocity.y / 2.0,
+                a.velocity.y,
+                a.radius / 2.0,
+                self.asteroid_counter,
+            )
+            .with_detail(&self.balance, self.size_scale, self.seed);
+            log_event!(
+                self,
+                "split id={} parent={} pos={} vel={}",
+                piece.id,
+                a.id,
+                piece.position,
+                piece.velocity
+            );
+            self.asteroids.push(piece);
+        }
+        self.spawn_burst(
+            a.position,
+            Vec2::X,
+            Self::SHATTER_PARTICLES,
+            std::f32::consts::PI,
+        );
+        if a.size.smokes() {
+            let count = self.effects_rng.gen_range(6.0, 10.99) as usize;
+            self.spawn_smoke(a.position, count, a.radius * 0.3);
+        }
+    }
+
+    // Lasers that hit count towards the run's accuracy, derelicts included
+    fn apply_laser_removals(&mut self, removals: Vec<LaserRemoval>) {
+        for removal in &removals {
+            match removal.reason {
+                LaserRemovalReason::Hit { asteroid } => {
+                    self.shots_hit += 1;
+                    log_event!(self, "laser_hit laser={} asteroid={}", removal.id, asteroid);
+                }
+                LaserRemovalReason::Derelict => {
+                    self.shots_hit += 1;
+                    log_event!(self, "laser_hit laser={} derelict", removal.id);
+                }
+                LaserRemovalReason::Offscreen => {
+                    log_event!(self, "laser_miss laser={}", removal.id);
+                }
+            }
+        }
+        self.lasers
+            .retain(|l| !removals.iter().any(|r| r.id == l.id));
+    }
+
+    // Whether most of the death explosion has faded out
+    fn death_effect_faded(&self) -> bool {
+        let live = self.debris.len() + self.particles.iter().filter(|p| p.death).count();
+        live * 5 <= self.death_effects_spawned
+    }
+
+    // Area of the world currently on screen. There is no camera yet, so it's the play field.
+    fn view_rect(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.width, self.height)
+    }
+
+    // Keep the play field in sync with the window, which can change size after startup
+    fn resize(&mut self, width: f32, height: f32) {
+        let width = width.max(Self::MIN_WIDTH);
+        let height = height.max(Self::MIN_HEIGHT);
+        self.width = width;
+        self.height = height;
+        self.center = Vec2::new(width / 2.0, height / 2.0);
+
+        let megapixels = width * height / 1_000_000.0;
+        let mut density = self.balance.asteroid_density;
+        if self.settings.boundary == BoundaryMode::Walls {
+            density *= self.balance.walls_density_scale;
+        }
+        self.max_asteroids = if self.settings.storm {
+            Self::STORM_ASTEROIDS
+        } else {
+            (density * megapixels).round() as usize
+        };
+        // Scale gently so rocks keep roughly the same size relative to the screen
+        self.size_scale = (width.min(height) / 1080.0).sqrt();
+        self.starfield = self.build_starfield();
+    }
+
+    // Stars follow the run seed so a retried seed gets the same sky
+    fn build_starfield(&self) -> Starfield {
+        Starfield::new(
+            self.width,
+            self.height,
+            self.settings.star_density,
+            self.seed ^ 0x5851_f42d_4c95_7f2d,
+        )
+    }
+
+    fn add_hitstop(&mut self, duration: f32) {
+        if self.settings.reduced_motion {
+            return;
+        }
+        self.hitstop
+            .start((self.hitstop.remaining() + duration).min(Self::MAX_HITSTOP));
+    }
+
+    fn render(&self) {
+        self.render_world(self.view_rect(), 1.0, true);
+        self.render_hud();
+    }
+
+    // Line weight multiplier: 1 up to 1080p, growing with the screen past that
+    fn stroke_scale(&self) -> f32 {
+        (self.width.min(self.height) / 1080.0).max(1.0) * self.settings.stroke_scale
+    }
+
+    // Entities inside `view`, drawn through whichever camera is active
+    fn render_world(&self, view: Rect, zoom: f32, show_ship: bool) {
+        let foreground = PALETTES[self.settings.palette].foreground;
+        let mut stats = RenderStats {
+            background_draw_calls: self.starfield.render(self.player.center()),
+            ..Default::default()
+        };
+        if self.settings.boundary == BoundaryMode::Walls {
+            let faint = Color {
+                a: 0.3,
+                ..foreground
+            };
+            draw_rectangle_lines(0.0, 0.0, self.width, self.height, stroke(2.0), faint);
+            for r in &self.wall_ripples {
+                r.render(foreground);
+            }
+        }
+        if show_ship && self.shows_lanes() {
+            self.render_lanes(foreground);
+        }
+        if show_ship {
+            for offset in self.ship_images() {
+                let mut image = self.player.clone();
+                image.position += offset;
+                image.render(foreground, &self.settings);
+            }
+            if self.strafe != 0.0 && self.player.health > 0 {
+                self.render_strafe_flame();
+            }
+            if self.muzzle_flash.is_running() {
+                let muzzle = self.player.muzzle_position();
+                let radius = 3.0 + 6.0 * (1.0 - self.muzzle_flash.remaining() / Self::MUZZLE_FLASH);
+                draw_circle_lines(muzzle.x, muzzle.y, radius, stroke(1.0), YELLOW);
+            }
+            if self.deflector.is_running() && self.player.health > 0 {
+                self.render_deflector();
+            }
+            if self.settings.laser_spread && self.player.health > 0 {
+                // Reticle ahead of the nose, opening up with the spread (exaggerated to be visible)
+                let muzzle = self.player.muzzle_position();
+                let facing = Vec2::from_angle(self.player.rotation);
+                let angle = 0.05 + self.laser_spread_angle() * 4.0;
+                for side in [-1.0, 1.0] {
+                    let direction = Vec2::from_angle(side * angle).rotate(facing);
+                    let start = muzzle + direction * 22.0;
+                    let end = muzzle + direction * 30.0;
+                    draw_line(start.x, start.y, end.x, end.y, stroke(1.0), GRAY);
+                }
+            }
+        }
+        for p in &self.particles {
+            p.render(foreground);
+        }
+        for d in &self.debris {
+            d.render(foreground);
+        }
+
+        for p in &self.power_ups {
+            p.render();
+        }
+        if let Some(d) = &self.derelict {
+            d.render(
+                foreground,
+                self.balance.derelict_scan_range * self.size_scale,
+                self.balance.derelict_scan_duration,
+            );
+        }
+        for m in &self.escape_markers {
+            m.render();
+        }
+        if let Some(zone) = &self.gold_rush {
+            zone.render(self.elapsed);
+        }
+        if let Some(ring) = &self.ring {
+            ring.render(self.elapsed);
+        }
+
+        for a in &self.asteroids {
+            if !a.is_visible(view) {
+                stats.asteroids_culled += 1;
+                continue;
+            }
+            if a.render_lod(foreground, zoom, self.settings.asteroid_fill) {
+                stats.asteroids_simplified += 1;
+            }
+            stats.asteroid_draw_calls += 1;
+        }
+        self.render_stats.set(stats);
+        for l in &self.lasers {
+            l.render(foreground);
+        }
+    }
+
+    fn render_hud(&self) {
+        let score_color = if self.mode == GameMode::Pressure && self.score_trend < 0.0 {
+            RED
+        } else {
+            WHITE
+        };
+        let score = self.score();
+        let score_text = self.score_text.get(score, || format!("Score: {}", score));
+        // Rows and columns grow with the text scale, the health column moves
+        // over if the score has grown into it
+        let top = ui(28.0);
+        let ring_y = ui(19.0);
+        let bar_y = ui(38.0);
+        let second_row = ui(56.0);
+        let score_dimensions = draw_text(&score_text, 10.0, top, ui(28.0), score_color);
+        let mut score_right = 10.0 + score_dimensions.width;
+
+        if self.mode == GameMode::Pressure {
+            // Ring filling up while the target score is held
+            let ring_x = score_right + ui(16.0);
+            score_right = ring_x + ui(8.0);
+            let progress = self.hold_time / self.balance.pressure_hold_duration;
+            draw_circle_lines(ring_x, ring_y, ui(8.0), 1.0, GRAY);
+            draw_arc(
+                ring_x,
+                ring_y,
+                24,
+                ui(8.0),
+                270.0,
+                2.0,
+                360.0 * progress,
+                WHITE,
+            );
+            draw_text(
+                &format!("Hold {}", self.balance.pressure_target),
+                10.0,
+                second_row,
+                ui(20.0),
+                GRAY,
+            );
+        }
+
+        if self.mode == GameMode::Waves {
+            draw_text(
+                &format!("Wave {}", self.wave),
+                10.0,
+                second_row,
+                ui(20.0),
+                GRAY,
+            );
+        }
+        let remaining = self.time_left().unwrap_or_default().ceil() as u32;
+        if self.mode == GameMode::Versus {
+            draw_text(
+                &format!("Survive {}:{:02}", remaining / 60, remaining % 60),
+                10.0,
+                second_row,
+                ui(20.0),
+                GRAY,
+            );
+        }
+
+        if self.mode == GameMode::Mining {
+            draw_text(
+                &format!(
+                    "Ore {} / {}   {}:{:02}",
+                    self.ore,
+                    self.balance.mining_ore_target,
+                    remaining / 60,
+                    remaining % 60
+                ),
+                10.0,
+                second_row,
+                ui(20.0),
+                GOLD,
+            );
+        }
+
+        // Progress toward the winning score, pulsing once the end is in sight
+        if self.mode == GameMode::Classic {
+            let progress = (self.score / self.win_score as f32).min(1.0);
+            let mut bar_color = Color::new(1.0, 1.0, 1.0, 0.5);
+            if self.in_endgame() {
+                bar_color.a = 0.6 + 0.4 * (get_time() as f32 * 6.0).sin();
+            }
+            draw_line(
+                10.0,
+                bar_y,
+                10.0 + ui(120.0) * progress,
+                bar_y,
+                2.0,
+                bar_color,
+            );
+        }
+        let health = self.player.health;
+        let health_text = self.health_text.get(health as u32, || {
+            format!("Health: {}", "<3 ".repeat(health))
+        });
+        let health_x = ui(150.0).max(score_right + ui(20.0));
+        let health_dimensions = draw_text(&health_text, health_x, top, ui(28.0), WHITE);
+        if self.player.health < self.player.max_health {
+            // Ring filling up toward the next field repair
+            let ring_x = health_x + health_dimensions.width + ui(8.0);
+            let progress = self
+                .field_repair
+                .progress(self.balance.field_repair_interval);
+            draw_arc(
+                ring_x,
+                ring_y,
+                24,
+                ui(8.0),
+                270.0,
+                1.0,
+                360.0 * progress,
+                GREEN,
+            );
+        }
+
+        if self.settings.ammo {
+            self.render_magazine(health_x, second_row + ui(6.0));
+        }
+
+        // Focus meter under the hearts, with a notch where it becomes usable
+        let focus_color = if self.focus_active { WHITE } else { SKYBLUE };
+        draw_line(
+            health_x,
+            bar_y,
+            health_x + ui(120.0) * self.focus,
+            bar_y,
+            2.0,
+            focus_color,
+        );
+        let notch_x = health_x + ui(120.0) * Self::FOCUS_MIN_TO_START;
+        draw_line(notch_x, bar_y - 3.0, notch_x, bar_y + 3.0, 1.0, GRAY);
+
+        if self.wave_clear.is_running() {
+            self.render_wave_clear(PALETTES[self.settings.palette].foreground);
+        }
+        self.render_final_seconds();
+
+        if let Some(challenge) = &self.challenge {
+            challenge.render_badges(self.height - 10.0);
+        }
+
+        if self.settings.hud_detail == HudDetail::Full {
+            // Under the score and health row once the text is big enough to crowd it
+            let feed_top = if ui(1.0) > 1.0 { ui(84.0) } else { 28.0 };
+            self.feed.render(self.width - 10.0, feed_top);
+        }
+
+        let mut toast_y = self.center.y - ui(120.0);
+        for t in &self.toasts {
+            t.render(toast_y);
+            toast_y += t.height();
+        }
+    }
+
+    fn tick(&mut self, frame_time: f32, input: &Input) {
+        #[cfg(debug_assertions)]
+        let score_before = self.score;
+        self.step(frame_time, input);
+        #[cfg(debug_assertions)]
+        self.check_integrity(score_before);
+    }
+
+    fn step(&mut self, frame_time: f32, input: &Input) {
+        self.frame += 1;
+        if !self.simulate_player {
+            self.tick_world(frame_time);
+            return;
+        }
+
+        // Hit-stop swallows real time before any of it reaches the simulation
+        let frame_time = frame_time * self.console_time_scale;
+        let mut frame_time = self.hitstop.absorb(frame_time);
+        if frame_time <= 0.0 {
+            return;
+        }
+
+        if self.slow_motion.is_running() {
+            self.slow_motion.tick(frame_time);
+            frame_time *= self.time_scale;
+        } else {
+            self.time_scale = 1.0;
+        }
+        // Focus slows the world down while the ship keeps moving at full speed
+        let player_time = frame_time;
+        let world_time = self.tick_focus(frame_time, input.focus);
+        // Nothing happens past the end of a timed run, it's over exactly at 0
+        let world_time = match self.time_left() {
+            Some(left) => world_time.min(left),
+            None => world_time,
+        };
+        self.elapsed += world_time;
+        if self.time_left() == Some(0.0) {
+            self.hitstop.start(Self::TIMEOUT_FREEZE);
+        }
+        let score_before = self.score;
+        let health_before = self.player.health;
+        let mut previous_vertices = self.player.vertices();
+
+        let mut position_before = self.player.position;
+        let move_distance = self.player_speed * player_time;
+        let rotation_degrees = self.turn_rate() * player_time;
+
+        // Check for movement input
+        if input.forward {
+            // Move forward
+            self.player.position.y += move_distance * self.player.rotation.sin();
+            self.player.position.x += move_distance * self.player.rotation.cos();
+        } else if input.backward {
+            // Move backward
+            self.player.position.y -= move_distance * self.player.rotation.sin();
+            self.player.position.x -= move_distance * self.player.rotation.cos();
+        }
+
+        // Side thrust at right angles to the facing, without taking the ship past
+        // the speed it has moving straight
+        self.strafe = match (self.strafe_thrusters, input.strafe_left, input.strafe_right) {
+            (true, true, _) => -1.0,
+            (true, false, true) => 1.0,
+            _ => 0.0,
+        };
+        if self.strafe != 0.0 {
+            let side = Vec2::from_angle(self.player.rotation).perp() * self.strafe;
+            let moved =
+                self.player.position - position_before + side * move_distance * Self::STRAFE_THRUST;
+            self.player.position = position_before + moved.clamp_length_max(move_distance);
+        }
+
+        if input.rotate_left {
+            // Rotate left
+            self.player.rotation =
+                geom::wrap_angle(self.player.rotation - rotation_degrees.to_radians());
+        } else if input.rotate_right {
+            // Rotate right
+            self.player.rotation =
+                geom::wrap_angle(self.player.rotation + rotation_degrees.to_radians());
+        }
+
+        // Recoil carries the ship on for a moment after the input movement
+        self.player.position += self.ship_knockback * player_time;
+        self.ship_knockback *= (1.0 - Self::KNOCKBACK_DAMPING * player_time).max(0.0);
+        self.deflector.tick(world_time);
+
+        if self.settings.boundary == BoundaryMode::Walls {
+            self.keep_ship_inside();
+        } else if self.settings.boundary == BoundaryMode::Wrap {
+            // Moved along with the ship so the sweep doesn't cross the whole field
+            let shift = self.wrap_ship();
+            position_before += shift;
+            for v in previous_vertices.iter_mut() {
+                *v += shift;
+            }
+        }
+        self.ship_speed = (self.player.position - position_before).length() / player_time;
+
+        if self.mode == GameMode::Versus {
+            self.tick_attacker(world_time, input.launch);
+        }
+
+        // Check for firing
+        self.muzzle_flash.tick(player_time);
+        self.dry_fire_flash.tick(player_time);
+        if self.settings.ammo {
+            self.tick_ammo_regen(player_time);
+        }
+        let dry = self.settings.ammo && self.ammo == 0;
+        if self.fire_cooldown.ready() && input.fire && dry {
+            // Nothing comes out, just a puff from the muzzle and the magazine flashing
+            let facing = Vec2::from_angle(self.player.rotation);
+            self.spawn_burst(self.player.muzzle_position(), facing, 1, 0.4);
+            self.dry_fire_flash.start(Self::DRY_FIRE_FLASH);
+            self.fire_cooldown.start(self.laser_cooldown);
+        } else if self.fire_cooldown.ready() && input.fire {
+            if self.settings.ammo {
+                self.ammo -= 1;
+            }
+            self.laser_counter += 1;
+            self.shots_fired += 1;
+            // Spawn point and direction both come from the ship as it is after this tick's rotation
+            let muzzle = self.player.muzzle_position();
+            let facing = Vec2::from_angle(self.player.rotation);
+            // Only the direction scatters, the laser still leaves from the muzzle
+            let direction = if self.settings.laser_spread {
+                let half_angle = self.laser_spread_angle();
+                Vec2::from_angle(self.rng.gen_range(-half_angle, half_angle)).rotate(facing)
+            } else {
+                facing
+            };
+            let fired_laser = Laser::new(
+                muzzle.x,
+                muzzle.y,
+                400.0 * direction.x,
+                400.0 * direction.y,
+                self.laser_counter,
+            );
+            self.lasers.push(fired_laser);
+            self.muzzle_flash.start(Self::MUZZLE_FLASH);
+            self.spawn_burst(muzzle, facing, 2, 0.8);
+            self.fire_cooldown.start(self.laser_cooldown);
+            if self.focus_active {
+                self.focus = (self.focus - self.balance.focus_fire_cost).max(0.0);
+            }
+        }
+
+        self.fire_cooldown.tick(player_time);
+        self.player.invulnerable.tick(player_time);
+
+        let mut hitstop: f32 = 0.0;
+        let mut remove_asteroids: Vec<AsteroidRemoval> = vec![];
+        let ship_center = self.player.center();
+        let ship_images = self.ship_images();
+        for a in self.asteroids.iter_mut() {
+            let mut previous_position = a.position;
+            a.tick(world_time);
+
+            if self.settings.boundary == BoundaryMode::Walls {
+                let restitution = self.balance.wall_restitution;
+                if let Some((contact, normal)) =
+                    a.bounce_off_walls(self.width, self.height, restitution)
+                {
+                    self.wall_ripples.push(WallRipple::new(contact, normal));
+                }
+            } else if self.settings.asteroid_behavior == AsteroidBehavior::Wrap {
+                // Breaking ones too, so the halves don't land off the field
+                if a.is_offscreen(self.width, self.height) {
+                    previous_position += a.wrap(self.width, self.height);
+                }
+            } else if a.is_offscreen(self.width, self.height) && a.breaking.is_none() {
+                remove_asteroids.push(AsteroidRemoval {
+                    id: a.id,
+                    reason: AsteroidRemovalReason::Escaped,
+                });
+            }
+            // Rocks already on their way out can't be hit again
+            let queued = remove_asteroids.iter().any(|r| r.id == a.id);
+            if a.breaking.is_some() || a.health == 0 || queued {
+                continue;
+            }
+
+            // The deflector covers the front, bouncing asteroids back faster with a knock
+            let deflected = self
+                .deflector
+                .is_running()
+                .then(|| {
+                    geom::circle_hits_arc(
+                        a.position,
+                        a.radius,
+                        ship_center,
+                        Self::DEFLECTOR_RADIUS,
+                        self.player.rotation,
+                        Self::DEFLECTOR_HALF_ANGLE,
+                    )
+                })
+                .flatten();
+            if let Some(normal) = deflected {
+                if a.velocity.dot(normal) < 0.0 {
+                    a.velocity = geom::reflect(a.velocity, normal) * self.balance.deflector_boost;
+                    a.position = ship_center + normal * (Self::DEFLECTOR_RADIUS + a.radius);
+                    self.ship_knockback -= normal * self.balance.deflector_recoil;
+                    log_event!(self, "deflect id={} vel={}", a.id, a.velocity);
+                    a.take_hit();
+                    if a.health == 0 {
+                        if a.size.splits() {
+                            a.breaking = Some(Asteroid::BREAK_DURATION);
+                        }
+                        remove_asteroids.push(AsteroidRemoval {
+                            id: a.id,
+                            reason: AsteroidRemovalReason::Destroyed(KilledBy::Deflector),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            // check for collision with player, and its copies across any edge it wraps over
+            let vertices = self.player.vertices();
+            let swept = ship_images.iter().flat_map(|offset| {
+                previous_vertices
+                    .iter()
+                    .zip(vertices)
+                    .map(move |(previous, p)| (*previous + *offset, p + *offset))
+            });
+            for (previous, p) in swept {
+                if collision::swept_point_hits_circle(
+                    previous,
+                    p,
+                    previous_position,
+                    a.position,
+                    a.radius,
+                ) {
+                    remove_asteroids.push(AsteroidRemoval {
+                        id: a.id,
+                        reason: AsteroidRemovalReason::HitPlayer,
+                    });
+                    break;
+                }
+            }
+        }
+
+        self.tick_derelict(world_time);
+
+        // check for lasers hitting asteroids
+        let mut remove_lasers: Vec<LaserRemoval> = vec![];
+        // Contact points and outward directions of hits that didn't break the rock
+        let mut dust: Vec<(Vec2, Vec2)> = vec![];
+        // Laser that shot the derelict down
+        let mut derelict_hit = None;
+        for l in self.lasers.iter_mut() {
+            let previous_position = l.position;
+            l.tick(world_time);
+
+            // check for contact with an asteroid anywhere along this tick's path
+            for a in self.asteroids.iter_mut().filter(|a| a.breaking.is_none()) {
+                // Killed or crumbled by an earlier laser this tick
+                if a.health == 0 || remove_asteroids.iter().any(|r| r.id == a.id) {
+                    continue;
+                }
+                if let Some(contact) = collision::swept_point_circle_contact(
+                    previous_position,
+                    l.position,
+                    a.position - a.velocity * world_time,
+                    a.position,
+                    a.radius,
+                ) {
+                    remove_lasers.push(LaserRemoval {
+                        id: l.id,
+                        reason: LaserRemovalReason::Hit { asteroid: a.id },
+                    });
+
+                    if self.mode == GameMode::Mining {
+                        // Chip ore off the rock instead of splitting it, until it crumbles
+                        a.radius -= Self::MINING_CHIP * self.size_scale;
+                        a.size = SizeClass::of(a.radius / self.size_scale);
+                        let outward = (contact - a.position).normalize_or_zero();
+                        let ore_count = if self.rng.gen_range(0.0, 1.0) < 0.5 {
+                            1
+                        } else {
+                            2
+                        };
+                        for _ in 0..ore_count {
+                            let spread = Vec2::from_angle(self.rng.gen_range(-0.6, 0.6));
+                            self.power_ups.push(PowerUp::new(
+                                PowerUpKind::Ore,
+                                contact,
+                                a.velocity + spread.rotate(outward) * 80.0,
+                            ));
+                        }
+                        if a.size.crumbles() {
+                            remove_asteroids.push(AsteroidRemoval {
+                                id: a.id,
+                                reason: AsteroidRemovalReason::Destroyed(KilledBy::Mining),
+                            });
+                        }
+                        break;
+                    }
+
+                    a.take_hit();
+                    if a.health > 0 {
+                        dust.push((contact, (contact - a.position).normalize_or_zero()));
+                    }
+                    if a.health == 0 {
+                        // Splitting rocks break apart for a moment before the pieces appear
+                        if a.size.splits() {
+                            a.breaking = Some(Asteroid::BREAK_DURATION);
+                        }
+                        remove_asteroids.push(AsteroidRemoval {
+                            id: a.id,
+                            reason: AsteroidRemovalReason::Destroyed(KilledBy::Laser),
+                        });
+                    }
+                    break;
+                }
+            }
+
+            // Derelicts only catch lasers that got past the asteroids
+            let mut hit = remove_lasers.last().is_some_and(|r| r.id == l.id);
+            if let Some(d) = self.derelict.as_ref().filter(|_| !hit) {
+                if collision::swept_point_hits_circle(
+                    previous_position,
+                    l.position,
+                    d.position - d.velocity * world_time,
+                    d.position,
+                    d.radius,
+                ) {
+                    remove_lasers.push(LaserRemoval {
+                        id: l.id,
+                        reason: LaserRemovalReason::Derelict,
+                    });
+                    derelict_hit = Some(l.id);
+                    hit = true;
+                }
+            }
+
+            // check for offscreen lasers
+            if !hit && l.is_offscreen(self.width, self.height) {
+                remove_lasers.push(LaserRemoval {
+                    id: l.id,
+                    reason: LaserRemovalReason::Offscreen,
+                });
+            }
+        }
+
+        hitstop = hitstop.max(self.apply_asteroid_removals(remove_asteroids));
+        self.apply_laser_removals(remove_lasers);
+        self.tick_breaking(world_time);
+        if let Some(laser) = derelict_hit {
+            self.destroy_derelict(laser);
+        }
+
+        if self.uses_director() {
+            self.tick_director(world_time);
+        } else {
+            self.generate_asteroids();
+        }
+
+        for (position, direction) in dust {
+            self.spawn_burst(position, direction, 3, 1.2);
+        }
+        self.tick_waves(world_time);
+
+        // Pick up power-ups touching the ship
+        let distance_from_ship = self.distance_from_ship();
+        for p in self.power_ups.iter_mut() {
+            p.tick(world_time);
+            if distance_from_ship(p.position) < PowerUp::RADIUS + 15.0 {
+                match p.kind {
+                    PowerUpKind::Ore => {
+                        self.ore += 1;
+                        self.score += 1.0;
+                    }
+                    PowerUpKind::Deflector => {
+                        self.deflector.start(self.balance.deflector_duration);
+                    }
+                    PowerUpKind::Salvage(amount) => {
+                        self.ammo = (self.ammo + amount).min(self.balance.ammo_max);
+                        self.ammo_salvaged += amount;
+                    }
+                    PowerUpKind::Repair => {
+                        if self.player.health < self.player.max_health {
+                            self.player.health += 1;
+                            self.feed.push(GameEvent::Repaired);
+                        } else {
+                            self.score += self.balance.repair_full_health_points;
+                        }
+                    }
+                }
+                p.life = Countdown::default();
+            }
+        }
+        self.power_ups.retain(|p| p.life.is_running());
+
+        for m in self.escape_markers.iter_mut() {
+            m.life.tick(world_time);
+        }
+        self.escape_markers.retain(|m| m.life.is_running());
+        for p in self.particles.iter_mut() {
+            p.tick(world_time);
+        }
+        self.particles.retain(|p| p.life.is_running());
+        for d in self.debris.iter_mut() {
+            d.tick(world_time);
+        }
+        self.debris.retain(|d| d.life.is_running());
+        for r in self.wall_ripples.iter_mut() {
+            r.life.tick(world_time);
+        }
+        self.starfield.tick(world_time);
+        self.wall_ripples.retain(|r| r.life.is_running());
+
+        let on_field = self.player.center() / vec2(self.width, self.height);
+        if self.player.health < health_before {
+            self.heatmap.mark_hit(on_field, self.player.health == 0);
+        } else if self.player.health > 0 {
+            self.heatmap.tick(player_time, on_field);
+        }
+
+        // Field repair restores health after a long enough stretch without damage
+        if self.player.health < health_before || self.player.health == self.player.max_health {
+            self.field_repair.reset();
+        } else if self.player.health > 0 {
+            let interval = self.balance.field_repair_interval;
+            if self.field_repair.tick(world_time, interval) > 0 {
+                self.player.health += 1;
+                self.field_repair.reset();
+            }
+        }
+
+        if self.mode == GameMode::Pressure {
+            self.score = (self.score - self.balance.pressure_decay_rate * world_time).max(0.0);
+            if self.score() >= self.balance.pressure_target {
+                self.hold_time += world_time;
+            } else {
+                self.hold_time = 0.0;
+            }
+        }
+
+        // Average the score rate over roughly the last second
+        let score_rate = (self.score - score_before) / player_time;
+        self.score_trend += (score_rate - self.score_trend) * (1.0 - (-player_time).exp());
+
+        if hitstop > 0.0 {
+            self.add_hitstop(hitstop);
+        }
+
+        if self.player.health > 1 {
+            self.player.last_hit_armed = true;
+        } else if self.player.health == 1 && self.player.last_hit_armed {
+            self.player.last_hit_armed = false;
+            if self.settings.last_hit_warning {
+                self.time_scale = 0.5;
+                self.slow_motion.start(1.0);
+                self.toasts
+                    .push(Toast::new("LAST HIT!", 48, 1.5).flashing());
+            }
+        }
+
+        for t in self.toasts.iter_mut() {
+            t.tick(player_time);
+        }
+        self.toasts.retain(|t| !t.is_done());
+        self.feed.tick(player_time);
+    }
+
+    // The field without a player: things drift and fade, but nothing collides,
+    // scores or spawns, so it slowly thins out
+    fn tick_world(&mut self, frame_time: f32) {
+        let (width, height) = (self.width, self.height);
+        for a in self.asteroids.iter_mut() {
+            a.tick(frame_time);
+        }
+        if self.settings.boundary == BoundaryMode::Walls {
+            let restitution = self.balance.wall_restitution;
+            for a in self.asteroids.iter_mut() {
+                if let Some((contact, normal)) = a.bounce_off_walls(width, height, restitution) {
+                    self.wall_ripples.push(WallRipple::new(contact, normal));
+                }
+            }
+        } else if self.settings.asteroid_behavior == AsteroidBehavior::Wrap {
+            for a in self.asteroids.iter_mut() {
+                if a.is_offscreen(width, height) {
+                    a.wrap(width, height);
+                }
+            }
+        } else {
+            self.asteroids.retain(|a| !a.is_offscreen(width, height));
+        }
+        self.tick_breaking(frame_time);
+        for l in self.lasers.iter_mut() {
+            l.tick(frame_time);
+        }
+        self.lasers.retain(|l| !l.is_offscreen(width, height));
+        for p in self.power_ups.iter_mut() {
+            p.tick(frame_time);
+        }
+        self.power_ups.retain(|p| p.life.is_running());
+        if let Some(d) = self.derelict.as_mut() {
+            d.tick(frame_time);
+        }
+        if self
+            .derelict
+            .as_ref()
+            .is_some_and(|d| d.has_left(width, height))
+        {
+            self.derelict = None;
+        }
+        for m in self.escape_markers.iter_mut() {
+            m.life.tick(frame_time);
+        }
+        self.escape_markers.retain(|m| m.life.is_running());
+        for p in self.particles.iter_mut() {
+            p.tick(frame_time);
+        }
+        self.particles.retain(|p| p.life.is_running());
+        for d in self.debris.iter_mut() {
+            d.tick(frame_time);
+        }
+        self.debris.retain(|d| d.life.is_running());
+        for r in self.wall_ripples.iter_mut() {
+            r.life.tick(frame_time);
+        }
+        self.starfield.tick(frame_time);
+        self.wall_ripples.retain(|r| r.life.is_running());
+        for t in self.toasts.iter_mut() {
+            t.tick(frame_time);
+        }
+        self.toasts.retain(|t| !t.is_done());
+        self.feed.tick(frame_time);
+    }
+
+    // Half-angle in radians lasers can stray by at the ship's current speed
+    fn laser_spread_angle(&self) -> f32 {
+        let speed_fraction = (self.ship_speed / self.player_speed).min(1.0);
+        Self::MAX_LASER_SPREAD.to_radians() * speed_fraction
+    }
+
+    // Wrap mode: bring the ship back in on the opposite edge once its center
+    // crosses one, returning how far it moved
+    fn wrap_ship(&mut self) -> Vec2 {
+        let center = self.player.center();
+        let wrapped = vec2(
+            center.x.rem_euclid(self.width),
+            center.y.rem_euclid(self.height),
+        );
+        let shift = wrapped - center;
+        self.player.position += shift;
+        shift
+    }
+
+    // Offsets the ship is drawn and collides at: just where it is, plus its
+    // copies across any edge it overlaps in Wrap mode
+    fn ship_images(&self) -> Vec<Vec2> {
+        if self.settings.boundary == BoundaryMode::Wrap {
+            geom::wrap_offsets(self.player.center(), Ship::REACH, self.width, self.height)
+        } else {
+            vec![Vec2::ZERO]
+        }
+    }
+
+    // Distance from the ship to a point, the short way round across the edges
+    // in Wrap mode, for checks made while other parts of the game are borrowed
+    fn distance_from_ship(&self) -> impl Fn(Vec2) -> f32 {
+        let ship = self.player.center();
+        let (width, height) = (self.width, self.height);
+        let wraps = self.settings.boundary == BoundaryMode::Wrap;
+        move |point| {
+            if wraps {
+                geom::torus_distance(ship, point, width, height)
+            } else {
+                geom::distance(ship, point)
+            }
+        }
+    }
+
+    // Push the whole hull back inside the field, rippling where it first touches a wall
+    fn keep_ship_inside(&mut self) {
+        let vertices = self.player.vertices();
+        let min = vertices.iter().fold(Vec2::MAX, |m, v| m.min(*v));
+        let max = vertices.iter().fold(Vec2::MIN, |m, v| m.max(*v));
+        let push =
+            (-min).max(Vec2::ZERO) + (Vec2::new(self.width, self.height) - max).min(Vec2::ZERO);
+        if push == Vec2::ZERO {
+            self.ship_on_wall = false;
+            return;
+        }
+
+        self.player.position += push;
+        if !self.ship_on_wall {
+            let center = self.player.center();
+            let contact = Vec2::new(
+                if push.x > 0.0 {
+                    0.0
+                } else if push.x < 0.0 {
+                    self.width
+                } else {
+                    center.x
+                },
+                if push.y > 0.0 {
+                    0.0
+                } else if push.y < 0.0 {
+                    self.height
+                } else {
+                    center.y
+                },
+            );
+            self.wall_ripples
+                .push(WallRipple::new(contact, push.normalize()));
+        }
+        self.ship_on_wall = true;
+    }
+
+    // The player's turn rate with everything currently affecting it
+    fn turn_rate(&self) -> f32 {
+        Ship::effective_turn_rate(self.player.turn_rate, &[])
+    }
+
+    // Drain the focus meter while it's held, returning how much time passes for the world
+    fn tick_focus(&mut self, frame_time: f32, held: bool) -> f32 {
+        if !held || self.focus <= 0.0 {
+            self.focus_active = false;
+        } else if !self.focus_active && self.focus >= Self::FOCUS_MIN_TO_START {
+            self.focus_active = true;
+        }
+        if !self.focus_active {
+            return frame_time;
+        }
+        self.focus = (self.focus - self.balance.focus_drain_rate * frame_time).max(0.0);
+        frame_time * self.balance.focus_time_scale
+    }
+
+    fn record_escape(&mut self, position: Vec2, velocity: Vec2) {
+        self.escaped_count += 1;
+        if self.mode == GameMode::Pressure {
+            self.score = (self.score - self.balance.pressure_escape_penalty).max(0.0);
+        }
+
+        // Point an arrow out of the screen at the spot the asteroid left from
+        let margin = 8.0;
+        self.escape_markers.push(EscapeMarker {
+            position: Vec2::new(
+                position.x.clamp(margin, self.width - margin),
+                position.y.clamp(margin, self.height - margin),
+            ),
+            direction: velocity.normalize_or_zero(),
+            life: Countdown::new(EscapeMarker::DURATION),
+        });
+    }
+
+    fn score(&self) -> u32 {
+        self.score as u32
+    }
+
+    fn generate_asteroids(&mut self) {
+        // Wave mode only spawns whole waves and versus asteroids are all thrown by a player
+        if matches!(self.mode, GameMode::Waves | GameMode::Versus) {
+            return;
+        }
+
+        let mut num_asteroids =
+            self.max_asteroids - cmp::min(self.asteroids.len(), self.max_asteroids);
+
+        if num_asteroids > 0 && self.in_endgame() && !self.has_reachable_target() {
+            self.spawn_reachable_target();
+            num_asteroids -= 1;
+        }
+
+        // Split generation evenly across the 4 screen boundaries
+        let plan = self.plan_asteroids(num_asteroids / 4 * 4, self.size_weights());
+        self.materialize(&plan);
+    }
+
+    // The director stands in for topping up the field in the modes that do,
+    // when turned on. The storm sandbox is all about the count, so it keeps it.
+    fn uses_director(&self) -> bool {
+        self.settings.director
+            && !self.settings.storm
+            && matches!(
+                self.mode,
+                GameMode::Classic | GameMode::Pressure | GameMode::Mining
+            )
+    }
+
+    // The fairness caps stay with the game: no more asteroids than the field
+    // holds, no gold rush while another major event is on, and a reachable
+    // target near the end of a run as with the refill
+    fn tick_director(&mut self, frame_time: f32) {
+        self.tick_gold_rush(frame_time);
+        let room = self.asteroids.len() < self.max_asteroids;
+        if room && self.in_endgame() && !self.has_reachable_target() {
+            self.spawn_reachable_target();
+        }
+
+        let allowed = Allowed {
+            asteroid: self.asteroids.len() < self.max_asteroids,
+            gold_rush: !self.major_event_busy(),
+        };
+        let Some(purchase) = self
+            .director
+            .tick(frame_time, self.elapsed, &self.balance, allowed)
+        else {
+            return;
+        };
+        log_event!(
+            self,
+            "director buy={:?} budget={:.1}",
+            purchase,
+            self.director.budget
+        );
+        match purchase {
+            Purchase::Asteroid(size) => {
+                let weights = SizeClass::ALL.map(|s| if s == size { 1.0 } else { 0.0 });
+                let edge = self.rng.next_u32() as usize % 4;
+                let plan = self.plan_asteroids_from(edge, 1, weights);
+                self.materialize(&plan);
+            }
+            Purchase::GoldRush => self.start_gold_rush(),
+        }
+    }
+
+    // How far from a corner an asteroid of `radius` may enter. With walls the
+    // ship gets pinned in corners, so rocks keep well clear of them.
+    fn corner_margin(&self, radius: f32) -> f32 {
+        if self.settings.boundary == BoundaryMode::Walls {
+            radius * Self::CORNER_EXCLUSION
+        } else {
+            radius
+        }
+    }
+
+    // The corner the ship is pinned in, if it's walls mode and it's close enough
+    fn occupied_corner(&self) -> Option<Vec2> {
+        if self.settings.boundary != BoundaryMode::Walls {
+            return None;
+        }
+        let ship = self.player.center();
+        [
+            Vec2::ZERO,
+            Vec2::new(self.width, 0.0),
+            Vec2::new(0.0, self.height),
+            Vec2::new(self.width, self.height),
+        ]
+        .into_iter()
+        .find(|&corner| geom::distance(ship, corner) < Self::CORNER_OCCUPIED_RANGE)
+    }
+
+    // Debug view of where asteroids may not enter and the ship counts as cornered
+    fn render_corner_exclusion(&self) {
+        if self.settings.boundary != BoundaryMode::Walls {
+            return;
+        }
+        let largest = self.corner_margin(100.0 * self.size_scale);
+        let occupied = self.occupied_corner();
+        for corner in [
+            Vec2::ZERO,
+            Vec2::new(self.width, 0.0),
+            Vec2::new(0.0, self.height),
+            Vec2::new(self.width, self.height),
+        ] {
+            draw_circle_lines(corner.x, corner.y, largest, 1.0, ORANGE);
+            let color = if occupied == Some(corner) {
+                RED
+            } else {
+                DARKGRAY
+            };
+            draw_circle_lines(corner.x, corner.y, Self::CORNER_OCCUPIED_RANGE, 1.0, color);
+        }
+    }
+
+    // Size class from `weights`, then a radius within its band, scaled to the screen
+    fn roll_radius(&mut self, weights: &[f32; 4]) -> (f32, SizeClass) {
+        let size = SizeClass::roll(&mut self.rng, weights);
+        let (min, max) = size.band();
+        // Down from the top, as the bottom of a band belongs to the class below
+        (
+            (max - self.rng.gen_range(0.0, max - min)) * self.size_scale,
+            size,
+        )
+    }
+
+    // Size mix for asteroids spawned outside of waves
+    fn size_weights(&self) -> [f32; 4] {
+        if self.settings.storm {
+            Self::STORM_SIZE_WEIGHTS
+        } else {
+            self.balance.size_weights
+        }
+    }
+
+    fn plan_asteroids(&mut self, count: usize, weights: [f32; 4]) -> Vec<AsteroidTemplate> {
+        self.plan_asteroids_from(0, count, weights)
+    }
+
+    // Roll asteroids entering from the screen boundaries in turn, starting
+    // with `first_edge`, moving roughly toward the center
+    fn plan_asteroids_from(
+        &mut self,
+        first_edge: usize,
+        count: usize,
+        weights: [f32; 4],
+    ) -> Vec<AsteroidTemplate> {
+        let angle_variation = 30f32.to_radians();
+
+        (first_edge..first_edge + count)
+            .map(|i| {
+                let (radius, _) = self.roll_radius(&weights);
+                let speed = self.balance.asteroid_speed * self.size_scale;
+                let margin = self.corner_margin(radius);
+                // Keep the ranges the right way round even if a rock is wider than the field
+                let max_x = (self.width - margin).max(margin);
+                let max_y = (self.height - margin).max(margin);
+                let position = match i % 4 {
+                    // Left, top, right and bottom boundaries
+                    0 => Vec2::new(0.0, self.rng.gen_range(margin, max_y)),
+                    1 => Vec2::new(self.rng.gen_range(margin, max_x), 0.0),
+                    2 => Vec2::new(self.width, self.rng.gen_range(margin, max_y)),
+                    _ => Vec2::new(self.rng.gen_range(margin, max_x), self.height),
+                };
+
+                // Somewhere between straight at the center and `angle_variation` off it
+                let toward_center = (self.center - position).to_angle();
+                let angle = geom::angle_lerp_shortest(
+                    toward_center,
+                    toward_center + angle_variation,
+                    self.rng.gen_range(0.0, 1.0),
+                );
+
+                AsteroidTemplate {
+                    position,
+                    velocity: Vec2::from_angle(angle) * speed,
+                    radius,
+                }
+            })
+            .collect()
+    }
+
+    fn materialize(&mut self, plan: &[AsteroidTemplate]) {
+        for t in plan {
+            self.asteroid_counter += 1;
+            log_event!(
+                self,
+                "spawn id={} pos={} vel={} radius={} rng={}",
+                self.asteroid_counter,
+                t.position,
+                t.velocity,
+                t.radius,
+                self.rng.draws()
+            );
+            self.asteroids.push(
+                Asteroid::new(
+                    t.position.x,
+                    t.position.y,
+                    t.velocity.x,
+                    t.velocity.y,
+                    t.radius,
+                    self.asteroid_counter,
+                )
+                .with_detail(&self.balance, self.size_scale, self.seed),
+            );
+        }
+    }
+
+    // How many of each size the next wave brings, as the clear screen previews it
+    fn next_wave_counts(&self) -> [u32; 4] {
+        let mut counts = [0; 4];
+        for t in &self.next_wave {
+            counts[SizeClass::of(t.radius / self.size_scale) as usize] += 1;
+        }
+        counts
+    }
+
+    fn plan_wave(&mut self, wave: u32) -> Vec<AsteroidTemplate> {
+        // Huge asteroids work their way in over the first few waves
+        let mut weights = self.size_weights();
+        weights[SizeClass::Huge as usize] *= ((wave - 1) as f32 / 3.0).min(1.0);
+        self.plan_asteroids(4 + 2 * wave as usize, weights)
+    }
+
+    // Rough score for having played through the waves a practice run skips, so
+    // it starts about where a full run would have been
+    fn practice_start_score(&self) -> f32 {
+        (1..self.practice_wave.unwrap_or(1))
+            .map(|wave| {
+                (4 + 2 * wave) as f32 * self.balance.practice_points_per_asteroid
+                    + self.balance.practice_bonus_per_wave
+            })
+            .sum()
+    }
+
+    fn tick_waves(&mut self, frame_time: f32) {
+        if self.mode != GameMode::Waves {
+            return;
+        }
+
+        for (remaining, _) in self.reentries.iter_mut() {
+            *remaining -= frame_time;
+        }
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.reentries)
+            .into_iter()
+            .partition(|(remaining, _)| *remaining <= 0.0);
+        self.reentries = waiting;
+        for (_, a) in ready {
+            log_event!(self, "reenter id={} pos={}", a.id, a.position);
+            self.asteroids.push(a);
+        }
+
+        self.wave_clear.tick(frame_time);
+        if self.wave_clear.just_expired() {
+            self.wave += 1;
+            let plan = std::mem::take(&mut self.next_wave);
+            self.start_wave(&plan);
+            self.feed.push(GameEvent::WaveStarted(self.wave));
+        } else if !self.wave_clear.is_running()
+            && self.asteroids.is_empty()
+            && self.reentries.is_empty()
+            && self.ring.is_none()
+        {
+            self.award_wave_bonuses();
+            // Roll the next wave now so the clear screen can show exactly what's coming
+            self.wave_clear.start(Self::WAVE_CLEAR_DURATION);
+            self.next_wave = self.plan_wave(self.wave + 1);
+        }
+
+        self.tick_gold_rush(frame_time);
+        self.tick_ring(frame_time);
+    }
+
+    fn start_wave(&mut self, plan: &[AsteroidTemplate]) {
+        self.wave_started_at = self.elapsed;
+        self.wave_asteroid_count = plan.len();
+        self.wave_damage_taken = false;
+        self.materialize(plan);
+    }
+
+    // A hit on the same tick as the last kill voids No Damage: ship collisions
+    // are checked before lasers, and waves are ticked after both
+    fn award_wave_bonuses(&mut self) {
+        let clear_time = self.elapsed - self.wave_started_at;
+        self.wave_clear_times.push(clear_time);
+        self.wave_bonuses.clear();
+
+        if !self.wave_damage_taken {
+            self.perfect_waves += 1;
+            self.wave_bonuses
+                .push(("No Damage", self.balance.no_damage_bonus * self.wave as f32));
+        }
+        let par = self.wave_asteroid_count as f32 * self.balance.wave_par_per_asteroid;
+        if clear_time < par {
+            let points = ((par - clear_time) * self.balance.speed_bonus_per_second).round();
+            self.wave_bonuses.push(("Speed", points));
+        }
+
+        let total: f32 = self.wave_bonuses.iter().map(|(_, points)| points).sum();
+        self.score += total;
+        log_event!(
+            self,
+            "wave_clear wave={} time={:.2} bonus={}",
+            self.wave,
+            clear_time,
+            total
+        );
+    }
+
+    fn schedule_gold_rush(&mut self) {
+        let interval = self.balance.gold_rush_interval;
+        let delay = self.rng.gen_range(interval * 0.8, interval * 1.2);
+        self.next_event_in.start(delay);
+    }
+
+    fn tick_gold_rush(&mut self, frame_time: f32) {
+        if let Some(zone) = self.gold_rush.as_mut() {
+            zone.life.tick(frame_time);
+            if zone.contains(self.player.center()) {
+                self.score += self.balance.gold_rush_ship_points * frame_time;
+            }
+            if !zone.life.is_running() {
+                self.gold_rush = None;
+                self.schedule_gold_rush();
+                self.last_major_event = self.elapsed;
+            }
+            return;
+        }
+
+        // With the director on, gold rushes are its to buy
+        if self.uses_director() {
+            return;
+        }
+        self.next_event_in.tick(frame_time);
+        if !self.next_event_in.ready() || self.wave_clear.is_running() || self.major_event_busy() {
+            return;
+        }
+        self.start_gold_rush();
+    }
+
+    fn start_gold_rush(&mut self) {
+        let radius = self.balance.gold_rush_radius * self.size_scale;
+        let max_x = (self.width - radius).max(radius);
+        let max_y = (self.height - radius).max(radius);
+        let position = Vec2::new(
+            self.rng.gen_range(radius, max_x),
+            self.rng.gen_range(radius, max_y),
+        );
+        self.gold_rush = Some(GoldRush {
+            position,
+            radius,
+            life: Countdown::new(self.balance.gold_rush_duration),
+            duration: self.balance.gold_rush_duration,
+        });
+        log_event!(self, "gold_rush pos={}", position);
+        self.toasts.push(Toast::new(
+            &format!(
+                "GOLD RUSH! {}x points in the zone",
+                self.balance.gold_rush_multiplier
+            ),
+            36,
+            2.5,
+        ));
+
+        // A few extra rocks converge on the zone
+        let mut plan =
+            self.plan_asteroids(self.balance.gold_rush_extra_spawns, self.size_weights());
+        for t in plan.iter_mut() {
+            t.velocity =
+                geom::steer_towards(t.velocity, position - t.position, std::f32::consts::PI);
+        }
+        self.materialize(&plan);
+    }
+
+    fn schedule_derelict(&mut self) {
+        let interval = self.balance.derelict_interval;
+        let delay = self.rng.gen_range(interval * 0.5, interval * 1.5);
+        self.next_derelict_in.start(delay);
+    }
+
+    // Derelicts drift across now and then, one at a time and never in Versus.
+    // Time the ship spends near one adds up to a scan.
+    fn tick_derelict(&mut self, frame_time: f32) {
+        let distance_from_ship = self.distance_from_ship();
+        if let Some(d) = self.derelict.as_mut() {
+            d.tick(frame_time);
+            let range = self.balance.derelict_scan_range * self.size_scale;
+            if !d.scanned && distance_from_ship(d.position) < range {
+                d.scan_time += frame_time;
+                if d.scan_time >= self.balance.derelict_scan_duration {
+                    d.scanned = true;
+                    let points = self.balance.derelict_scan_points;
+                    self.score += points;
+                    self.feed.push(GameEvent::DerelictScanned { points });
+                    log_event!(self, "derelict_scanned pos={}", d.position);
+                }
+            }
+            if d.has_left(self.width, self.height) {
+                self.derelict = None;
+                self.schedule_derelict();
+            }
+            return;
+        }
+
+        self.next_derelict_in.tick(frame_time);
+        if !self.next_derelict_in.ready() || self.mode == GameMode::Versus {
+            return;
+        }
+        // Comes in just past a random point on an edge, heading for somewhere
+        // in the middle half of the field
+        let radius = self.balance.derelict_radius * self.size_scale;
+        let along = self.rng.gen_range(0.0, 1.0);
+        let start = match self.rng.next_u32() % 4 {
+            0 => Vec2::new(along * self.width, -radius),
+            1 => Vec2::new(along * self.width, self.height + radius),
+            2 => Vec2::new(-radius, along * self.height),
+            _ => Vec2::new(self.width + radius, along * self.height),
+        };
+        let target = Vec2::new(
+            self.rng.gen_range(0.25, 0.75) * self.width,
+            self.rng.gen_range(0.25, 0.75) * self.height,
+        );
+        let speed = self.balance.derelict_speed * self.size_scale;
+        let velocity = (target - start).normalize_or_zero() * speed;
+        let spin = self.rng.gen_range(-0.4, 0.4);
+        self.derelict = Some(Derelict::new(start, velocity, radius, spin));
+        log_event!(self, "derelict pos={} vel={}", start, velocity);
+    }
+
+    // Shot down for a few points, coming apart in a shower of sparks
+    fn destroy_derelict(&mut self, laser: u32) {
+        let Some(d) = self.derelict.take() else {
+            return;
+        };
+        let points = self.balance.derelict_destroy_points;
+        self.score += points;
+        self.feed.push(GameEvent::DerelictDestroyed { points });
+        log_event!(self, "derelict_destroyed laser={}", laser);
+        self.spawn_burst(d.position, Vec2::X, 16, std::f32::consts::PI);
+        self.schedule_derelict();
+    }
+
+    // Another major event is running or ended too recently to start one
+    fn major_event_busy(&self) -> bool {
+        self.gold_rush.is_some()
+            || self.ring.is_some()
+            || self.elapsed - self.last_major_event < Self::MAJOR_EVENT_GAP
+    }
+
+    fn schedule_ring(&mut self) {
+        let interval = self.balance.ring_interval;
+        let delay = self.rng.gen_range(interval * 0.8, interval * 1.2);
+        self.next_ring_in.start(delay);
+    }
+
+    // Late waves: warn with chevrons where a ring of asteroids is about to close
+    // in around the ship, then bring them all in at once
+    fn tick_ring(&mut self, frame_time: f32) {
+        if let Some(ring) = self.ring.as_mut() {
+            ring.warning.tick(frame_time);
+            if !ring.warning.is_running() {
+                let Some(ring) = self.ring.take() else {
+                    return;
+                };
+                log_event!(self, "ring_spawn count={}", ring.plan.len());
+                self.materialize(&ring.plan);
+                self.last_major_event = self.elapsed;
+                self.schedule_ring();
+            }
+            return;
+        }
+
+        if self.wave < self.balance.ring_min_wave {
+            return;
+        }
+        self.next_ring_in.tick(frame_time);
+        if !self.next_ring_in.ready() || self.wave_clear.is_running() || self.major_event_busy() {
+            return;
+        }
+
+        let center = self.player.center();
+        let ring_radius = self.balance.ring_radius * self.size_scale;
+        let speed = self.balance.asteroid_speed * self.balance.ring_speed_scale * self.size_scale;
+        let (fewest, most) = self.balance.ring_count;
+        let count = fewest + self.rng.next_u32() as usize % (most - fewest + 1);
+        let (min, max) = SizeClass::Medium.band();
+        let mut plan = Vec::with_capacity(count);
+        for i in 0..count {
+            let radius = self.rng.gen_range(min, max) * self.size_scale;
+            let angle = i as f32 * std::f32::consts::TAU / count as f32;
+            let wanted = center + Vec2::from_angle(angle) * ring_radius;
+            // Pulled in from the edges, but never right on top of the ship
+            let position = Vec2::new(
+                wanted.x.clamp(radius, (self.width - radius).max(radius)),
+                wanted.y.clamp(radius, (self.height - radius).max(radius)),
+            );
+            if geom::distance(position, center) < ring_radius / 2.0 {
+                continue;
+            }
+            plan.push(AsteroidTemplate {
+                position,
+                velocity: (center - position).normalize_or_zero() * speed,
+                radius,
+            });
+        }
+        log_event!(self, "ring_warning pos={} count={}", center, plan.len());
+        self.toasts.push(Toast::new("SURROUNDED!", 36, 2.5));
+        self.ring = Some(RingAmbush {
+            plan,
+            warning: Countdown::new(self.balance.ring_warning),
+        });
+    }
+
+    fn tick_attacker(&mut self, frame_time: f32, launch: Option<Launch>) {
+        self.attacker_budget = (self.attacker_budget
+            + self.balance.versus_budget_regen * frame_time)
+            .min(self.balance.versus_budget_max);
+        self.attacker_cooldown.tick(frame_time);
+
+        let Some(launch) = launch else {
+            return;
+        };
+        let (radius, cost) = Self::LAUNCH_SIZES[launch.size];
+        if !self.attacker_cooldown.ready() || self.attacker_budget < cost {
+            return;
+        }
+        let Some(position) = self.launch_edge_point(launch.from) else {
+            return;
+        };
+
+        // Drag length sets the speed, a click without dragging aims at the center
+        let drag = launch.to - launch.from;
+        let direction = if drag.length() < 5.0 {
+            (self.center - position).normalize_or_zero()
+        } else {
+            drag.normalize()
+        };
+        let speed = (drag.length() * 1.5).clamp(60.0, 300.0) * self.size_scale;
+
+        self.attacker_budget -= cost;
+        self.attacker_cooldown
+            .start(self.balance.versus_launch_cooldown);
+        self.materialize(&[AsteroidTemplate {
+            position,
+            velocity: direction * speed,
+            radius: radius * self.size_scale,
+        }]);
+    }
+
+    // Snap a throw's starting point onto the nearest screen edge, if it's close enough to one
+    fn launch_edge_point(&self, point: Vec2) -> Option<Vec2> {
+        let distances = [
+            (point.x, Vec2::new(0.0, point.y)),
+            (point.y, Vec2::new(point.x, 0.0)),
+            (self.width - point.x, Vec2::new(self.width, point.y)),
+            (self.height - point.y, Vec2::new(point.x, self.height)),
+        ];
+        distances
+            .into_iter()
+            .filter(|(d, _)| *d <= Self::LAUNCH_EDGE_MARGIN)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, edge_point)| edge_point)
+    }
+
+    // Attacker's budget bar along the bottom of the screen, dimmed while throwing is on cooldown
+    fn render_attacker_hud(&self, selected_size: usize) {
+        let width = 240.0;
+        let x = self.center.x - width / 2.0;
+        let y = self.height - ui(30.0);
+        let color = if !self.attacker_cooldown.ready() {
+            GRAY
+        } else {
+            ORANGE
+        };
+        let fill = self.attacker_budget / self.balance.versus_budget_max;
+        draw_rectangle_lines(x, y, width, 10.0, 1.0, GRAY);
+        draw_rectangle(x, y, width * fill, 10.0, color);
+
+        let (radius, cost) = Self::LAUNCH_SIZES[selected_size];
+        draw_poly_lines(x - 24.0, y + 5.0, 8, radius / 5.0, 0.0, 1.0, color);
+        // Mark how much of the bar the selected size costs
+        let cost_x = x + width * cost / self.balance.versus_budget_max;
+        draw_line(cost_x, y - 3.0, cost_x, y + 13.0, 1.0, WHITE);
+        draw_text(
+            &format!(
+                "Player {} - drag from an edge to throw, right click for size",
+                3 - self.versus_defender
+            ),
+            x,
+            y - 8.0,
+            ui(18.0),
+            GRAY,
+        );
+    }
+
+    // Hold an escaped asteroid back for a moment before it comes in from the opposite edge
+    fn queue_reentry(&mut self, mut asteroid: Asteroid) {
+        asteroid.wrap(self.width, self.height);
+        self.reentries.push((Self::REENTRY_DELAY, asteroid));
+    }
+
+    // "Wave N cleared" banner with the next wave's asteroids counted by size
+    fn render_wave_clear(&self, color: Color) {
+        let shown = Self::WAVE_CLEAR_DURATION - self.wave_clear.remaining();
+        Banner {
+            text: &format!("Wave {} cleared", self.wave),
+            color: WHITE,
+            height: 0.06,
+        }
+        .render(
+            self.center.y - ui(40.0),
+            shown,
+            self.settings.reduced_motion,
+        );
+        draw_text_h_centered_color("Next wave", self.center.y, 20, GRAY);
+
+        let counts = self.next_wave_counts();
+        let spacing = ui(90.0);
+        let y = self.center.y + ui(35.0);
+        for (i, (count, radius)) in counts.iter().zip([6.0, 10.0, 14.0, 18.0]).enumerate() {
+            let x = self.center.x + spacing * (i as f32 - 1.5);
+            draw_poly_lines(x - ui(14.0), y, 8, ui(radius), 0.0, 1.0, color);
+            draw_text(
+                &format!("x{}", count),
+                x + ui(4.0),
+                y + ui(8.0),
+                ui(24.0),
+                color,
+            );
+        }
+
+        // Bonuses called out one after another, each counting up to its value
+        for (i, (name, points)) in self.wave_bonuses.iter().enumerate() {
+            let appears_at = 0.3 + i as f32 * 0.8;
+            if shown < appears_at {
+                break;
+            }
+            let progress = ((shown - appears_at) / 0.5).min(1.0);
+            draw_text_h_centered_color(
+                &format!("{} bonus +{}", name, (points * progress) as u32),
+                self.center.y + ui(90.0 + i as f32 * 32.0),
+                28,
+                GOLD,
+            );
+        }
+    }
+
+    // A learning aid, so never in competitive runs
+    fn shows_lanes(&self) -> bool {
+        self.settings.lanes
+            && self.challenge.is_none()
+            && self.mode != GameMode::Versus
+            && self.player.health > 0
+    }
+
+    // Faint dotted paths the nearest asteroids will follow if nothing changes,
+    // fading with distance, each marked where it passes closest to the ship
+    fn render_lanes(&self, color: Color) {
+        let ship = self.player.center();
+        let range = Self::LANE_RANGE * self.size_scale;
+        let mut near: Vec<(f32, &Asteroid)> = self
+            .asteroids
+            .iter()
+            .filter(|a| a.breaking.is_none())
+            .map(|a| (geom::distance(a.position, ship), a))
+            .filter(|(distance, _)| *distance < range)
+            .collect();
+        near.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (distance, a) in near.into_iter().take(Self::LANE_MAX) {
+            let fade = 1.0 - distance / range;
+            let dot = Color {
+                a: 0.3 * fade,
+                ..color
+            };
+            let step = Self::LANE_HORIZON / Self::LANE_DOTS as f32;
+            for i in 1..=Self::LANE_DOTS {
+                let p = a.position + a.velocity * step * i as f32;
+                draw_circle(p.x, p.y, stroke(1.0), dot);
+            }
+            let end = a.position + a.velocity * Self::LANE_HORIZON;
+            let closest = geom::closest_point_on_segment(ship, a.position, end);
+            let marker = Color {
+                a: 0.6 * fade,
+                ..color
+            };
+            draw_circle_lines(closest.x, closest.y, 4.0, stroke(1.0), marker);
+        }
+    }
+
+    // Small flame out of the side opposite the way the ship is strafing
+    fn render_strafe_flame(&self) {
+        let facing = Vec2::from_angle(self.player.rotation);
+        let side = facing.perp() * self.strafe;
+        let base = self.player.center() - side * 12.0;
+        let flicker = 5.0 + 2.0 * (self.elapsed * 40.0).sin();
+        let tip = base - side * flicker;
+        for edge in [base + facing * 4.0, base - facing * 4.0] {
+            draw_line(edge.x, edge.y, tip.x, tip.y, stroke(1.0), ORANGE);
+        }
+    }
+
+    // Shimmering arc in front of the ship, blinking as it runs out
+    fn render_deflector(&self) {
+        let remaining = self.deflector.remaining();
+        if remaining < 1.5 && (remaining * 8.0) as i32 % 2 == 1 {
+            return;
+        }
+        let center = self.player.center();
+        let segments = 12;
+        let start = self.player.rotation - Self::DEFLECTOR_HALF_ANGLE;
+        let step = 2.0 * Self::DEFLECTOR_HALF_ANGLE / segments as f32;
+        for i in 0..segments {
+            let from = center + Vec2::from_angle(start + step * i as f32) * Self::DEFLECTOR_RADIUS;
+            let to =
+                center + Vec2::from_angle(start + step * (i + 1) as f32) * Self::DEFLECTOR_RADIUS;
+            let shimmer = 0.6 + 0.4 * (self.elapsed * 12.0 + i as f32 * 0.8).sin();
+            let color = Color {
+                a: shimmer,
+                ..SKYBLUE
+            };
+            draw_line(from.x, from.y, to.x, to.y, stroke(2.0), color);
+        }
+    }
+
+    // Ammo count under the focus meter, warning when it runs low and flashing
+    // red when fired empty
+    fn render_magazine(&self, x: f32, y: f32) {
+        let low = self.ammo < self.balance.ammo_low;
+        let color = if self.dry_fire_flash.is_running() {
+            RED
+        } else if low && (get_time() * 4.0) as i32 % 2 == 0 {
+            ORANGE
+        } else if low {
+            GRAY
+        } else {
+            WHITE
+        };
+        let text = if self.ammo == 0 {
+            String::from("Ammo EMPTY")
+        } else {
+            format!("Ammo {} / {}", self.ammo, self.balance.ammo_max)
+        };
+        draw_text(&text, x, y, ui(20.0), color);
+    }
+
+    fn in_endgame(&self) -> bool {
+        self.mode == GameMode::Classic && self.score() + self.endgame_margin >= self.win_score
+    }
+
+    // Range within which an asteroid's path must pass the player to count as
+    // reachable, and the sizes that can count
+    const REACHABLE_RANGE: f32 = 300.0;
+    const REACHABLE_SIZE_WEIGHTS: [f32; 4] = [1.0, 1.0, 0.0, 0.0];
+
+    fn has_reachable_target(&self) -> bool {
+        self.asteroids.iter().any(|a| {
+            matches!(a.size, SizeClass::Small | SizeClass::Medium)
+                && a.closest_approach(self.player.position) < Self::REACHABLE_RANGE
+        })
+    }
+
+    // Spawn a small or medium asteroid from a random boundary on a path that passes near the player
+    fn spawn_reachable_target(&mut self) {
+        let (radius, _) = self.roll_radius(&Self::REACHABLE_SIZE_WEIGHTS);
+        let along = self.rng.gen_range(0.0, 1.0);
+        // Only walls mode keeps these away from the corners, so open mode rolls stay the same
+        let margin = if self.settings.boundary == BoundaryMode::Walls {
+            self.corner_margin(radius)
+        } else {
+            0.0
+        };
+        let along_x = margin + along * (self.width - 2.0 * margin).max(0.0);
+        let along_y = margin + along * (self.height - 2.0 * margin).max(0.0);
+        let position = match self.rng.next_u32() % 4 {
+            0 => Vec2::new(0.0, along_y),
+            1 => Vec2::new(along_x, 0.0),
+            2 => Vec2::new(self.width, along_y),
+            _ => Vec2::new(along_x, self.height),
+        };
+
+        let offset_angle = self.rng.gen_range(0.0, std::f32::consts::TAU);
+        let offset_distance = self.rng.gen_range(0.0, Self::REACHABLE_RANGE * 0.8);
+        // A cornered ship can't dodge, so aim for the middle instead of at it
+        let target = match self.occupied_corner() {
+            Some(_) => self.center,
+            None => self.player.position,
+        };
+        let aim = target + Vec2::from_angle(offset_angle) * offset_distance;
+        let velocity = (aim - position).normalize_or_zero() * 100.0 * self.size_scale;
+
+        self.materialize(&[AsteroidTemplate {
+            position,
+            velocity,
+            radius,
+        }]);
+    }
+
+    fn has_won(&self) -> bool {
+        match self.mode {
+            GameMode::Classic => self.score() >= self.win_score,
+            GameMode::Pressure => self.hold_time >= self.balance.pressure_hold_duration,
+            GameMode::Waves => false,
+            GameMode::Versus => self.elapsed >= self.balance.versus_duration,
+            GameMode::Mining => self.ore >= self.balance.mining_ore_target,
+        }
+    }
+
+    // Every mode is casual on its own, ranked rules come from the weekly challenge
+    fn pause_policy(&self) -> PausePolicy {
+        if self.challenge.is_some() {
+            PausePolicy::RANKED
+        } else {
+            PausePolicy::Unlimited
+        }
+    }
+
+    // Count a pause against the budget, returning whether it just went over
+    fn record_pause(&mut self) -> bool {
+        self.pauses_used += 1;
+        self.check_pause_budget()
+    }
+
+    // Count paused time against the budget, returning whether it just went over
+    fn tick_pause(&mut self, frame_time: f32) -> bool {
+        self.pause_time_used += frame_time;
+        self.check_pause_budget()
+    }
+
+    // Using up the budget exactly is still fine, going past it forfeits
+    fn check_pause_budget(&mut self) -> bool {
+        let PausePolicy::Limited { pauses, seconds } = self.pause_policy() else {
+            return false;
+        };
+        if self.pause_forfeited || (self.pauses_used <= pauses && self.pause_time_used <= seconds) {
+            return false;
+        }
+        self.pause_forfeited = true;
+        log_event!(
+            self,
+            "pause_forfeit pauses={} seconds={:.1}",
+            self.pauses_used,
+            self.pause_time_used
+        );
+        true
+    }
+
+    // What's left of a limited pause budget, for the pause menu
+    fn pause_budget_text(&self) -> Option<String> {
+        let PausePolicy::Limited { pauses, seconds } = self.pause_policy() else {
+            return None;
+        };
+        if self.pause_forfeited {
+            return Some(String::from(
+                "Pause budget used up, this run won't count for the weekly best",
+            ));
+        }
+        Some(format!(
+            "Ranked run: {} more pauses, {:.0}s of pause time left",
+            pauses - self.pauses_used,
+            (seconds - self.pause_time_used).max(0.0)
+        ))
+    }
+
+    fn is_over(&self) -> bool {
+        let out_of_time =
+            self.mode == GameMode::Mining && self.elapsed >= self.balance.mining_duration;
+        self.player.health == 0 || self.has_won() || out_of_time
+    }
+
+    // Seconds left in a mode played against the clock
+    fn time_left(&self) -> Option<f32> {
+        let duration = match self.mode {
+            GameMode::Mining => self.balance.mining_duration,
+            GameMode::Versus => self.balance.versus_duration,
+            _ => return None,
+        };
+        Some((duration - self.elapsed).max(0.0))
+    }
+
+    // Last seconds of a timed run: a big countdown at the top middle popping
+    // on each new second, with the screen edges pulsing along. Reduced motion
+    // keeps it still and only turns it red.
+    fn render_final_seconds(&self) {
+        let Some(left) = self
+            .time_left()
+            .filter(|left| *left > 0.0 && *left <= Self::FINAL_SECONDS)
+        else {
+            return;
+        };
+        let urgency = 1.0 - left / Self::FINAL_SECONDS;
+        let color = Color::new(1.0, 1.0 - 0.8 * urgency, 1.0 - 0.8 * urgency, 1.0);
+        // 1 as each second starts, falling off before the next
+        let beat = if self.settings.reduced_motion {
+            0.0
+        } else {
+            left.fract().powi(2)
+        };
+        draw_text_h_centered_color(
+            &(left.ceil() as u32).to_string(),
+            ui(100.0),
+            (64.0 * (1.0 + 0.35 * beat)) as u16,
+            color,
+        );
+        if beat > 0.0 {
+            let edge = Color {
+                a: 0.25 * beat,
+                ..color
+            };
+            draw_rectangle_lines(0.0, 0.0, self.width, self.height, ui(8.0), edge);
+        }
+    }
+
+    // `shown` is seconds since the results came up, they slide up from below.
+    // `standing` is how the run did against its mode's high scores.
+    fn render_game_over(&self, shown: f32, standing: Option<&(String, Color)>) {
+        let t = if self.settings.reduced_motion {
+            1.0
+        } else {
+            (shown / Self::RESULTS_SLIDE_DURATION).min(1.0)
+        };
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let center_y = self.center.y + (1.0 - eased) * self.height;
+        let title = if self.mode == GameMode::Versus {
+            let winner = if self.has_won() {
+                self.versus_defender
+            } else {
+                3 - self.versus_defender
+            };
+            format!("Player {} Wins", winner)
+        } else if self.has_won() {
+            String::from("You Win")
+        } else {
+            String::from("Game Over")
+        };
+        let tint = if self.has_won() || self.mode == GameMode::Versus {
+            Banner::WIN_TINT
+        } else {
+            Banner::LOSS_TINT
+        };
+        Banner {
+            text: &title,
+            color: tint,
+            height: 0.08,
+        }
+        .render(center_y, shown, self.settings.reduced_motion);
+        draw_text_h_centered(&format!("Score: {}", self.score()), center_y + ui(50.0), 28);
+        let detail = if self.mode == GameMode::Waves {
+            format!("Reached wave {}", self.wave)
+        } else if self.mode == GameMode::Mining {
+            format!(
+                "Ore collected: {} / {}",
+                self.ore, self.balance.mining_ore_target
+            )
+        } else {
+            format!("Escaped asteroids: {}", self.escaped_count)
+        };
+        let detail = match self.accuracy() {
+            Some(accuracy) => format!("{}   Accuracy: {:.0}%", detail, accuracy * 100.0),
+            None => detail,
+        };
+        let detail = if self.settings.ammo {
+            format!("{}   Ammo salvaged: {}", detail, self.ammo_salvaged)
+        } else {
+            detail
+        };
+        draw_text_h_centered(&detail, center_y + ui(85.0), 20);
+        let seed = if self.retried_seed {
+            format!("Seed {} (retry, best score not recorded)", self.seed)
+        } else {
+            format!("Seed {}", self.seed)
+        };
+        draw_text_h_centered_color(&seed, center_y + ui(105.0), 16, GRAY);
+        let unrecorded = if self.console_used {
+            Some("Console used, run not recorded")
+        } else if self.settings.storm {
+            Some("Storm sandbox, run not recorded")
+        } else if self.practice_wave.is_some() {
+            Some("Practice run, not recorded")
+        } else if self.pause_forfeited {
+            Some("Pause budget exceeded, not counted for the weekly best")
+        } else {
+            None
+        };
+        if let Some(reason) = unrecorded {
+            draw_text_h_centered_color(reason, center_y - ui(60.0), 20, ORANGE);
+        } else if let Some((text, color)) = standing {
+            draw_text_h_centered_color(text, center_y - ui(60.0), 20, *color);
+        }
+        let prompt = |text: &str, y: f32| {
+            prompt::draw_prompt_centered_at(
+                text,
+                self.center.x,
+                y,
+                28,
+                WHITE,
+                &self.settings.bindings,
+            )
+        };
+        if self.mode == GameMode::Versus {
+            prompt(
+                "Press {Confirm} for a rematch, {S} to swap roles",
+                center_y + ui(130.0),
+            );
+        } else if self.challenge.is_some() || self.practice_wave.is_some() {
+            prompt("Press {Confirm} to play again", center_y + ui(130.0));
+        } else {
+            prompt(
+                "Press {Confirm} to play again, {R} to retry this seed",
+                center_y + ui(130.0),
+            );
+        }
+        prompt("Press {V} to watch replay", center_y + ui(180.0));
+
+        // Heatmap thumbnail in the corner, sliding in with the rest
+        if !self.heatmap.is_empty() {
+            let (w, h) = (ui(192.0), ui(108.0));
+            let x = self.width - w - ui(20.0);
+            let y = center_y - self.center.y + self.height - h - ui(20.0);
+            draw_text("Where you flew", x, y - ui(8.0), ui(18.0), GRAY);
+            self.heatmap.render(Rect::new(x, y, w, h));
+        }
+    }
+}
+
+enum Screen {
+    Title,
+    Playing,
+    // Seconds since the pause menu was opened, used to slide it in
+    Paused(f32),
+    // Seconds left counting down before play continues in a ranked run
+    Resuming(f32),
+    // Watching the field after the ship was destroyed, with seconds since
+    Spectating(f32),
+    // Seconds since the results came up, used to slide them in
+    GameOver(f32),
+    Replay(Box<ReplayPlayer>),
+    // Frozen game with a free camera, entered from the pause menu
+    Photo(PhotoMode),
+    // Past runs, entered from the title screen
+    History(HistoryBrowser),
+    // High score tables, entered from the title screen
+    Records(RecordsBrowser),
+    // Typing in a seed to play Classic on, entered from the title screen
+    SeedEntry(TextEntry),
+}
+
+// Screen changes that go through a fade transition
+#[derive(Debug)]
+enum ScreenChange {
+    StartRun(GameMode),
+    // Waves from a later wave, unrecorded
+    StartPractice(u32),
+    // Classic under this week's challenge rules and seed
+    StartWeekly,
+    // A mode and seed played before, from the run that just ended or the history
+    RetrySeed(GameMode, u64),
+    ShowResults,
+    WatchReplay,
+    CloseReplay,
+    QuitToTitle,
+}
+
+struct App {
+    game: Game,
+    profile: Profile,
+    screen: Screen,
+    // Recording of the current or most recent run
+    replay: Option<Replay>,
+    transition: Option<Transition<ScreenChange>>,
+    // Announcements that outlive a single screen, like unlocks
+    toasts: Vec<Toast>,
+    // Palette highlighted on the title screen, which may still be locked
+    palette_cursor: usize,
+    // Wave a practice run picked on the title screen starts from
+    practice_wave_cursor: u32,
+    // How the last run stood against its mode's high scores, for the results
+    record_standing: Option<(String, Color)>,
+    hints: Hints,
+    music: MusicMix,
+    assets: AssetStore,
+    show_debug_overlay: bool,
+    // Recent frame times in seconds for the debug overlay's graph, oldest first
+    frame_times: VecDeque<f32>,
+    log: EventLog,
+    // Shows the event log, and the developer console when it's enabled
+    show_console: bool,
+    console: Console,
+    // This week's challenge, shown on the title screen
+    weekly: Challenge,
+    // Only created while the CRT filter is on
+    crt: Option<CrtFilter>,
+    // Versus attacker's mouse drag start and selected asteroid size
+    attacker_drag: Option<Vec2>,
+    attacker_size: usize,
+    // Seconds without input on the title screen, driving the panel rotation
+    title_idle: f32,
+    // Paused because the window got too small, so it resumes by itself
+    auto_paused: bool,
+    // Ignore Fire until it's released, in case it shares a key with the menu action that resumed play
+    fire_latched: bool,
+    // Restart held down during play
+    quick_restart: HoldToConfirm,
+    #[cfg(feature = "overlay")]
+    overlay: Option<overlay::OverlayServer>,
+}
+impl App {
+    fn new(mut game: Game, mut profile: Profile, assets: AssetStore) -> App {
+        let palette_cursor = game.settings.palette;
+        // Shown on the title screen, once per startup
+        let mut toasts: Vec<Toast> = assets
+            .problems
+            .iter()
+            .map(|problem| Toast::new(problem, 20, 5.0))
+            .collect();
+        if let Some((path, run)) = crash::take_report() {
+            let text = if let Some(run) = run {
+                profile.record_history(run);
+                profile.save();
+                format!(
+                    "A crash report was saved at {}, last run's score was preserved",
+                    path.display()
+                )
+            } else {
+                format!("A crash report was saved at {}", path.display())
+            };
+            toasts.push(Toast::new(&text, 20, 8.0));
+        }
+        let log = EventLog::new(game.settings.log_file.as_deref());
+        let mut crt = None;
+        if game.settings.crt {
+            match CrtFilter::new(&game.settings, screen_width(), screen_height()) {
+                Ok(filter) => crt = Some(filter),
+                Err(e) => {
+                    eprintln!("CRT filter isn't supported here, turning it off: {}", e);
+                    game.settings.crt = false;
+                }
+            }
+        }
+        #[cfg(feature = "overlay")]
+        let overlay = game.settings.overlay_port.and_then(|port| {
+            overlay::OverlayServer::start(port)
+                .map_err(|e| eprintln!("Failed to start the overlay feed on port {}: {}", port, e))
+                .ok()
+        });
+        #[cfg(not(feature = "overlay"))]
+        if game.settings.overlay_port.is_some() {
+            eprintln!(
+                "This build doesn't include the overlay feed, rebuild with --features overlay"
+            );
+        }
+        App {
+            game,
+            profile,
+            screen: Screen::Title,
+            replay: None,
+            transition: None,
+            toasts,
+            palette_cursor,
+            practice_wave_cursor: 2,
+            record_standing: None,
+            hints: Hints::default(),
+            music: MusicMix::default(),
+            assets,
+            show_debug_overlay: false,
+            frame_times: VecDeque::new(),
+            log,
+            show_console: false,
+            console: Console::new(),
+            weekly: Challenge::current(),
+            crt,
+            attacker_drag: None,
+            attacker_size: 0,
+            title_idle: 0.0,
+            auto_paused: false,
+            fire_latched: false,
+            quick_restart: HoldToConfirm::new(Self::QUICK_RESTART_HOLD),
+            #[cfg(feature = "overlay")]
+            overlay,
+        }
+    }
+
+    fn finish_run(&mut self) {
+        self.game.simulate_player = false;
+        self.record_standing = None;
+        if !self.game.is_recorded() {
+            return;
+        }
+        if self.game.mode == GameMode::Waves {
+            self.profile.record_waves(
+                self.game.wave,
+                self.game.perfect_waves,
+                &self.game.wave_clear_times,
+            );
+        }
+        match &self.game.challenge {
+            Some(challenge) if !self.game.pause_forfeited => {
+                self.profile
+                    .record_weekly(&challenge.tag, self.game.score());
+            }
+            _ => {}
+        }
+        self.profile.record_run(
+            self.game.score(),
+            self.game.has_won(),
+            self.game.asteroids_destroyed,
+            self.game.retried_seed,
+        );
+        // Only runs played to the mode's normal rules, start to finish, go in
+        // its table
+        let ranked = !self.game.retried_seed
+            && self.game.challenge.is_none()
+            && self.game.mode != GameMode::Versus;
+        if ranked {
+            let mode = format!("{:?}", self.game.mode);
+            let place = self.profile.record_score(ScoreRecord {
+                mode: mode.clone(),
+                score: self.game.score(),
+                ended_at: miniquad::date::now(),
+                seed: self.game.seed,
+            });
+            self.record_standing = match place {
+                Some(0) => Some((format!("New {} high score!", mode), GOLD)),
+                Some(place) => Some((format!("#{} on the {} high scores", place + 1, mode), GOLD)),
+                None => self
+                    .profile
+                    .records(&mode)
+                    .next()
+                    .map(|best| (format!("{} high score: {}", mode, best.score), GRAY)),
+            };
+        }
+        self.profile.record_history(self.game.summary());
+        for unlockable in self.profile.grant_unlocks() {
+            self.toasts.push(Toast::new(
+                &format!("Unlocked: {}", unlockable.name),
+                28,
+                3.0,
+            ));
+        }
+        self.profile.save();
+    }
+
+    // Between wave 2 and the record, a practice run from wave 1 being a normal one
+    fn move_practice_wave_cursor(&mut self, step: i32) {
+        let best = self.profile.best_wave.max(2);
+        self.practice_wave_cursor = self
+            .practice_wave_cursor
+            .saturating_add_signed(step)
+            .clamp(2, best);
+    }
+
+    // Off for competitive play, where a tip popping up would be a distraction
+    fn update_hints(&mut self, frame_time: f32, events: &[GameEvent]) {
+        let competitive = self.game.challenge.is_some() || self.game.mode == GameMode::Versus;
+        if !self.game.settings.hints || !self.profile.wants_hints() || competitive {
+            return;
+        }
+        self.hints
+            .check(&self.game, events, &self.profile.shown_hints);
+        if let Some(hint) = self.hints.tick(frame_time) {
+            self.profile.shown_hints.push(String::from(hint.id()));
+            self.profile.save();
+        }
+    }
+
+    // Each derelict scanned turns up the next lore line this profile hasn't seen
+    fn show_lore(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if !matches!(event, GameEvent::DerelictScanned { .. }) {
+                continue;
+            }
+            let Some((id, text)) = derelict::next_lore(&self.profile.seen_lore) else {
+                return;
+            };
+            self.toasts.push(Toast::new(text, 24, 5.0));
+            self.profile.seen_lore.push(String::from(id));
+            self.profile.save();
+        }
+    }
+
+    fn move_palette_cursor(&mut self, step: isize) {
+        let count = PALETTES.len() as isize;
+        self.palette_cursor = (self.palette_cursor as isize + step).rem_euclid(count) as usize;
+        let palette = &PALETTES[self.palette_cursor];
+        if palette.unlock.is_none_or(|id| self.profile.is_unlocked(id)) {
+            self.game.settings.palette = self.palette_cursor;
+        }
+    }
+
+    fn change_screen(&mut self, change: ScreenChange) {
+        if self.game.settings.reduced_motion {
+            self.apply(change);
+        } else {
+            self.transition = Some(Transition::new(change));
+        }
+    }
+
+    // Follow the window size, pausing while it's minimized or too small to play in
+    fn handle_window_size(&mut self, width: f32, height: f32) {
+        if let Some(crt) = self.crt.as_mut() {
+            crt.resize(width, height);
+        }
+        let too_small = width < Game::MIN_WIDTH || height < Game::MIN_HEIGHT;
+        if too_small && matches!(self.screen, Screen::Playing | Screen::Resuming(_)) {
+            self.pause();
+            self.auto_paused = true;
+        } else if !too_small && self.auto_paused {
+            if matches!(self.screen, Screen::Paused(_)) {
+                self.resume();
+            }
+            self.auto_paused = false;
+        }
+
+        let width = width.max(Game::MIN_WIDTH);
+        let height = height.max(Game::MIN_HEIGHT);
+        if width != self.game.width || height != self.game.height {
+            self.game.resize(width, height);
+        }
+    }
+
+    // Mouse controls for the versus attacker, returning a throw when a drag is released
+    fn attacker_launch(&mut self) -> Option<Launch> {
+        if keys::is_mouse_pressed(MouseButton::Right) {
+            self.attacker_size = (self.attacker_size + 1) % Game::LAUNCH_SIZES.len();
+        }
+        let mouse = keys::mouse();
+        if keys::is_mouse_pressed(MouseButton::Left) {
+            self.attacker_drag = Some(mouse);
+        }
+        if keys::is_mouse_released(MouseButton::Left) {
+            return self.attacker_drag.take().map(|from| Launch {
+                from,
+                to: mouse,
+                size: self.attacker_size,
+            });
+        }
+        None
+    }
+
+    fn drain_events(&mut self) {
+        for event in self.game.events.drain(..) {
+            self.log.write(event);
+        }
+        self.log.flush();
+    }
+
+    fn apply(&mut self, change: ScreenChange) {
+        self.drain_events();
+        if self.game.settings.log_events {
+            self.log
+                .write(format!("frame={} screen {:?}", self.game.frame, change));
+        }
+        match change {
+            ScreenChange::StartRun(mode) => {
+                self.game.challenge = None;
+                self.game.practice_wave = None;
+                let seed = self.game.settings.seed.unwrap_or_else(fresh_seed);
+                self.start_run(mode, seed);
+            }
+            ScreenChange::StartPractice(wave) => {
+                self.game.challenge = None;
+                self.game.practice_wave = Some(wave);
+                let seed = self.game.settings.seed.unwrap_or_else(fresh_seed);
+                self.start_run(GameMode::Waves, seed);
+            }
+            ScreenChange::StartWeekly => {
+                // The week may have rolled over since startup
+                self.weekly = Challenge::current();
+                self.game.challenge = Some(self.weekly.clone());
+                self.game.practice_wave = None;
+                self.start_run(GameMode::Classic, self.weekly.seed);
+            }
+            ScreenChange::RetrySeed(mode, seed) => {
+                self.game.challenge = None;
+                self.game.practice_wave = None;
+                self.start_run(mode, seed);
+                self.game.retried_seed = true;
+            }
+            ScreenChange::ShowResults => self.screen = Screen::GameOver(0.0),
+            ScreenChange::WatchReplay => {
+                if let Some(replay) = self.replay.take() {
+                    self.screen = Screen::Replay(Box::new(ReplayPlayer::new(replay)));
+                }
+            }
+            ScreenChange::CloseReplay => {
+                if let Screen::Replay(player) =
+                    // Already slid in, so it doesn't animate again
+                    std::mem::replace(&mut self.screen, Screen::GameOver(1.0))
+                {
+                    self.replay = Some(player.into_replay());
+                }
+            }
+            ScreenChange::QuitToTitle => {
+                self.title_idle = 0.0;
+                self.screen = Screen::Title;
+            }
+        }
+    }
+
+    // Throw the run away and start the same kind over straight away, on the same
+    // seed if it was a fixed one. The history keeps it as abandoned, nothing
+    // else counts it.
+    fn restart_run(&mut self) {
+        if self.game.is_recorded() {
+            self.profile.record_history(RunSummary {
+                abandoned: true,
+                ..self.game.summary()
+            });
+            self.profile.save();
+        }
+        let change = if self.game.challenge.is_some() {
+            ScreenChange::StartWeekly
+        } else if let Some(wave) = self.game.practice_wave {
+            ScreenChange::StartPractice(wave)
+        } else if self.game.retried_seed {
+            ScreenChange::RetrySeed(self.game.mode, self.game.seed)
+        } else {
+            ScreenChange::StartRun(self.game.mode)
+        };
+        self.apply(change);
+    }
+
+    fn start_run(&mut self, mode: GameMode, seed: u64) {
+        self.game.mode = mode;
+        self.game.strafe_thrusters = self.profile.is_unlocked("strafe_thrusters");
+        self.game.reset(seed);
+        self.hints.clear();
+        self.attacker_drag = None;
+        self.fire_latched = true;
+        self.quick_restart = HoldToConfirm::new(Self::QUICK_RESTART_HOLD);
+        self.replay = Some(Replay::new(&self.game));
+        self.screen = Screen::Playing;
+    }
+
+    fn update(&mut self, frame_time: f32) {
+        for t in self.toasts.iter_mut() {
+            t.tick(frame_time);
+        }
+        self.toasts.retain(|t| !t.is_done());
+        for failure in persist::take_failures() {
+            self.toasts.push(Toast::new(&failure, 20, 5.0));
+        }
+
+        if self.frame_times.len() == Self::FRAME_TIME_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+
+        let in_run = matches!(
+            self.screen,
+            Screen::Playing | Screen::Paused(_) | Screen::Resuming(_)
+        );
+        crash::note_run(
+            (in_run && self.game.simulate_player && self.game.is_recorded())
+                .then(|| self.game.run_state()),
+        );
+
+        // State as the last tick left it, including the results once a run is over
+        #[cfg(feature = "overlay")]
+        if let Some(overlay) = &mut self.overlay {
+            overlay.publish(frame_time, || self.game.overlay_snapshot());
+        }
+
+        if keys::is_pressed(KeyCode::F3) {
+            self.show_debug_overlay = !self.show_debug_overlay;
+        }
+        let settings = &self.game.settings;
+        if keys::is_pressed(KeyCode::GraveAccent) && (settings.log_events || settings.dev_console) {
+            self.show_console = !self.show_console;
+            self.console.open();
+        }
+        if self.show_console && self.game.settings.dev_console {
+            // The game holds still while typing, so keys never leak into it
+            if self.console.update(&mut self.game) {
+                self.game.console_used = true;
+                // Console changes aren't in the recorded inputs, so it would replay differently
+                self.replay = None;
+            }
+            self.drain_events();
+            return;
+        }
+        self.drain_events();
+
+        // Input is blocked while fading, except that any key skips the fade
+        if let Some(transition) = self.transition.as_mut() {
+            let change = if keys::any_pressed() {
+                transition.skip()
+            } else {
+                transition.tick(frame_time)
+            };
+            if transition.is_done() {
+                self.transition = None;
+            }
+            if let Some(change) = change {
+                self.apply(change);
+            }
+            return;
+        }
+
+        match &mut self.screen {
+            Screen::Title => {
+                self.title_idle += frame_time;
+                if keys::any_pressed() || keys::is_mouse_pressed(MouseButton::Left) {
+                    self.title_idle = 0.0;
+                }
+
+                if UiAction::Confirm.is_pressed() {
+                    self.change_screen(ScreenChange::StartRun(GameMode::Classic));
+                } else if keys::is_pressed(KeyCode::P) {
+                    self.change_screen(ScreenChange::StartRun(GameMode::Pressure));
+                } else if keys::is_pressed(KeyCode::W) {
+                    self.change_screen(ScreenChange::StartRun(GameMode::Waves));
+                } else if keys::is_pressed(KeyCode::V) {
+                    self.change_screen(ScreenChange::StartRun(GameMode::Versus));
+                } else if keys::is_pressed(KeyCode::M) {
+                    self.change_screen(ScreenChange::StartRun(GameMode::Mining));
+                } else if keys::is_pressed(KeyCode::C) {
+                    self.change_screen(ScreenChange::StartWeekly);
+                } else if keys::is_pressed(KeyCode::H) {
+                    self.screen = Screen::History(HistoryBrowser::new());
+                } else if keys::is_pressed(KeyCode::T) {
+                    self.screen = Screen::Records(RecordsBrowser::new(self.game.mode));
+                } else if keys::is_pressed(KeyCode::E) {
+                    let entry = TextEntry::new("", text_entry::DIGITS, 20);
+                    self.screen = Screen::SeedEntry(entry);
+                } else if keys::is_pressed(KeyCode::K) && self.profile.best_wave > 1 {
+                    let wave = self.practice_wave_cursor.min(self.profile.best_wave);
+                    self.change_screen(ScreenChange::StartPractice(wave));
+                } else if UiAction::NavUp.is_pressed() {
+                    self.move_practice_wave_cursor(1);
+                } else if UiAction::NavDown.is_pressed() {
+                    self.move_practice_wave_cursor(-1);
+                } else if UiAction::NavLeft.is_pressed() {
+                    self.move_palette_cursor(-1);
+                } else if UiAction::NavRight.is_pressed() {
+                    self.move_palette_cursor(1);
+                }
+            }
+            Screen::Playing if !self.game.simulate_player => {
+                // Over, holding the final frame before the results
+                self.game.hitstop.tick(frame_time);
+                if !self.game.hitstop.is_running() {
+                    self.change_screen(ScreenChange::ShowResults);
+                }
+            }
+            Screen::Playing => {
+                if UiAction::Back.is_pressed() {
+                    self.pause();
+                    return;
+                }
+                let restart = self.game.settings.bindings.is_down(GameAction::Restart);
+                if self.quick_restart.update(restart, frame_time) {
+                    self.restart_run();
+                    return;
+                }
+                let mut input = Input::from_keyboard(&self.game.settings.bindings);
+                if self.fire_latched {
+                    self.fire_latched = input.fire;
+                    input.fire = false;
+                }
+                if self.game.mode == GameMode::Versus {
+                    input.launch = self.attacker_launch();
+                }
+                self.game.tick(frame_time, &input);
+                if let Some(r) = self.replay.as_mut() {
+                    r.record(frame_time, input, &self.game);
+                }
+                let events = self.game.feed.take_reported();
+                self.show_lore(&events);
+                self.update_hints(frame_time, &events);
+                self.music.update(frame_time, &self.game);
+                if self.game.is_over() {
+                    self.finish_run();
+                    if self.game.player.health == 0 && self.game.settings.spectate_max > 0.0 {
+                        self.screen = Screen::Spectating(0.0);
+                    } else if !self.game.hitstop.is_running() {
+                        self.change_screen(ScreenChange::ShowResults);
+                    }
+                }
+            }
+            Screen::Spectating(shown) => {
+                // The world keeps going without the ship until the explosion
+                // has mostly faded, the time runs out or the player skips ahead
+                *shown += frame_time;
+                let skipped = *shown >= Self::SPECTATE_HINT_DELAY && keys::any_pressed();
+                let shown = *shown;
+                self.game.tick(frame_time, &Input::default());
+                if self.game.death_effect_faded()
+                    || shown >= self.game.settings.spectate_max
+                    || skipped
+                {
+                    self.screen = Screen::GameOver(0.0);
+                }
+            }
+            Screen::Resuming(remaining) => {
+                *remaining -= frame_time;
+                if UiAction::Back.is_pressed() {
+                    self.pause();
+                } else if *remaining <= 0.0 {
+                    self.screen = Screen::Playing;
+                }
+            }
+            Screen::Paused(shown) => {
+                *shown += frame_time;
+                if self.game.tick_pause(frame_time) {
+                    self.toasts.push(Self::pause_forfeit_toast());
+                }
+                if UiAction::Back.is_pressed() {
+                    self.resume();
+                } else if keys::is_pressed(KeyCode::Q) {
+                    self.change_screen(ScreenChange::QuitToTitle);
+                } else if keys::is_pressed(KeyCode::C) {
+                    self.screen = Screen::Photo(PhotoMode::new(self.game.center));
+                } else if keys::is_pressed(KeyCode::T) {
+                    self.game.settings.cycle_text_scale();
+                    persist::save(&self.game.settings);
+                } else if keys::is_pressed(KeyCode::H) {
+                    self.game.settings.high_visibility_ship ^= true;
+                    persist::save(&self.game.settings);
+                } else if keys::is_pressed(KeyCode::L) {
+                    self.game.settings.heading_line ^= true;
+                    persist::save(&self.game.settings);
+                } else if keys::is_pressed(KeyCode::F) {
+                    self.game.settings.cycle_fps_cap();
+                    persist::save(&self.game.settings);
+                } else if keys::is_pressed(KeyCode::I) {
+                    self.game.settings.hints ^= true;
+                    persist::save(&self.game.settings);
+                } else if keys::is_pressed(KeyCode::P) {
+                    self.game.settings.lanes ^= true;
+                    persist::save(&self.game.settings);
+                }
+            }
+            Screen::GameOver(shown) => {
+                *shown += frame_time;
+                // Any key skips straight to the finished results
+                if keys::any_pressed() {
+                    *shown = shown.max(Banner::REVEAL_DURATION.max(Game::RESULTS_SLIDE_DURATION));
+                }
+                self.game.tick(frame_time, &Input::default());
+                if UiAction::Confirm.is_pressed() {
+                    let change = if self.game.challenge.is_some() {
+                        ScreenChange::StartWeekly
+                    } else if let Some(wave) = self.game.practice_wave {
+                        ScreenChange::StartPractice(wave)
+                    } else {
+                        ScreenChange::StartRun(self.game.mode)
+                    };
+                    self.change_screen(change);
+                } else if keys::is_pressed(KeyCode::R)
+                    && self.game.mode != GameMode::Versus
+                    && self.game.challenge.is_none()
+                    && self.game.practice_wave.is_none()
+                {
+                    self.change_screen(ScreenChange::RetrySeed(self.game.mode, self.game.seed));
+                } else if keys::is_pressed(KeyCode::S) && self.game.mode == GameMode::Versus {
+                    self.game.versus_defender = 3 - self.game.versus_defender;
+                    self.change_screen(ScreenChange::StartRun(GameMode::Versus));
+                } else if keys::is_pressed(KeyCode::V) && self.replay.is_some() {
+                    self.change_screen(ScreenChange::WatchReplay);
+                }
+            }
+            Screen::Replay(player) => {
+                player.update();
+                if UiAction::Back.is_pressed() {
+                    self.change_screen(ScreenChange::CloseReplay);
+                }
+            }
+            Screen::History(browser) => match browser.update(&self.profile.history) {
+                Some(HistoryAction::Close) => {
+                    self.title_idle = 0.0;
+                    self.screen = Screen::Title;
+                }
+                Some(HistoryAction::Retry(run)) => {
+                    if let Some(mode) = GameMode::from_name(&run.mode) {
+                        self.change_screen(ScreenChange::RetrySeed(mode, run.seed));
+                    }
+                }
+                None => {}
+            },
+            Screen::Records(browser) => {
+                if browser.update() {
+                    self.title_idle = 0.0;
+                    self.screen = Screen::Title;
+                }
+            }
+            Screen::SeedEntry(entry) => match entry.update(frame_time) {
+                Some(EntryResult::Done(text)) if !text.is_empty() => match text.parse() {
+                    Ok(seed) => {
+                        self.change_screen(ScreenChange::RetrySeed(GameMode::Classic, seed))
+                    }
+                    Err(_) => self
+                        .toasts
+                        .push(Toast::new("That seed is too big", 24, 2.0)),
+                },
+                Some(_) => {
+                    self.title_idle = 0.0;
+                    self.screen = Screen::Title;
+                }
+                None => {}
+            },
+            Screen::Photo(photo) => {
+                // Studying the frozen field from photo mode costs pause time too
+                if self.game.tick_pause(frame_time) {
+                    self.toasts.push(Self::pause_forfeit_toast());
+                }
+                if let Some(path) = photo.update(frame_time) {
+                    self.toasts
+                        .push(Toast::new(&format!("Saved {}", path.display()), 20, 3.0));
+                }
+                if UiAction::Back.is_pressed() {
+                    // Already slid in, so it doesn't animate again
+                    self.screen = Screen::Paused(1.0);
+                }
+            }
+        }
+    }
+
     fn render(&self) {
-        draw_text(&format!("Score: {}", self.score), 10.0, 28.0, 28.0, WHITE);
-        draw_text(
-            &format!("Health: {}", "<3 ".repeat(self.player.health)),
-            150.0,
-            28.0,
-            28.0,
-            WHITE,
+        TEXT_SCALE.set(self.game.settings.text_scale);
+        STROKE_SCALE.set(self.game.stroke_scale());
+        if let Some(crt) = &self.crt {
+            crt.begin();
+        }
+        match &self.screen {
+            Screen::Title => self.render_title(),
+            Screen::Playing => {
+                self.game.render();
+                if self.game.mode == GameMode::Versus {
+                    self.game.render_attacker_hud(self.attacker_size);
+                    if let Some(from) = self.attacker_drag {
+                        let (x, y) = keys::mouse().into();
+                        draw_line(from.x, from.y, x, y, 1.0, ORANGE);
+                    }
+                }
+                self.hints.render();
+                self.quick_restart
+                    .render("Restarting", screen_width() / 2.0, ui(40.0));
+                if controls::is_held(&self.game.settings.bindings) {
+                    controls::render(&self.game.settings.bindings, self.game.strafe_thrusters);
+                }
+            }
+            Screen::Paused(shown) => {
+                self.game.render();
+                self.render_pause_menu(*shown);
+            }
+            Screen::Resuming(remaining) => {
+                self.game.render();
+                let progress = (remaining / PausePolicy::RESUME_COUNTDOWN).clamp(0.0, 1.0);
+                let (center_x, center_y) = (screen_width() / 2.0, screen_height() / 2.0);
+                draw_text_h_centered("Get ready", center_y, 48);
+                let width = ui(200.0) * progress;
+                draw_rectangle(
+                    center_x - width / 2.0,
+                    center_y + ui(20.0),
+                    width,
+                    4.0,
+                    WHITE,
+                );
+            }
+            Screen::Spectating(shown) => {
+                self.game.render_world(self.game.view_rect(), 1.0, true);
+                if *shown >= Self::SPECTATE_HINT_DELAY {
+                    let alpha = ((*shown - Self::SPECTATE_HINT_DELAY) / 0.3).min(1.0);
+                    draw_text_h_centered_color(
+                        "Press any key for results",
+                        screen_height() - 40.0,
+                        20,
+                        Color::new(0.5, 0.5, 0.5, alpha),
+                    );
+                }
+            }
+            Screen::GameOver(shown) => {
+                self.game.render_world(self.game.view_rect(), 1.0, true);
+                self.game
+                    .render_game_over(*shown, self.record_standing.as_ref());
+            }
+            Screen::Replay(player) => player.render(),
+            Screen::Photo(photo) => photo.render(&self.game, self.crt.as_ref()),
+            Screen::History(browser) => {
+                browser.render(&self.profile.history, &self.game.settings.bindings)
+            }
+            Screen::Records(browser) => browser.render(&self.profile, &self.game.settings.bindings),
+            Screen::SeedEntry(entry) => {
+                entry.render("Seed to play Classic on", screen_height() / 2.0 - ui(100.0));
+                prompt::draw_prompt_centered_at(
+                    "Type or pick with {NavLeft} / {NavRight} and {Confirm}, {Back} to return",
+                    screen_width() / 2.0,
+                    screen_height() - ui(30.0),
+                    20,
+                    GRAY,
+                    &self.game.settings.bindings,
+                );
+            }
+        }
+
+        let mut toast_y = 120.0;
+        for t in &self.toasts {
+            t.render(toast_y);
+            toast_y += t.height();
+        }
+
+        if let Some(transition) = &self.transition {
+            transition.render();
+        }
+
+        // Debug tools are drawn after the filter so they stay readable
+        if let Some(crt) = &self.crt {
+            crt.present();
+        }
+
+        if self.show_debug_overlay {
+            self.render_debug_overlay();
+        }
+        if self.show_console {
+            if self.game.settings.log_events {
+                self.log.render();
+            }
+            if self.game.settings.dev_console {
+                self.console.render();
+            }
+        }
+    }
+
+    fn render_debug_overlay(&self) {
+        self.game.render_corner_exclusion();
+        self.game.render_spawn_candidates();
+        let stats = self.game.render_stats.get();
+        let lines = [
+            format!("FPS: {} ({:.1} ms)", get_fps(), get_frame_time() * 1000.0),
+            format!(
+                "Asteroids: {} / {}",
+                self.game.asteroids.len(),
+                self.game.max_asteroids
+            ),
+            format!("Lasers: {}", self.game.lasers.len()),
+            format!(
+                "Asteroid draw calls: {} ({} simplified, {} culled)",
+                stats.asteroid_draw_calls, stats.asteroids_simplified, stats.asteroids_culled
+            ),
+            format!("Background draw calls: {}", stats.background_draw_calls),
+            format!("Stroke scale: {:.2}", self.game.stroke_scale()),
+            format!("Turn rate: {:.0} deg/s", self.game.turn_rate()),
+            match (self.game.uses_director(), self.game.director.last) {
+                (true, Some(last)) => format!(
+                    "Director budget: {:.1}, last bought {:?}",
+                    self.game.director.budget, last
+                ),
+                (true, None) => format!("Director budget: {:.1}", self.game.director.budget),
+                (false, _) => String::from("Director: off"),
+            },
+            format!("Music intensity: {:.2}", self.music.intensity),
+            format!(
+                "Layers: {}",
+                music::Layer::ALL
+                    .iter()
+                    .zip(self.music.volumes)
+                    .map(|(layer, volume)| {
+                        // Stems that aren't there are marked, their volume is still tracked
+                        let missing = if self.assets.has(&assets::stem_path(*layer)) {
+                            ""
+                        } else {
+                            "?"
+                        };
+                        format!("{}{} {:.1}", layer.name(), missing, volume)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            format!(
+                "Frame cap: {}, vsync {}",
+                fps_cap_text(self.game.settings.fps_cap),
+                if self.game.settings.vsync {
+                    "on"
+                } else {
+                    "off"
+                }
+            ),
+        ];
+        let x = screen_width() - 360.0;
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(line, x, 24.0 + i as f32 * 20.0, 20.0, YELLOW);
+        }
+
+        #[cfg(feature = "alloc-audit")]
+        {
+            let (allocations, bytes) = alloc_audit::last_frame();
+            draw_text(
+                &format!("Allocations last frame: {} ({} bytes)", allocations, bytes),
+                x,
+                24.0 + lines.len() as f32 * 20.0,
+                20.0,
+                YELLOW,
+            );
+        }
+
+        // Frame time graph, with a line at 60 FPS, one at the frame cap and the top at 30
+        let graph_y = 24.0 + (lines.len() as f32 + 1.0) * 20.0;
+        let graph_height = 40.0;
+        let bar_height = |frame_time: f32| (frame_time * 30.0).min(1.0) * graph_height;
+        let target = graph_y + graph_height - bar_height(1.0 / 60.0);
+        draw_rectangle_lines(x, graph_y, 240.0, graph_height, 1.0, GRAY);
+        draw_line(x, target, x + 240.0, target, 1.0, DARKGRAY);
+        if let Some(fps) = self.game.settings.fps_cap {
+            let cap = graph_y + graph_height - bar_height(1.0 / fps as f32);
+            draw_line(x, cap, x + 240.0, cap, 1.0, SKYBLUE);
+        }
+        for (i, frame_time) in self.frame_times.iter().enumerate() {
+            let height = bar_height(*frame_time);
+            // A little slack so frames held to exactly 60 FPS stay green
+            let color = if *frame_time > 1.05 / 60.0 {
+                RED
+            } else {
+                GREEN
+            };
+            let bar_x = x + i as f32 * 2.0;
+            draw_line(
+                bar_x,
+                graph_y + graph_height,
+                bar_x,
+                graph_y + graph_height - height,
+                2.0,
+                color,
+            );
+        }
+    }
+
+    const TITLE_SLIDE_DURATION: f32 = 0.5;
+    const FRAME_TIME_SAMPLES: usize = 120;
+    const SPECTATE_HINT_DELAY: f32 = 1.5;
+    // Seconds Restart has to be held to restart the run
+    const QUICK_RESTART_HOLD: f32 = 0.8;
+    // How far below the title the main panel's last line reaches, at 1x
+    const TITLE_PANEL_EXTENT: f32 = 420.0;
+
+    // Cycle through the title panels while idle, sliding the next one in from the right
+    fn render_title(&self) {
+        let settings = &self.game.settings;
+        let panels: Vec<TitlePanel> = settings
+            .title_panels
+            .iter()
+            .copied()
+            .filter(|&p| p != TitlePanel::Unlocks || !self.profile.unlocked.is_empty())
+            .collect();
+        if panels.is_empty() {
+            self.render_title_panel(TitlePanel::Main, 0.0);
+            return;
+        }
+
+        let cycle = (self.title_idle / settings.title_panel_interval) as usize;
+        let current = panels[cycle % panels.len()];
+        let next = panels[(cycle + 1) % panels.len()];
+        let slide_start = settings.title_panel_interval - Self::TITLE_SLIDE_DURATION;
+        let into_panel = self.title_idle % settings.title_panel_interval;
+        if panels.len() == 1 || into_panel < slide_start || settings.reduced_motion {
+            self.render_title_panel(current, 0.0);
+            return;
+        }
+
+        let t = (into_panel - slide_start) / Self::TITLE_SLIDE_DURATION;
+        let eased = t * t * (3.0 - 2.0 * t);
+        let width = screen_width();
+        self.render_title_panel(current, -width * eased);
+        self.render_title_panel(next, width * (1.0 - eased));
+    }
+
+    fn render_title_panel(&self, panel: TitlePanel, x_offset: f32) {
+        // The window's own center, the field stops shrinking at its minimum size
+        let center = Vec2::new(screen_width() / 2.0 + x_offset, screen_height() / 2.0);
+        let bindings = &self.game.settings.bindings;
+        // Lines are spaced with the text scale, squeezed together if the main
+        // panel would otherwise run off a short window
+        let spacing = ui(1.0).min((screen_height() - ui(60.0)) / Self::TITLE_PANEL_EXTENT);
+        let text = |text: &str, y: f32, font_size: u16, color: Color| {
+            prompt::draw_prompt_centered_at(text, center.x, y, font_size, color, bindings)
+        };
+        match panel {
+            TitlePanel::Main => {
+                // Moved up as far as it needs to be to fit
+                let bottom = screen_height() - 20.0 - Self::TITLE_PANEL_EXTENT * spacing;
+                let center = Vec2::new(center.x, center.y.min(bottom));
+                let bob = if self.game.settings.reduced_motion {
+                    0.0
+                } else {
+                    (get_time() as f32 * 2.0).sin() * ui(6.0)
+                };
+                text("Asteroids", center.y + bob, 50, WHITE);
+                text(
+                    "Press {Confirm} to start the game",
+                    center.y + 50.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                text(
+                    "Press {P} for Pressure mode",
+                    center.y + 90.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                text(
+                    "Press {W} for Wave mode",
+                    center.y + 130.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                let practice = if self.profile.best_wave > 1 {
+                    format!(
+                        "{{K}} to practice from wave {}, {{NavUp}}{{NavDown}} to pick - record wave {}",
+                        self.practice_wave_cursor.min(self.profile.best_wave),
+                        self.profile.best_wave
+                    )
+                } else {
+                    String::from("Reach wave 2 to unlock practice from a later wave")
+                };
+                text(&practice, center.y + 160.0 * spacing, 20, GRAY);
+                text(
+                    "Press {V} for two player Versus",
+                    center.y + 195.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                text(
+                    "Press {M} for Mining mode",
+                    center.y + 235.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                text(
+                    "{H} for history, {T} for high scores, {E} to play a seed",
+                    center.y + 265.0 * spacing,
+                    20,
+                    GRAY,
+                );
+                text(
+                    &format!("Press {{C}} for the {} challenge", self.weekly.tag),
+                    center.y + 300.0 * spacing,
+                    28,
+                    WHITE,
+                );
+                let best = match self.profile.weekly_best(&self.weekly.tag) {
+                    Some(best) => format!(" - best {}", best),
+                    None => String::new(),
+                };
+                text(
+                    &format!("{}{}", self.weekly.rules(), best),
+                    center.y + 330.0 * spacing,
+                    20,
+                    GRAY,
+                );
+                self.render_palette_selector(center.x, center.y + 380.0 * spacing, spacing);
+            }
+            TitlePanel::LifetimeStats => {
+                let profile = &self.profile;
+                text("Lifetime Stats", center.y - 60.0 * spacing, 40, WHITE);
+                let lines = [
+                    format!("Runs played: {}", profile.runs_played),
+                    format!("Runs won: {}", profile.runs_won),
+                    format!("Asteroids destroyed: {}", profile.asteroids_destroyed),
+                    format!("Best score: {}", profile.best_score),
+                    format!("Perfect waves: {}", profile.perfect_waves),
+                ];
+                for (i, line) in lines.iter().enumerate() {
+                    text(line, center.y + i as f32 * 36.0 * spacing, 28, WHITE);
+                }
+            }
+            TitlePanel::Controls => {
+                text("Controls", center.y - 60.0 * spacing, 40, WHITE);
+                let mut lines = vec![
+                    "{forward} / {backward} - Move forward / back",
+                    "{rotate_left} / {rotate_right} - Rotate",
+                ];
+                if self.profile.is_unlocked("strafe_thrusters") {
+                    lines.push("{strafe_left} / {strafe_right} - Strafe");
+                }
+                lines.extend([
+                    "{fire} - Fire",
+                    "{focus} - Focus, slows time once the meter is half full",
+                    "Hold {restart} - Restart the run",
+                    "{Back} - Pause",
+                    "Hold {Tab} - Show controls while playing",
+                ]);
+                for (i, line) in lines.iter().enumerate() {
+                    text(line, center.y + i as f32 * 36.0 * spacing, 28, WHITE);
+                }
+            }
+            TitlePanel::Unlocks => {
+                text("Unlocked", center.y - 60.0 * spacing, 40, WHITE);
+                let unlocked = UNLOCKABLES
+                    .iter()
+                    .filter(|u| self.profile.unlocked.iter().any(|id| id == u.id));
+                for (i, unlockable) in unlocked.enumerate() {
+                    text(
+                        unlockable.name,
+                        center.y + i as f32 * 36.0 * spacing,
+                        28,
+                        WHITE,
+                    );
+                }
+                text(
+                    "Pick palettes with Left and Right on the title screen",
+                    center.y + 160.0 * spacing,
+                    20,
+                    GRAY,
+                );
+            }
+        }
+    }
+
+    fn render_palette_selector(&self, center_x: f32, y: f32, spacing: f32) {
+        let palette = &PALETTES[self.palette_cursor];
+        let locked = palette.unlock.filter(|id| !self.profile.is_unlocked(id));
+        let color = if locked.is_some() {
+            GRAY
+        } else {
+            palette.foreground
+        };
+        draw_text_centered_at(
+            &format!("< Palette: {} >", palette.name),
+            center_x,
+            y,
+            24,
+            color,
         );
+        if let Some(id) = locked {
+            draw_text_centered_at(
+                &format!("Locked: {}", unlocks::requirement_for(id)),
+                center_x,
+                y + 28.0 * spacing,
+                20,
+                GRAY,
+            );
+        }
+    }
 
-        self.player.render();
+    fn render_pause_menu(&self, shown: f32) {
+        let budget = self.game.pause_budget_text();
+        let width = ui(420.0);
+        let height = ui(if budget.is_some() { 280.0 } else { 245.0 });
+        let x = (screen_width() - width) / 2.0;
+        let rest_y = (screen_height() - height) / 2.0;
 
-        for a in &self.asteroids {
-            a.render();
+        // Slide down from above the screen, easing out
+        let t = if self.game.settings.reduced_motion {
+            1.0
+        } else {
+            (shown / 0.2).min(1.0)
+        };
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let y = -height + (rest_y + height) * eased;
+
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.85));
+        draw_rectangle_lines(x, y, width, height, 1.0, WHITE);
+        draw_text_h_centered("Paused", y + ui(50.0), 48);
+        let bindings = &self.game.settings.bindings;
+        let center_x = screen_width() / 2.0;
+        prompt::draw_prompt_centered_at(
+            "{Back} to resume, {Q} to quit to title",
+            center_x,
+            y + ui(110.0),
+            24,
+            WHITE,
+            bindings,
+        );
+        prompt::draw_prompt_centered_at(
+            "{C} for photo mode",
+            center_x,
+            y + ui(145.0),
+            24,
+            WHITE,
+            bindings,
+        );
+        let settings = &self.game.settings;
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        prompt::draw_prompt_centered_at(
+            &format!(
+                "{{T}} text {}x, {{H}} bold ship {}, {{L}} heading {}",
+                settings.text_scale,
+                on_off(settings.high_visibility_ship),
+                on_off(settings.heading_line)
+            ),
+            center_x,
+            y + ui(180.0),
+            20,
+            GRAY,
+            bindings,
+        );
+        prompt::draw_prompt_centered_at(
+            &format!(
+                "{{F}} frame cap {}, {{I}} hints {}, {{P}} paths {}",
+                fps_cap_text(settings.fps_cap),
+                on_off(settings.hints),
+                on_off(settings.lanes)
+            ),
+            center_x,
+            y + ui(210.0),
+            20,
+            GRAY,
+            bindings,
+        );
+        if let Some(budget) = budget {
+            let color = if self.game.pause_forfeited {
+                ORANGE
+            } else {
+                GRAY
+            };
+            draw_text_h_centered_color(&budget, y + ui(250.0), 20, color);
         }
-        for l in &self.lasers {
-            l.render();
+    }
+
+    fn pause(&mut self) {
+        self.screen = Screen::Paused(0.0);
+        if self.game.record_pause() {
+            self.toasts.push(Self::pause_forfeit_toast());
         }
     }
 
-    fn tick(&mut self, frame_time: f32) {
-        let move_distance = self.player_speed * frame_time;
-        let rotation_degrees: f32 = 250.0 * frame_time;
+    // Ranked runs count down before play continues
+    fn resume(&mut self) {
+        self.fire_latched = true;
+        self.screen = match self.game.pause_policy() {
+            PausePolicy::Unlimited => Screen::Playing,
+            PausePolicy::Limited { .. } => Screen::Resuming(PausePolicy::RESUME_COUNTDOWN),
+        };
+    }
 
-        // Check for movement input
-        if is_key_down(KeyCode::W) {
-            // Move forward
-            self.player.position.y += move_distance * self.player.rotation.sin();
-            self.player.position.x += move_distance * self.player.rotation.cos();
-        } else if is_key_down(KeyCode::S) {
-            // Move backward
-            self.player.position.y -= move_distance * self.player.rotation.sin();
-            self.player.position.x -= move_distance * self.player.rotation.cos();
+    fn pause_forfeit_toast() -> Toast {
+        Toast::new(
+            "Pause budget exceeded - this run won't count for the weekly best",
+            24,
+            3.0,
+        )
+    }
+}
+
+fn fps_cap_text(fps_cap: Option<u32>) -> String {
+    match fps_cap {
+        Some(fps) => format!("{} FPS", fps),
+        None => String::from("off"),
+    }
+}
+
+fn window_conf(settings: &Settings) -> Conf {
+    let windowed = settings.display_mode == DisplayMode::Windowed;
+    Conf {
+        window_title: String::from("Asteroids"),
+        window_width: 1280,
+        window_height: 720,
+        window_resizable: windowed,
+        fullscreen: !windowed,
+        // Lay out in logical pixels so text and entities scale up on high-DPI displays
+        high_dpi: true,
+        icon: Some(icon::ship_icon()),
+        // Only a hint to the driver, the frame cap is what actually limits the rate
+        platform: miniquad::conf::Platform {
+            swap_interval: Some(if settings.vsync { 1 } else { 0 }),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+// Longest the game waits on quit for queued saves to be written
+const SHUTDOWN_FLUSH: Duration = Duration::from_secs(3);
+
+// Spelled out instead of `#[macroquad::main]` so the headless random agent can
+// run before a window is opened
+fn main() {
+    crash::install();
+    // Read once, so warnings print once and a corrupt settings file is only set aside once
+    let settings = Settings::from_args();
+    if let Some(steps) = settings.random_agent_steps {
+        env::run_random_agent(steps, fresh_seed());
+        return;
+    }
+    macroquad::Window::from_config(window_conf(&settings), run(settings));
+}
+
+// The window can report its default size for a frame or two before a
+// fullscreen switch lands, so the field is only laid out once the same size
+// has come back a couple of frames running, or after a short wait
+async fn settled_screen_size() -> (f32, f32) {
+    let mut sizes = vec![(screen_width(), screen_height())];
+    loop {
+        if let Some(size) = settled_size(&sizes) {
+            return size;
         }
+        clear_background(BLACK);
+        next_frame().await;
+        sizes.push((screen_width(), screen_height()));
+    }
+}
 
-        if is_key_down(KeyCode::A) {
-            // Rotate left
-            self.player.rotation -= rotation_degrees.to_radians();
-        } else if is_key_down(KeyCode::D) {
-            // Rotate right
-            self.player.rotation += rotation_degrees.to_radians();
+// The size to lay out at given the sizes reported so far, one per frame from
+// startup, or None to wait another frame
+fn settled_size(sizes: &[(f32, f32)]) -> Option<(f32, f32)> {
+    const STABLE_FRAMES: usize = 2;
+    const MAX_FRAMES: usize = 30;
+    let last = *sizes.last()?;
+    let repeats = sizes.iter().rev().take_while(|&&size| size == last).count() - 1;
+    (repeats >= STABLE_FRAMES || sizes.len() > MAX_FRAMES).then_some(last)
+}
+
+async fn run(settings: Settings) {
+    if settings.monitor.is_some() {
+        eprintln!("Monitor selection is not supported by the windowing backend, using the primary display");
+    }
+    let mut profile = Profile::load();
+    profile.unlock_all = settings.unlock_all;
+    let ui_snapshot = settings.ui_snapshot;
+    let (width, height) = settled_screen_size().await;
+    let assets = AssetStore::load().await;
+    let game = Game::with_size(fresh_seed(), settings, width, height);
+    let mut app = App::new(game, profile, assets);
+    #[cfg(debug_assertions)]
+    if ui_snapshot {
+        snapshot::run(app).await;
+        return;
+    }
+    #[cfg(not(debug_assertions))]
+    if ui_snapshot {
+        eprintln!("UI snapshots are only available in debug builds");
+    }
+    // Leave the loop on close instead of exiting in place, so the app is dropped
+    // and anything running in the background shuts down
+    prevent_quit();
+    let mut pacer = FramePacer::new();
+
+    while !is_quit_requested() {
+        #[cfg(feature = "alloc-audit")]
+        alloc_audit::end_frame();
+        let frame_time: f32 = get_frame_time();
+
+        clear_background(BLACK);
+
+        // The fullscreen switch and DPI scale are only reported once the window is up
+        app.handle_window_size(screen_width(), screen_height());
+
+        app.update(frame_time);
+        app.render();
+
+        next_frame().await;
+        pacer.wait(app.game.settings.fps_cap);
+    }
+    // Give saves made just before quitting, like a new high score, a chance to land
+    if !persist::flush(SHUTDOWN_FLUSH) {
+        eprintln!("Quit with saves still being written");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keys::script;
+    use std::f32::consts::PI;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    // An app with no window behind it, saving to its own directory and
+    // reading scripted keys
+    fn headless_app(name: &str, settings: Settings) -> App {
+        persist::use_test_data_dir(name);
+        script::start();
+        let game = Game::with_size(1, settings, 1280.0, 720.0);
+        App::new(game, Profile::load(), AssetStore::default())
+    }
+
+    fn frame(app: &mut App) {
+        app.update(FRAME);
+        script::end_frame();
+    }
+
+    // Turns toward the nearest whole asteroid and fires once it's lined up
+    fn aim_and_fire(game: &Game) {
+        let ship = game.player.center();
+        let target = game
+            .asteroids
+            .iter()
+            .filter(|a| a.breaking.is_none())
+            .min_by(|a, b| {
+                geom::distance_sq(a.position, ship).total_cmp(&geom::distance_sq(b.position, ship))
+            });
+        let bindings = &game.settings.bindings;
+        for action in [
+            GameAction::RotateLeft,
+            GameAction::RotateRight,
+            GameAction::Fire,
+        ] {
+            script::release(bindings.key(action));
         }
+        let Some(target) = target else {
+            return;
+        };
+        let off = geom::wrap_angle((target.position - ship).to_angle() - game.player.rotation);
+        if off < -0.05 {
+            script::hold(bindings.key(GameAction::RotateLeft));
+        } else if off > 0.05 {
+            script::hold(bindings.key(GameAction::RotateRight));
+        }
+        if off.abs() < 0.15 {
+            script::hold(bindings.key(GameAction::Fire));
+        }
+    }
 
-        // Check for firing
-        if self.laser_cooldown_remaining <= 0.0 && is_key_down(KeyCode::Space) {
-            self.laser_counter += 1;
-            let front = self.player.vertices()[1];
-            let fired_laser = Laser::new(
-                front.x,
-                front.y,
-                400.0 * self.player.rotation.cos(),
-                400.0 * self.player.rotation.sin(),
-                self.laser_counter,
-            );
-            self.lasers.push(fired_laser);
-            self.laser_cooldown_remaining = self.laser_cooldown;
+    fn quiet_settings() -> Settings {
+        Settings {
+            log_events: false,
+            ..Settings::default()
         }
+    }
+
+    fn particle_bits(game: &Game) -> Vec<[u32; 6]> {
+        game.particles
+            .iter()
+            .map(|p| {
+                [
+                    p.position.x.to_bits(),
+                    p.position.y.to_bits(),
+                    p.velocity.x.to_bits(),
+                    p.velocity.y.to_bits(),
+                    p.life.remaining().to_bits(),
+                    p.size.to_bits(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_effects_repeat_exactly() {
+        let explode = || {
+            let settings = Settings {
+                deterministic_effects: true,
+                ..quiet_settings()
+            };
+            let mut game = Game::with_size(99, settings, 1280.0, 720.0);
+            game.spawn_death_effect();
+            let big = game
+                .asteroids
+                .iter()
+                .position(|a| a.size.splits())
+                .expect("no asteroid to shatter");
+            let rock = game.asteroids.remove(big);
+            game.shatter(rock);
+            for _ in 0..10 {
+                game.tick(FRAME, &Input::default());
+            }
+            game
+        };
+        let (first, second) = (explode(), explode());
+        assert!(!first.particles.is_empty());
+        assert_eq!(particle_bits(&first), particle_bits(&second));
+        assert_eq!(first.debris.len(), second.debris.len());
+    }
+
+    #[test]
+    fn casual_effects_are_not_tied_to_the_seed() {
+        let deterministic = Settings {
+            deterministic_effects: true,
+            ..quiet_settings()
+        };
+        assert_eq!(
+            Game::effects_seed(&deterministic, 5),
+            Game::effects_seed(&deterministic, 5)
+        );
+        assert_ne!(
+            Game::effects_seed(&quiet_settings(), 5),
+            Game::effects_seed(&deterministic, 5)
+        );
+    }
 
-        if self.laser_cooldown_remaining > 0.0 {
-            self.laser_cooldown_remaining -= frame_time;
+    // Sends every asteroid at the nearest edge and runs the world on past the
+    // end of the run for a few seconds
+    fn drift_out_after_the_run(settings: Settings) -> Game {
+        let mut game = Game::with_size(3, settings, 1280.0, 720.0);
+        let center = game.center;
+        for a in game.asteroids.iter_mut() {
+            let out = (a.position - center).normalize_or_zero();
+            a.velocity = out * 200.0;
+        }
+        game.simulate_player = false;
+        for _ in 0..600 {
+            game.tick(FRAME, &Input::default());
         }
+        game
+    }
+
+    #[test]
+    fn walls_hold_the_field_in_after_the_run() {
+        let settings = Settings {
+            boundary: BoundaryMode::Walls,
+            ..quiet_settings()
+        };
+        let before = Game::with_size(3, settings.clone(), 1280.0, 720.0)
+            .asteroids
+            .len();
+        let game = drift_out_after_the_run(settings);
+        assert_eq!(game.asteroids.len(), before);
+        assert!(game
+            .asteroids
+            .iter()
+            .all(|a| !a.is_offscreen(game.width, game.height)));
+    }
 
-        if self.player.iframes > 0 {
-            self.player.iframes -= 1;
+    #[test]
+    fn rocks_breaking_past_the_edge_split_back_inside() {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        game.asteroids.truncate(1);
+        let a = &mut game.asteroids[0];
+        a.position = vec2(game.width - 2.0, game.height / 2.0);
+        a.velocity = vec2(600.0, 0.0);
+        a.radius = 40.0;
+        a.breaking = Some(Asteroid::BREAK_DURATION);
+        for _ in 0..12 {
+            game.tick(FRAME, &Input::default());
         }
+        assert_eq!(game.escaped_count, 0);
+        assert!(game
+            .asteroids
+            .iter()
+            .all(|a| !a.is_offscreen(game.width, game.height)));
+    }
 
-        let mut remove_asteroid_ids: HashSet<u32> = HashSet::new();
-        for a in self.asteroids.iter_mut() {
-            a.tick(frame_time);
+    fn class_index(size: SizeClass) -> usize {
+        SizeClass::ALL.iter().position(|&c| c == size).unwrap()
+    }
 
-            // destroy offscreen asteroids
-            if a.position.x > self.width + a.radius
-                || a.position.y > self.height + a.radius
-                || a.position.x < -a.radius
-                || a.position.y < -a.radius
-            {
-                remove_asteroid_ids.insert(a.id);
+    #[test]
+    fn size_bands_follow_on_from_each_other() {
+        for pair in SizeClass::ALL.windows(2) {
+            assert_eq!(pair[0].band().1, pair[1].band().0);
+        }
+        for class in SizeClass::ALL {
+            let (min, max) = class.band();
+            assert_eq!(SizeClass::of(min + 0.01), class);
+            assert_eq!(SizeClass::of((min + max) / 2.0), class);
+            assert_eq!(SizeClass::of(max), class);
+        }
+        assert_eq!(SizeClass::of(1.0), SizeClass::Small);
+        assert_eq!(SizeClass::of(500.0), SizeClass::Huge);
+    }
+
+    #[test]
+    fn rolled_asteroids_land_in_the_rolled_class() {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        for (i, class) in SizeClass::ALL.into_iter().enumerate() {
+            let mut weights = [0.0; 4];
+            weights[i] = 1.0;
+            for _ in 0..1000 {
+                assert_eq!(SizeClass::roll(&mut game.rng, &weights), class);
+                let (radius, size) = game.roll_radius(&weights);
+                assert_eq!(size, class);
+                assert_eq!(SizeClass::of(radius / game.size_scale), class);
             }
+        }
+    }
 
-            // check for collision with player
-            for p in self.player.vertices() {
-                if distance(&p, &a.position) < a.radius {
-                    self.player.take_hit();
-                    remove_asteroid_ids.insert(a.id);
+    #[test]
+    fn split_pieces_come_out_a_class_smaller() {
+        for class in SizeClass::ALL {
+            let (min, max) = class.band();
+            for radius in [min + 0.01, max] {
+                let mut game = lone_asteroid(radius);
+                assert_eq!(game.asteroids[0].size, class);
+                assert_eq!(class.splits(), class != SizeClass::Small);
+                if !class.splits() {
+                    continue;
+                }
+                remove_lone(&mut game, AsteroidRemovalReason::Shattered);
+                assert_eq!(game.asteroids.len(), 2);
+                for piece in &game.asteroids {
+                    assert!(class_index(piece.size) < class_index(class));
                 }
             }
         }
+    }
 
-        // check for lasers hitting asteroids
-        let mut remove_laser_ids: HashSet<u32> = HashSet::new();
-        let mut split_asteroids: Vec<Asteroid> = vec![];
-        for l in self.lasers.iter_mut() {
-            l.tick(frame_time);
+    #[test]
+    fn slow_saves_stay_off_the_frame_and_land_on_quit() {
+        persist::use_test_data_dir("slow-save");
+        persist::use_test_writer(|path, version, document| {
+            std::thread::sleep(Duration::from_millis(300));
+            persist::write_versioned(path, version, document);
+        });
+        let profile = Profile {
+            runs_played: 7,
+            ..Profile::default()
+        };
+        let start = std::time::Instant::now();
+        profile.save();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert!(!<Profile as persist::Persisted>::path().exists());
 
-            // check for contact with an asteroid
-            for a in self.asteroids.iter_mut() {
-                if distance(&l.position, &a.position) < a.radius {
-                    a.take_hit();
-                    remove_laser_ids.insert(l.id);
-                    if a.health == 0 {
-                        remove_asteroid_ids.insert(a.id);
-
-                        // Split asteroid
-                        if a.radius > 20.0 {
-                            let new_radius = a.radius / 2.0;
-                            split_asteroids.push(Asteroid::new(
-                                a.position.x,
-                                a.position.y,
-                                -(a.velocity.y / 2.0),
-                                a.velocity.y,
-                                new_radius,
-                                self.asteroid_counter + 1,
-                            ));
-                            split_asteroids.push(Asteroid::new(
-                                a.position.x,
-                                a.position.y,
-                                a.velocity.y / 2.0,
-                                a.velocity.y,
-                                new_radius,
-                                self.asteroid_counter + 2,
-                            ));
-                            self.asteroid_counter += 2;
-                        }
+        assert!(persist::flush(SHUTDOWN_FLUSH));
+        assert_eq!(Profile::load().runs_played, 7);
+    }
 
-                        self.score += 1;
-                    }
-                    break;
-                }
-            }
+    #[test]
+    fn startup_waits_for_the_window_size_to_settle() {
+        let (small, full) = ((1280.0, 720.0), (1920.0, 1080.0));
+        assert_eq!(settled_size(&[]), None);
+        assert_eq!(settled_size(&[small, small]), None);
+        assert_eq!(settled_size(&[small, small, small]), Some(small));
 
-            // check for offscreen lasers
-            if l.position.x > self.width || l.position.y > self.height {
-                remove_laser_ids.insert(l.id);
-            }
+        // The fullscreen switch lands late, after the default size came back once
+        let switch = [small, small, full, full, full];
+        for seen in 1..switch.len() {
+            assert_eq!(settled_size(&switch[..seen]), None);
         }
+        assert_eq!(settled_size(&switch), Some(full));
 
-        self.asteroids = self
-            .asteroids
-            .iter()
-            .cloned()
-            .filter(|a| !remove_asteroid_ids.contains(&a.id))
+        // A size that keeps changing is taken as it stands after the wait
+        let flicker: Vec<_> = (0..31)
+            .map(|i| if i % 2 == 0 { small } else { full })
             .collect();
+        assert_eq!(settled_size(&flicker[..30]), None);
+        assert_eq!(settled_size(&flicker), Some(small));
+    }
 
-        self.lasers = self
-            .lasers
-            .iter()
-            .cloned()
-            .filter(|l| !remove_laser_ids.contains(&l.id))
-            .collect();
+    #[test]
+    fn turn_rate_multiplies_modifiers_up_to_the_cap() {
+        let max = Ship::MAX_TURN_RATE;
+        let cases: [(f32, &[f32], f32); 9] = [
+            (250.0, &[], 250.0),
+            (250.0, &[1.0], 250.0),
+            (250.0, &[0.6], 150.0),
+            (250.0, &[1.5], 375.0),
+            (250.0, &[1.5, 0.6], 225.0),
+            (250.0, &[2.0, 2.0], max),
+            (max, &[1.1], max),
+            (250.0, &[0.0], 0.0),
+            (250.0, &[-1.0], 0.0),
+        ];
+        for (base, modifiers, expected) in cases {
+            let rate = Ship::effective_turn_rate(base, modifiers);
+            assert!((rate - expected).abs() < 1e-3, "{base} {modifiers:?}");
+        }
+    }
 
-        self.generate_asteroids();
+    #[test]
+    fn using_the_whole_pause_budget_still_counts() {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        game.challenge = Some(Challenge::current());
+        let PausePolicy::Limited { pauses, seconds } = game.pause_policy() else {
+            panic!("weekly runs are ranked");
+        };
+        assert_eq!((pauses, seconds), (3, 30.0));
+        for _ in 0..pauses {
+            assert!(!game.record_pause());
+        }
+        game.pause_time_used = seconds;
+        assert!(!game.check_pause_budget());
+        assert!(!game.pause_forfeited);
 
-        self.asteroids.extend(split_asteroids);
+        assert!(game.tick_pause(FRAME));
+        assert!(game.pause_forfeited);
+        // Only reported the once
+        assert!(!game.record_pause());
     }
 
-    fn generate_asteroids(&mut self) {
-        // Split generation evenly across the 4 screen boundaries
-        // Generate asteroids moving roughly toward the center of the screen
+    #[test]
+    fn casual_runs_pause_freely() {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        assert_eq!(game.pause_policy(), PausePolicy::Unlimited);
+        for _ in 0..10 {
+            assert!(!game.record_pause());
+        }
+        assert!(!game.tick_pause(600.0));
+        assert!(!game.pause_forfeited);
+    }
 
-        let num_asteroids = self.max_asteroids - cmp::min(self.asteroids.len(), self.max_asteroids);
-        let asteroids_per_boundary = num_asteroids / 4;
+    #[test]
+    fn timed_runs_stop_scoring_exactly_at_zero() {
+        // The director keeps the gold rush ticking, scoring every moment the ship is in it
+        let settings = Settings {
+            director: true,
+            ..quiet_settings()
+        };
+        let mut game = Game::with_size(3, settings, 1280.0, 720.0);
+        game.mode = GameMode::Mining;
+        game.asteroids.clear();
+        game.player.invulnerable.start(f32::MAX);
+        game.balance.gold_rush_ship_points = 100.0;
+        game.gold_rush = Some(GoldRush {
+            position: game.player.center(),
+            radius: 200.0,
+            life: Countdown::new(60.0),
+            duration: 60.0,
+        });
+        // Half a second left, then a long frame that runs well past the end
+        game.elapsed = game.balance.mining_duration - 0.5;
+        let score_before = game.score;
+        for _ in 0..29 {
+            game.tick(FRAME, &Input::default());
+        }
+        assert!(game.time_left().unwrap() > 0.0);
+        game.tick(0.5, &Input::default());
+        assert_eq!(game.time_left(), Some(0.0));
+        let score_at_zero = game.score;
+        assert!((score_at_zero - score_before - 50.0).abs() < 0.1);
 
-        let min_radius = 10.0;
-        let max_radius = 100.0;
-        let speed = 100.0;
-        let angle_variation_degrees = 30.0;
+        // The timeout freeze and anything after it changes nothing
+        for _ in 0..60 {
+            game.tick(FRAME, &Input::default());
+            assert_eq!(game.time_left(), Some(0.0));
+            assert_eq!(game.score, score_at_zero);
+        }
+        assert!(game.is_over());
+    }
 
-        // Left boundary
-        for _ in 0..asteroids_per_boundary {
-            let radius: f32 = gen_range(min_radius, max_radius);
-            let y: f32 = gen_range(radius, self.height - radius);
+    #[test]
+    fn a_hit_on_the_last_kill_tick_voids_no_damage() {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        game.mode = GameMode::Waves;
+        game.player.invulnerable = Countdown::default();
+        let scale = game.size_scale;
+        // One on a hull corner, since the ship collides by its vertices
+        let corner = game.player.vertices()[0];
+        game.asteroids = [(corner, 1000), (Vec2::new(400.0, 200.0), 1001)]
+            .into_iter()
+            .map(|(at, id)| {
+                Asteroid::new(at.x, at.y, 0.0, 0.0, 15.0 * scale, id).with_detail(
+                    &game.balance,
+                    scale,
+                    game.seed,
+                )
+            })
+            .collect();
+        game.lasers = vec![Laser::new(400.0, 200.0, 0.0, 0.0, 2000)];
+        game.tick(FRAME, &Input::default());
 
-            let delta_x = self.center.x;
-            let delta_y = self.center.y - y;
+        assert!(game.asteroids.is_empty());
+        assert!(game.wave_clear.is_running());
+        assert!(game.wave_damage_taken);
+        assert!(game
+            .wave_bonuses
+            .iter()
+            .all(|(name, _)| *name != "No Damage"));
+        assert_eq!(game.perfect_waves, 0);
+    }
 
-            let angle_toward_center = delta_y.atan2(delta_x).to_degrees();
+    #[test]
+    fn a_reachable_target_spawns_near_the_win() {
+        const CYCLES: usize = 3;
+        for seed in 0..20 {
+            let mut game = Game::with_size(seed, quiet_settings(), 1280.0, 720.0);
+            game.score = (game.win_score - game.endgame_margin) as f32;
+            assert!(game.in_endgame());
+            // The stalled field: giants along the top edge, all drifting away
+            let scale = game.size_scale;
+            game.asteroids =
+                (0..game.max_asteroids - 1)
+                    .map(|i| {
+                        let x = 100.0 + i as f32 * 40.0;
+                        Asteroid::new(x, 0.0, 0.0, -50.0, 80.0 * scale, 1000 + i as u32)
+                            .with_detail(&game.balance, scale, game.seed)
+                    })
+                    .collect();
+            assert!(!game.has_reachable_target());
 
-            // add random variation to the angle
-            let angle =
-                (angle_toward_center + gen_range(0.0, angle_variation_degrees)).to_radians();
-            let x_vel = speed * angle.cos();
-            let y_vel = speed * angle.sin();
+            let reached = (0..CYCLES).any(|_| {
+                game.generate_asteroids();
+                let reached = game.has_reachable_target();
+                game.asteroids.remove(0);
+                reached
+            });
+            assert!(
+                reached,
+                "no reachable target within {} cycles for seed {}",
+                CYCLES, seed
+            );
+        }
+    }
 
-            self.asteroid_counter += 1;
-            self.asteroids.push(Asteroid::new(
-                0.0,
-                y,
-                x_vel,
-                y_vel,
-                radius,
-                self.asteroid_counter,
-            ))
+    #[test]
+    fn asteroid_counts_follow_the_screen_area() {
+        let density = Balance::default().asteroid_density;
+        for (width, height, count, scale) in [
+            (1280.0, 720.0, 9, 0.8165),
+            (1920.0, 1080.0, 20, 1.0),
+            (3840.0, 2160.0, 80, std::f32::consts::SQRT_2),
+        ] {
+            let game = Game::with_size(3, quiet_settings(), width, height);
+            assert_eq!(game.max_asteroids, count, "at {}x{}", width, height);
+            let megapixels = width * height / 1_000_000.0;
+            assert!((game.max_asteroids as f32 - density * megapixels).abs() <= 0.5);
+            assert!(
+                (game.size_scale - scale).abs() < 1e-4,
+                "at {}x{}",
+                width,
+                height
+            );
         }
 
-        // Top boundary
-        for _ in 0..asteroids_per_boundary {
-            let radius: f32 = gen_range(min_radius, max_radius);
-            let x: f32 = gen_range(radius, self.width - radius);
-            let delta_x = self.center.x - x;
-            let delta_y = self.center.y;
+        // Resizing the window recounts
+        let mut game = Game::with_size(3, quiet_settings(), 1920.0, 1080.0);
+        game.resize(3840.0, 2160.0);
+        assert_eq!(game.max_asteroids, 80);
+        game.resize(1280.0, 720.0);
+        assert_eq!(game.max_asteroids, 9);
+    }
 
-            let angle_toward_center = delta_y.atan2(delta_x).to_degrees();
+    #[test]
+    fn the_next_wave_arrives_as_previewed() {
+        let mut game = Game::with_size(5, quiet_settings(), 1280.0, 720.0);
+        game.mode = GameMode::Waves;
+        game.player.invulnerable.start(f32::MAX);
+        game.asteroids.clear();
+        game.tick(FRAME, &Input::default());
+        assert!(game.wave_clear.is_running());
+        let preview = game.next_wave.clone();
+        let counts = game.next_wave_counts();
+        assert_eq!(counts.iter().sum::<u32>() as usize, preview.len());
 
-            // add random variation to the angle
-            let angle =
-                (angle_toward_center + gen_range(0.0, angle_variation_degrees)).to_radians();
-            let x_vel = speed * angle.cos();
-            let y_vel = speed * angle.sin();
+        while game.wave == 1 {
+            game.tick(FRAME, &Input::default());
+        }
+        assert_eq!(game.asteroids.len(), preview.len());
+        let mut arrived = [0; 4];
+        for (a, t) in game.asteroids.iter().zip(&preview) {
+            assert_eq!(a.position, t.position);
+            assert_eq!(a.velocity, t.velocity);
+            assert_eq!(a.radius, t.radius);
+            arrived[a.size as usize] += 1;
+        }
+        assert_eq!(arrived, counts);
+    }
 
-            self.asteroid_counter += 1;
-            self.asteroids.push(Asteroid::new(
-                x,
-                0.0,
-                x_vel,
-                y_vel,
-                radius,
-                self.asteroid_counter,
-            ))
+    #[test]
+    fn walls_spawns_keep_out_of_the_corners() {
+        let settings = Settings {
+            boundary: BoundaryMode::Walls,
+            ..quiet_settings()
+        };
+        let mut game = Game::with_size(9, settings, 1280.0, 720.0);
+        let mut spawned = 0;
+        for cycle in 0..500 {
+            game.asteroids.clear();
+            // Every other cycle near the win, to cover the reachable target too
+            game.score = if cycle % 2 == 0 { 0.0 } else { 99.0 };
+            game.generate_asteroids();
+            for a in &game.asteroids {
+                let margin = a.radius * Game::CORNER_EXCLUSION;
+                let (along, length) = if a.position.x == 0.0 || a.position.x == game.width {
+                    (a.position.y, game.height)
+                } else {
+                    (a.position.x, game.width)
+                };
+                assert!(
+                    along >= margin && along <= length - margin,
+                    "spawned at {} with radius {} in cycle {}",
+                    a.position,
+                    a.radius,
+                    cycle
+                );
+            }
+            spawned += game.asteroids.len();
         }
+        assert!(spawned >= 500 * 4);
+    }
 
-        // Right boundary
-        for _ in 0..asteroids_per_boundary {
-            let radius: f32 = gen_range(min_radius, max_radius);
-            let y: f32 = gen_range(radius, self.height - radius);
-            let delta_x = self.center.x - self.width;
-            let delta_y = self.center.y - y;
+    #[test]
+    fn focus_slows_the_world_but_not_the_ship() {
+        let mut game = lone_asteroid(30.0);
+        game.max_asteroids = 0;
+        game.asteroids[0].velocity = Vec2::new(60.0, 0.0);
+        game.focus = 1.0;
+        game.player.invulnerable.start(5.0);
+        // Ready again after 9.5 frames of ship time, but 23.75 of world time
+        game.fire_cooldown.start(9.5 * FRAME);
+        let start = game.asteroids[0].position;
+        let elapsed_before = game.elapsed;
+        let focus = Input {
+            focus: true,
+            ..Input::default()
+        };
+        for _ in 0..10 {
+            game.tick(FRAME, &focus);
+        }
 
-            let angle_toward_center = delta_y.atan2(delta_x).to_degrees();
+        let real = 10.0 * FRAME;
+        let world = real * game.balance.focus_time_scale;
+        assert!(game.focus_active);
+        assert!((game.elapsed - elapsed_before - world).abs() < 1e-5);
+        let travelled = game.asteroids[0].position - start;
+        assert!((travelled.x - 60.0 * world).abs() < 1e-3);
+        assert!((game.player.invulnerable.remaining() - (5.0 - real)).abs() < 1e-5);
+        assert!(game.fire_cooldown.ready());
+        let drained = game.balance.focus_drain_rate * real;
+        assert!((game.focus - (1.0 - drained)).abs() < 1e-5);
+    }
 
-            // add random variation to the angle
-            let angle =
-                (angle_toward_center + gen_range(0.0, angle_variation_degrees)).to_radians();
-            let x_vel = speed * angle.cos();
-            let y_vel = speed * angle.sin();
+    // A game whose only asteroid sits still with `radius` at 1080p
+    fn lone_asteroid(radius: f32) -> Game {
+        let mut game = Game::with_size(3, quiet_settings(), 1280.0, 720.0);
+        let scale = game.size_scale;
+        game.asteroids = vec![Asteroid::new(400.0, 200.0, 0.0, 0.0, radius * scale, 1000)
+            .with_detail(&game.balance, scale, game.seed)];
+        game
+    }
 
-            self.asteroid_counter += 1;
-            self.asteroids.push(Asteroid::new(
-                self.width,
-                y,
-                x_vel,
-                y_vel,
-                radius,
-                self.asteroid_counter,
-            ))
+    fn remove_lone(game: &mut Game, reason: AsteroidRemovalReason) -> f32 {
+        let id = game.asteroids[0].id;
+        game.apply_asteroid_removals(vec![AsteroidRemoval { id, reason }])
+    }
+
+    #[test]
+    fn hitstop_takes_its_length_out_of_simulated_time() {
+        // A hit on the ship stops for a length that ends partway through a frame
+        let mut game = lone_asteroid(15.0);
+        game.player.invulnerable = Countdown::default();
+        let stop = remove_lone(&mut game, AsteroidRemovalReason::HitPlayer);
+        assert!(stop > 0.0 && (stop / FRAME).fract() > 0.1);
+        game.add_hitstop(stop);
+        game.player.invulnerable.start(f32::MAX);
+
+        let elapsed_before = game.elapsed;
+        for _ in 0..60 {
+            game.tick(FRAME, &Input::default());
         }
+        let simulated = game.elapsed - elapsed_before;
+        assert!(
+            (simulated - (60.0 * FRAME - stop)).abs() < 1e-4,
+            "simulated {} with a {} stop",
+            simulated,
+            stop
+        );
+    }
 
-        // Bottom boundary
-        for _ in 0..asteroids_per_boundary {
-            let radius: f32 = gen_range(min_radius, max_radius);
-            let x: f32 = gen_range(radius, self.width - radius);
-            let delta_x = self.center.x - x;
-            let delta_y = self.center.y - self.height;
+    #[test]
+    fn escapes_only_count_as_escapes() {
+        let mut game = lone_asteroid(15.0);
+        remove_lone(&mut game, AsteroidRemovalReason::Escaped);
+        assert!(game.asteroids.is_empty());
+        assert_eq!(game.escaped_count, 1);
+        assert_eq!(game.escape_markers.len(), 1);
+        assert_eq!(game.score, 0.0);
+        assert_eq!(game.asteroids_destroyed, 0);
 
-            let angle_toward_center = delta_y.atan2(delta_x).to_degrees();
+        let mut game = lone_asteroid(15.0);
+        game.mode = GameMode::Waves;
+        remove_lone(&mut game, AsteroidRemovalReason::Escaped);
+        assert_eq!(game.escaped_count, 0);
+        assert_eq!(game.reentries.len(), 1);
+    }
 
-            // add random variation to the angle
-            let angle =
-                (angle_toward_center + gen_range(0.0, angle_variation_degrees)).to_radians();
-            let x_vel = speed * angle.cos();
-            let y_vel = speed * angle.sin();
+    #[test]
+    fn asteroids_that_hit_the_player_only_do_damage() {
+        let mut game = lone_asteroid(15.0);
+        let health = game.player.health;
+        game.player.invulnerable = Countdown::default();
+        let hitstop = remove_lone(&mut game, AsteroidRemovalReason::HitPlayer);
+        assert!(game.asteroids.is_empty());
+        assert_eq!(game.player.health, health - 1);
+        assert!(hitstop > 0.0);
+        assert_eq!(game.score, 0.0);
+        assert_eq!(game.asteroids_destroyed, 0);
+        assert_eq!(game.escaped_count, 0);
+    }
 
-            self.asteroid_counter += 1;
-            self.asteroids.push(Asteroid::new(
-                x,
-                self.height,
-                x_vel,
-                y_vel,
-                radius,
-                self.asteroid_counter,
-            ))
+    #[test]
+    fn each_kill_pays_out_for_what_made_it() {
+        let mut game = lone_asteroid(15.0);
+        remove_lone(&mut game, AsteroidRemovalReason::Destroyed(KilledBy::Laser));
+        assert!(game.asteroids.is_empty());
+        assert_eq!(game.score, 1.0);
+        assert_eq!(game.asteroids_destroyed, 1);
+        assert!(game.focus > 0.0);
+
+        let mut game = lone_asteroid(15.0);
+        remove_lone(
+            &mut game,
+            AsteroidRemovalReason::Destroyed(KilledBy::Deflector),
+        );
+        assert!(game.asteroids.is_empty());
+        assert_eq!(game.score, 1.0);
+        assert_eq!(game.asteroids_destroyed, 1);
+        assert_eq!(game.focus, 0.0);
+        assert!(game.power_ups.is_empty());
+
+        let mut game = lone_asteroid(15.0);
+        remove_lone(
+            &mut game,
+            AsteroidRemovalReason::Destroyed(KilledBy::Mining),
+        );
+        assert!(game.asteroids.is_empty());
+        assert_eq!(game.score, 0.0);
+        assert_eq!(game.asteroids_destroyed, 1);
+        assert_eq!(game.focus, 0.0);
+    }
+
+    #[test]
+    fn destroyed_rocks_break_before_they_shatter() {
+        let mut game = lone_asteroid(50.0);
+        game.asteroids[0].health = 0;
+        game.asteroids[0].breaking = Some(Asteroid::BREAK_DURATION);
+        remove_lone(
+            &mut game,
+            AsteroidRemovalReason::Destroyed(KilledBy::Deflector),
+        );
+        assert_eq!(game.asteroids.len(), 1);
+        assert_eq!(game.score, 1.0);
+        assert_eq!(game.asteroids_destroyed, 1);
+
+        remove_lone(&mut game, AsteroidRemovalReason::Shattered);
+        assert_eq!(game.asteroids.len(), 2);
+        assert!(game.asteroids.iter().all(|a| a.id != 1000));
+        assert_eq!(game.score, 1.0);
+        assert_eq!(game.asteroids_destroyed, 1);
+    }
+
+    #[test]
+    fn a_rock_shot_twice_in_one_tick_scores_once() {
+        let mut game = lone_asteroid(15.0);
+        game.lasers = vec![
+            Laser::new(380.0, 200.0, 600.0, 0.0, 900),
+            Laser::new(382.0, 200.0, 600.0, 0.0, 901),
+        ];
+        game.tick(FRAME, &Input::default());
+        assert_eq!(game.asteroids_destroyed, 1);
+        assert_eq!(game.score, 1.0);
+        assert_eq!(game.shots_hit, 1);
+    }
+
+    #[test]
+    fn wrapping_asteroids_stay_on_the_field_after_the_run() {
+        let settings = Settings {
+            asteroid_behavior: AsteroidBehavior::Wrap,
+            ..quiet_settings()
+        };
+        let before = Game::with_size(3, settings.clone(), 1280.0, 720.0)
+            .asteroids
+            .len();
+        let game = drift_out_after_the_run(settings);
+        assert_eq!(game.asteroids.len(), before);
+        assert!(game
+            .asteroids
+            .iter()
+            .all(|a| !a.is_offscreen(game.width, game.height)));
+    }
+
+    #[test]
+    fn lasers_leave_along_the_facing_ray() {
+        for rotation in [0.0, 0.7, PI / 2.0, 2.5, PI - 0.01, -PI, -2.0, -0.3] {
+            let mut game = Game::with_size(7, quiet_settings(), 1280.0, 720.0);
+            game.asteroids.clear();
+            game.player.rotation = rotation;
+            let fire = Input {
+                fire: true,
+                ..Input::default()
+            };
+            game.tick(FRAME, &fire);
+
+            let laser = game.lasers.last().expect("no laser fired");
+            let facing = Vec2::from_angle(rotation);
+            let offset = laser.position - game.player.center();
+            assert!(offset.dot(facing) > 0.0, "behind the ship at {}", rotation);
+            assert!(
+                offset.perp_dot(facing).abs() < 1e-3,
+                "off the ray by {} at {}",
+                offset.perp_dot(facing),
+                rotation
+            );
+            assert!(laser.velocity.normalize().abs_diff_eq(facing, 1e-5));
         }
     }
 
-    fn check_game_over(&self) -> bool {
-        if self.player.health == 0 {
-            draw_text_h_centered("Game Over", self.center.y, 48);
-            draw_text_h_centered(&format!("Score: {}", self.score), self.center.y + 50.0, 28);
-            draw_text_h_centered("Press enter to play again", self.center.y + 100.0, 28);
-            return true;
-        } else if self.score == 100 {
-            draw_text_h_centered("You Win", self.center.y, 48);
-            draw_text_h_centered(&format!("Score: {}", self.score), self.center.y + 50.0, 28);
-            draw_text_h_centered("Press enter to play again", self.center.y + 100.0, 28);
-            return true;
+    #[test]
+    fn spread_shots_stay_inside_the_speed_cone() {
+        let settings = Settings {
+            laser_spread: true,
+            ..quiet_settings()
+        };
+        for forward in [false, true] {
+            let mut game = Game::with_size(11, settings.clone(), 1280.0, 720.0);
+            game.player.invulnerable.start(f32::MAX);
+            let input = Input {
+                forward,
+                fire: true,
+                ..Input::default()
+            };
+            let start = game.player.position;
+            let mut widest: f32 = 0.0;
+            for _ in 0..500 {
+                // Held in the middle so no shot leaves the field on the tick it's fired
+                game.player.position = start;
+                game.asteroids.clear();
+                game.fire_cooldown = Cooldown::default();
+                let fired = game.laser_counter;
+                game.tick(FRAME, &input);
+                assert_eq!(game.laser_counter, fired + 1);
+
+                let laser = game.lasers.last().unwrap();
+                let facing = Vec2::from_angle(game.player.rotation);
+                let angle = facing.angle_between(laser.velocity).abs();
+                let bound = game.laser_spread_angle();
+                assert!(angle <= bound + 1e-5, "{} past the {} bound", angle, bound);
+                widest = widest.max(angle);
+            }
+            if forward {
+                // Flat out the cone is at its widest and shots use most of it
+                let max = Game::MAX_LASER_SPREAD.to_radians();
+                assert!((game.laser_spread_angle() - max).abs() < 1e-4);
+                assert!(widest > max * 0.9);
+            } else {
+                assert!(widest < 1e-5);
+            }
         }
-        false
     }
-}
 
-fn window_conf() -> Conf {
-    Conf {
-        window_title: String::from("Asteroids"),
-        window_resizable: false,
-        fullscreen: true,
-        ..Default::default()
+    #[test]
+    fn side_thrust_is_square_to_the_facing() {
+        for rotation in [0.0, 0.7, PI / 2.0, 2.5, PI - 0.01, -PI, -2.0, -0.3] {
+            let facing = Vec2::from_angle(rotation);
+            for (left, side) in [(true, -facing.perp()), (false, facing.perp())] {
+                let mut game = Game::with_size(7, quiet_settings(), 1280.0, 720.0);
+                game.asteroids.clear();
+                game.strafe_thrusters = true;
+                game.player.rotation = rotation;
+                let start = game.player.position;
+                let strafe = Input {
+                    strafe_left: left,
+                    strafe_right: !left,
+                    ..Input::default()
+                };
+                game.tick(FRAME, &strafe);
+
+                let moved = game.player.position - start;
+                assert!(moved.length() > 0.0, "no side thrust at {}", rotation);
+                assert!(
+                    moved.normalize().abs_diff_eq(side, 1e-4),
+                    "moved along {} instead of {} at {}",
+                    moved.normalize(),
+                    side,
+                    rotation
+                );
+            }
+        }
     }
-}
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    let mut game = Game::new();
-    let mut game_started = false;
-    let mut game_over = false;
+    #[test]
+    fn minimizing_pauses_and_restoring_resumes() {
+        let settings = Settings {
+            reduced_motion: true,
+            log_events: false,
+            ..Settings::default()
+        };
+        let mut app = headless_app("minimize", settings);
+        script::tap(KeyCode::Enter);
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Playing));
 
-    loop {
-        let frame_time: f32 = get_frame_time();
+        app.handle_window_size(1920.0, 1080.0);
+        assert!(matches!(app.screen, Screen::Playing));
+        assert_eq!((app.game.width, app.game.height), (1920.0, 1080.0));
+        let count = app.game.max_asteroids;
 
-        clear_background(BLACK);
-        if !game_started {
-            draw_text_h_centered("Asteroids", game.center.y, 50);
-            draw_text_h_centered("Press enter to start the game", game.center.y + 50.0, 28);
+        // Minimized windows report no size at all
+        app.handle_window_size(0.0, 0.0);
+        assert!(matches!(app.screen, Screen::Paused(_)));
+        assert!(app.auto_paused);
+        assert_eq!(
+            (app.game.width, app.game.height),
+            (Game::MIN_WIDTH, Game::MIN_HEIGHT)
+        );
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Paused(_)));
+
+        app.handle_window_size(1920.0, 1080.0);
+        assert!(matches!(app.screen, Screen::Playing));
+        assert!(!app.auto_paused);
+        assert_eq!((app.game.width, app.game.height), (1920.0, 1080.0));
+        assert_eq!(app.game.max_asteroids, count);
+
+        // A pause the player chose outlasts the window coming back
+        app.pause();
+        app.handle_window_size(0.0, 0.0);
+        app.handle_window_size(1920.0, 1080.0);
+        assert!(matches!(app.screen, Screen::Paused(_)));
+    }
+
+    #[test]
+    fn fire_on_enter_does_not_fire_when_leaving_the_pause_menu() {
+        let mut settings = Settings {
+            reduced_motion: true,
+            log_events: false,
+            ..Settings::default()
+        };
+        let ui_action = settings.bindings.rebind(GameAction::Fire, KeyCode::Enter);
+        assert!(matches!(ui_action, Ok(Some(UiAction::Confirm))));
+        let mut app = headless_app("fire-on-enter", settings);
+        script::tap(KeyCode::Enter);
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Playing));
+        // Play a moment with Enter up, so starting the run from it is forgotten
+        for _ in 0..5 {
+            frame(&mut app);
         }
+        assert_eq!(app.game.shots_fired, 0);
+
+        script::tap(UiAction::Back.key());
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Paused(_)));
+        // Enter does nothing in the pause menu, and is still down when Escape resumes
+        script::hold(KeyCode::Enter);
+        frame(&mut app);
+        script::tap(UiAction::NavDown.key());
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Paused(_)));
+        script::tap(UiAction::Back.key());
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Playing));
+        for _ in 0..30 {
+            frame(&mut app);
+        }
+        assert_eq!(app.game.shots_fired, 0);
+        assert!(app.game.lasers.is_empty());
+
+        // Pressed again after letting go, it fires
+        script::release(KeyCode::Enter);
+        frame(&mut app);
+        script::hold(KeyCode::Enter);
+        frame(&mut app);
+        assert_eq!(app.game.shots_fired, 1);
+    }
 
-        if !game_over && game_started {
-            game.tick(frame_time);
-            game.render();
-        } else if is_key_down(KeyCode::Enter) {
-            game.reset();
-            game_over = false;
-            game_started = true;
-            continue;
+    #[test]
+    fn seeded_classic_run_from_the_title_to_a_win() {
+        const SEED: u64 = 42;
+        let settings = Settings {
+            seed: Some(SEED),
+            // No fades to wait through
+            reduced_motion: true,
+            log_events: false,
+            ..Settings::default()
+        };
+        let mut app = headless_app("end-to-end", settings);
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Title));
+
+        script::tap(UiAction::Confirm.key());
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Playing));
+        assert_eq!(app.game.mode, GameMode::Classic);
+        assert_eq!(app.game.seed, SEED);
+
+        let mut frames = 0;
+        while app.game.simulate_player {
+            aim_and_fire(&app.game);
+            frame(&mut app);
+            frames += 1;
+            assert!(frames < 60 * 600, "no win after ten minutes of play");
+        }
+        assert!(app.game.has_won(), "lost with {} points", app.game.score());
+        for action in GameAction::ALL {
+            script::release(app.game.settings.bindings.key(action));
         }
-        game_over = game.check_game_over();
+        while !matches!(app.screen, Screen::GameOver(_)) {
+            frame(&mut app);
+        }
+
+        let run = app.profile.history.last().expect("no run summary");
+        assert!(run.won);
+        assert_eq!(run.mode, "Classic");
+        assert_eq!(run.seed, SEED);
+        assert_eq!(run.score, app.game.score());
+        let best = app
+            .profile
+            .records("Classic")
+            .next()
+            .expect("no high score");
+        assert_eq!(best.score, app.game.score());
+        assert_eq!(best.seed, SEED);
+        assert_eq!(app.profile.runs_played, 1);
+        assert_eq!(app.profile.runs_won, 1);
+        assert_eq!(
+            app.profile.asteroids_destroyed,
+            app.game.asteroids_destroyed
+        );
+
+        // The same totals made it to disk
+        assert!(persist::flush(SHUTDOWN_FLUSH));
+        let saved = Profile::load();
+        assert_eq!(saved.runs_won, 1);
+        assert_eq!(saved.history.len(), 1);
+        assert_eq!(saved.records("Classic").count(), 1);
 
-        next_frame().await
+        // Playing again starts clean
+        script::tap(UiAction::Confirm.key());
+        frame(&mut app);
+        assert!(matches!(app.screen, Screen::Playing));
+        assert!(app.game.simulate_player);
+        assert_eq!(app.game.score(), 0);
+        assert_eq!(app.game.asteroids_destroyed, 0);
+        assert_eq!(app.game.player.health, app.game.player.max_health);
+        assert!(!app.game.has_won());
+        assert!(app.game.lasers.is_empty());
     }
 }
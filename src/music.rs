@@ -0,0 +1,109 @@
+// Intensity tracking for layered music: how hectic play is, sampled once a
+// second, and the volume each stem should be at for it. There's no audio
+// playback yet, so for now the mix is only shown on the debug overlay for
+// tuning.
+use crate::{geom, Game};
+
+#[derive(Clone, Copy)]
+pub enum Layer {
+    Bass,
+    Rhythm,
+    Lead,
+    Danger,
+}
+impl Layer {
+    pub const ALL: [Layer; 4] = [Layer::Bass, Layer::Rhythm, Layer::Lead, Layer::Danger];
+
+    // Stem file under assets/music
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Bass => "bass",
+            Layer::Rhythm => "rhythm",
+            Layer::Lead => "lead",
+            Layer::Danger => "danger",
+        }
+    }
+
+    // Intensity a layer comes in above and drops out below, apart so a layer
+    // doesn't flutter when intensity sits near one value
+    fn thresholds(self) -> (f32, f32) {
+        match self {
+            Layer::Bass => (0.0, 0.0),
+            Layer::Rhythm => (0.3, 0.2),
+            Layer::Lead => (0.6, 0.5),
+            // Brought in by low health instead
+            Layer::Danger => (f32::INFINITY, f32::INFINITY),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MusicMix {
+    // Smoothed from 0 for calm to 1 for frantic
+    pub intensity: f32,
+    // Seconds until the next sample
+    sample_in: f32,
+    playing: [bool; 4],
+    // Current volume of each layer, eased toward its target every frame
+    pub volumes: [f32; 4],
+}
+impl MusicMix {
+    const SAMPLE_INTERVAL: f32 = 1.0;
+    // Share of the way to a new sample intensity moves each second
+    const SMOOTHING: f32 = 0.3;
+    // Volume change per second when a layer comes in or out
+    const FADE_RATE: f32 = 0.5;
+    // Everything but danger plays this quietly at 1 health
+    const DUCKED: f32 = 0.2;
+    // Asteroids within this distance of the ship at 1080p count as close, and
+    // this many of them is as hectic as it gets
+    const NEAR_DISTANCE: f32 = 300.0;
+    const NEAR_MAX: f32 = 8.0;
+
+    pub fn update(&mut self, frame_time: f32, game: &Game) {
+        self.sample_in -= frame_time;
+        if self.sample_in <= 0.0 {
+            self.sample_in = Self::SAMPLE_INTERVAL;
+            self.intensity += (Self::sample(game) - self.intensity) * Self::SMOOTHING;
+            for (layer, playing) in Layer::ALL.into_iter().zip(&mut self.playing) {
+                let (enter, exit) = layer.thresholds();
+                if self.intensity >= enter {
+                    *playing = true;
+                } else if self.intensity < exit {
+                    *playing = false;
+                }
+            }
+        }
+
+        let danger = game.player.health == 1;
+        for (i, layer) in Layer::ALL.into_iter().enumerate() {
+            let target = match layer {
+                Layer::Danger if danger => 1.0,
+                Layer::Danger => 0.0,
+                _ if !self.playing[i] => 0.0,
+                _ if danger => Self::DUCKED,
+                _ => 1.0,
+            };
+            let step = Self::FADE_RATE * frame_time;
+            self.volumes[i] += (target - self.volumes[i]).clamp(-step, step);
+        }
+    }
+
+    // Unsmoothed intensity from how crowded it is around the ship and how much
+    // health is gone
+    fn sample(game: &Game) -> f32 {
+        if game.is_over() {
+            return 0.0;
+        }
+        let ship = game.player.center();
+        let near_distance = Self::NEAR_DISTANCE * game.size_scale;
+        let near = game
+            .asteroids
+            .iter()
+            .filter(|a| geom::distance(a.position, ship) < near_distance + a.radius)
+            .count() as f32;
+        let crowding = (near / Self::NEAR_MAX).min(1.0);
+        let damage = 1.0 - game.player.health as f32 / game.player.max_health as f32;
+        (0.6 * crowding + 0.4 * damage).min(1.0)
+    }
+}
@@ -0,0 +1,269 @@
+// Read-only WebSocket feed of the game state for stream overlays, built with the
+// `overlay` feature. A background thread owns the sockets; the game loop only
+// drops snapshots into a small queue and never waits on the network.
+use crate::{Game, GameMode};
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+// Snapshots waiting for the server thread, the oldest dropped past this
+const QUEUE_LENGTH: usize = 4;
+// Seconds between snapshots
+const PUBLISH_INTERVAL: f32 = 0.25;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(1);
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+pub struct OverlayServer {
+    queue: Arc<Mutex<VecDeque<String>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    // Seconds since the last snapshot was queued
+    since_publish: f32,
+}
+impl OverlayServer {
+    // Listens on localhost only, overlays run on the streaming machine
+    pub fn start(port: u16) -> io::Result<OverlayServer> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        let queue = Arc::new(Mutex::new(VecDeque::with_capacity(QUEUE_LENGTH)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (queue, stop) = (queue.clone(), stop.clone());
+            thread::Builder::new()
+                .name(String::from("overlay"))
+                .spawn(move || serve(listener, &queue, &stop))?
+        };
+        Ok(OverlayServer {
+            queue,
+            stop,
+            thread: Some(thread),
+            since_publish: PUBLISH_INTERVAL,
+        })
+    }
+
+    // Queue a snapshot a few times a second. `snapshot` is only called when one
+    // is due, and a queue the server is holding is skipped until next frame.
+    pub fn publish(&mut self, frame_time: f32, snapshot: impl FnOnce() -> String) {
+        self.since_publish += frame_time;
+        if self.since_publish < PUBLISH_INTERVAL {
+            return;
+        }
+        let Ok(mut queue) = self.queue.try_lock() else {
+            return;
+        };
+        if queue.len() == QUEUE_LENGTH {
+            queue.pop_front();
+        }
+        queue.push_back(snapshot());
+        self.since_publish = 0.0;
+    }
+}
+impl Drop for OverlayServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(listener: TcpListener, queue: &Mutex<VecDeque<String>>, stop: &AtomicBool) {
+    let mut clients: Vec<TcpStream> = vec![];
+    while !stop.load(Ordering::Relaxed) {
+        while let Ok((stream, _)) = listener.accept() {
+            match accept_client(stream) {
+                Ok(client) => clients.push(client),
+                Err(e) => eprintln!("Overlay client failed to connect: {}", e),
+            }
+        }
+
+        // Only the newest snapshot matters to an overlay
+        let latest = queue.lock().ok().and_then(|mut q| {
+            let latest = q.pop_back();
+            q.clear();
+            latest
+        });
+        if let Some(snapshot) = latest {
+            let frame = encode_frame(0x1, snapshot.as_bytes());
+            clients.retain_mut(|c| c.write_all(&frame).is_ok());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    for mut client in clients {
+        let _ = client.write_all(&encode_frame(0x8, &[]));
+    }
+}
+
+// Read the HTTP upgrade request and answer it, after which the client only
+// ever receives frames
+fn accept_client(mut stream: TcpStream) -> io::Result<TcpStream> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+
+    let mut request = vec![];
+    let mut buffer = [0; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 || request.len() > 8192 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete upgrade request",
+            ));
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let key = request.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("sec-websocket-key")
+            .then(|| value.trim())
+    });
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a WebSocket request",
+        ));
+    };
+    let accept = base64(&sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()));
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )?;
+    Ok(stream)
+}
+
+// Unmasked final frame, as sent by servers
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (chunk, state) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&state.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+impl Game {
+    // Everything an overlay shows, as a flat JSON object
+    pub(crate) fn overlay_snapshot(&self) -> String {
+        let mut effects = vec![];
+        if self.focus_active {
+            effects.push("\"focus\"");
+        }
+        if self.gold_rush.is_some() {
+            effects.push("\"gold_rush\"");
+        }
+        if self.deflector.is_running() {
+            effects.push("\"deflector\"");
+        }
+        let wave = if self.mode == GameMode::Waves {
+            self.wave.to_string()
+        } else {
+            String::from("null")
+        };
+        format!(
+            "{{\"mode\":\"{:?}\",\"score\":{},\"health\":{},\"max_health\":{},\"wave\":{},\"focus\":{:.2},\"effects\":[{}],\"ore\":{},\"elapsed\":{:.1},\"over\":{},\"won\":{}}}",
+            self.mode,
+            self.score(),
+            self.player.health,
+            self.player.max_health,
+            wave,
+            self.focus,
+            effects.join(","),
+            self.ore,
+            self.elapsed,
+            self.is_over(),
+            self.has_won()
+        )
+    }
+}
@@ -0,0 +1,37 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Frame-rate cap settings cycle through, None for uncapped
+pub const FPS_CAPS: [Option<u32>; 5] = [None, Some(60), Some(120), Some(144), Some(240)];
+
+// Sleeping overshoots by up to a millisecond or so, the rest is waited out spinning
+const SPIN: Duration = Duration::from_millis(1);
+
+// Holds each frame to the cap by waiting out what's left of its budget once
+// it's been presented. The game steps by the measured frame time, so a capped
+// frame is just a longer one. Vsync already waits inside `next_frame`, and the
+// cap only adds to that wait when it's below the refresh rate.
+pub struct FramePacer {
+    frame_start: Instant,
+}
+impl FramePacer {
+    pub fn new() -> FramePacer {
+        FramePacer {
+            frame_start: Instant::now(),
+        }
+    }
+
+    pub fn wait(&mut self, cap: Option<u32>) {
+        if let Some(fps) = cap {
+            let deadline = self.frame_start + Duration::from_secs_f64(1.0 / fps as f64);
+            let now = Instant::now();
+            if deadline > now + SPIN {
+                thread::sleep(deadline - now - SPIN);
+            }
+            while Instant::now() < deadline {
+                std::hint::spin_loop();
+            }
+        }
+        self.frame_start = Instant::now();
+    }
+}
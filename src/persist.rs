@@ -0,0 +1,400 @@
+// Versioned `key=value` files in the user's data directory. Every file records
+// its `schema_version` and older ones are migrated step by step on load.
+// Loading happens in place at startup, saving is handed to a writer thread so a
+// slow disk never holds up a frame.
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex, OnceLock},
+    thread,
+    time::Duration,
+};
+
+const VERSION_KEY: &str = "schema_version";
+
+#[derive(Default)]
+pub struct Document {
+    entries: Vec<(String, String)>,
+}
+impl Document {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Parse a value, falling back to the type's default if missing or malformed
+    pub fn get_or_default<T: std::str::FromStr + Default>(&self, key: &str) -> T {
+        self.get(key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        let value = value.to_string();
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((String::from(key), value)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    // None if any non-blank line isn't a `key=value` pair
+    fn parse(contents: &str) -> Option<Document> {
+        let mut document = Document::default();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let (key, value) = line.split_once('=')?;
+            document.set(key, value);
+        }
+        Some(document)
+    }
+
+    fn version(&self) -> Option<u32> {
+        // Files written before versioning count as version 1
+        self.get(VERSION_KEY).map_or(Some(1), |v| v.parse().ok())
+    }
+
+    fn contents(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(k, v)| format!("{}={}\n", k, v))
+            .collect()
+    }
+}
+
+pub trait Persisted: Default {
+    const FILE_NAME: &'static str;
+    // Entry i upgrades a document from schema version i + 1 to i + 2
+    const MIGRATIONS: &'static [fn(&mut Document)];
+
+    fn from_document(document: &Document) -> Self;
+    fn to_document(&self) -> Document;
+
+    fn version() -> u32 {
+        Self::MIGRATIONS.len() as u32 + 1
+    }
+
+    fn path() -> PathBuf {
+        data_dir().join(Self::FILE_NAME)
+    }
+}
+
+// Missing files load as defaults. Corrupt ones are moved aside with a `.bad`
+// suffix, and files from a newer version are left alone.
+pub fn load<T: Persisted>() -> T {
+    let path = T::path();
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return T::default(),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            set_aside_corrupt(&path);
+            return T::default();
+        }
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return T::default();
+        }
+    };
+
+    let Some(mut document) = Document::parse(&contents) else {
+        set_aside_corrupt(&path);
+        return T::default();
+    };
+    // There's no version 0, and migrating from it would start before the first step
+    let Some(version) = document.version().filter(|&v| v > 0) else {
+        set_aside_corrupt(&path);
+        return T::default();
+    };
+    if version > T::version() {
+        eprintln!(
+            "{} was created by a newer version of the game (schema {}, this build reads up to {}), using defaults",
+            path.display(),
+            version,
+            T::version()
+        );
+        return T::default();
+    }
+
+    for migrate in &T::MIGRATIONS[(version - 1) as usize..] {
+        migrate(&mut document);
+    }
+    T::from_document(&document)
+}
+
+// Queue `value` to be written out. The document is built right away, so later
+// changes to `value` don't leak into this save, and saves land in the order
+// they were made.
+pub fn save<T: Persisted>(value: &T) {
+    let mut document = value.to_document();
+    document.remove(VERSION_KEY);
+    document
+        .entries
+        .insert(0, (String::from(VERSION_KEY), T::version().to_string()));
+    let job = Job::Save {
+        path: T::path(),
+        version: T::version(),
+        document,
+        write: write_hook(),
+    };
+    // Without a writer thread the save happens here instead
+    if let Err(mpsc::SendError(Job::Save {
+        path,
+        version,
+        document,
+        write,
+    })) = writer().send(job)
+    {
+        write(&path, version, &document);
+    }
+}
+
+// Wait up to `timeout` for every queued save to be written, false if some
+// were still pending when it ran out
+pub fn flush(timeout: Duration) -> bool {
+    let (done, finished) = mpsc::channel();
+    writer().send(Job::Flush(done)).is_err() || finished.recv_timeout(timeout).is_ok()
+}
+
+// Saves that failed since the last call, as messages for the player
+pub fn take_failures() -> Vec<String> {
+    FAILURES
+        .lock()
+        .map(|mut failures| std::mem::take(&mut *failures))
+        .unwrap_or_default()
+}
+
+// Writes one saved document to disk
+pub type WriteFn = fn(&Path, u32, &Document);
+
+enum Job {
+    Save {
+        path: PathBuf,
+        version: u32,
+        document: Document,
+        // Picked on the saving thread, so a test's writer only sees its own saves
+        write: WriteFn,
+    },
+    // Answered once everything queued before it is written
+    Flush(mpsc::Sender<()>),
+}
+
+static WRITER: OnceLock<mpsc::Sender<Job>> = OnceLock::new();
+static FAILURES: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+// Started on the first save
+fn writer() -> &'static mpsc::Sender<Job> {
+    WRITER.get_or_init(|| {
+        let (sender, jobs) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name(String::from("persist"))
+            .spawn(move || {
+                for job in jobs {
+                    match job {
+                        Job::Save {
+                            path,
+                            version,
+                            document,
+                            write,
+                        } => write(&path, version, &document),
+                        Job::Flush(done) => {
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("Failed to start the save thread, saving in place: {}", e);
+        }
+        sender
+    })
+}
+
+fn write_hook() -> WriteFn {
+    #[cfg(test)]
+    if let Some(write) = TEST_WRITER.with(|w| w.get()) {
+        return write;
+    }
+    write_versioned
+}
+
+// Write to a temporary file and rename it over the old one, so there's always
+// a complete file in place, keeping a copy of the previous one as `.bak`
+pub fn write_versioned(path: &Path, version: u32, document: &Document) {
+    let on_disk_version = fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| Document::parse(&contents))
+        .and_then(|document| document.version());
+    if on_disk_version.is_some_and(|v| v > version) {
+        eprintln!(
+            "Not saving over {}, it was created by a newer version of the game",
+            path.display()
+        );
+        return;
+    }
+
+    let temp_path = with_suffix(path, "tmp");
+    let result = fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+        .and_then(|_| {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(document.contents().as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| match fs::copy(path, with_suffix(path, "bak")) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if let Err(e) = result {
+        eprintln!("Failed to save {}: {}", path.display(), e);
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Ok(mut failures) = FAILURES.lock() {
+            failures.push(format!("Couldn't save {}", name));
+        }
+    }
+}
+
+// One-off unversioned document in the data directory, for diagnostics
+pub fn write_dump(name: &str, document: &Document) -> io::Result<PathBuf> {
+    let path = data_dir().join(name);
+    fs::create_dir_all(data_dir()).and_then(|_| fs::write(&path, document.contents()))?;
+    Ok(path)
+}
+
+// A document written by `write_dump`, None if it's missing or unreadable
+pub fn read_dump(name: &str) -> Option<Document> {
+    let contents = fs::read_to_string(data_dir().join(name)).ok()?;
+    Document::parse(&contents)
+}
+
+fn set_aside_corrupt(path: &Path) {
+    let bad_path = with_suffix(path, "bad");
+    eprintln!(
+        "{} is corrupt, using defaults and moving it to {}",
+        path.display(),
+        bad_path.display()
+    );
+    if let Err(e) = fs::rename(path, &bad_path) {
+        eprintln!("Failed to move {}: {}", path.display(), e);
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_DATA_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+    static TEST_WRITER: std::cell::Cell<Option<WriteFn>> = const { std::cell::Cell::new(None) };
+}
+
+// Hand the calling test's saves to `write` instead of `write_versioned`
+#[cfg(test)]
+pub fn use_test_writer(write: WriteFn) {
+    TEST_WRITER.with(|w| w.set(Some(write)));
+}
+
+// Point `data_dir` at a fresh, empty directory for the rest of the calling test
+#[cfg(test)]
+pub fn use_test_data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asteroids-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    TEST_DATA_DIR.with(|d| *d.borrow_mut() = Some(dir.clone()));
+    dir
+}
+
+// Per-user directory for saved data, falling back to the working directory
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_DATA_DIR.with(|dir| dir.borrow().clone()) {
+        return dir;
+    }
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_default();
+    base.join("asteroids")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn settings_path() -> PathBuf {
+        data_dir().join(Settings::FILE_NAME)
+    }
+
+    #[test]
+    fn corrupt_file_loads_defaults_and_is_kept_as_bad() {
+        use_test_data_dir("corrupt");
+        let contents = "this is not a settings file\n";
+        fs::write(settings_path(), contents).unwrap();
+
+        let settings: Settings = load();
+        assert_eq!(settings.text_scale, Settings::default().text_scale);
+        assert!(!settings_path().exists());
+        let bad = fs::read_to_string(with_suffix(&settings_path(), "bad")).unwrap();
+        assert_eq!(bad, contents);
+    }
+
+    #[test]
+    fn version_zero_counts_as_corrupt() {
+        use_test_data_dir("version-zero");
+        fs::write(settings_path(), "schema_version=0\ntext_scale=2\n").unwrap();
+
+        let settings: Settings = load();
+        assert_eq!(settings.text_scale, Settings::default().text_scale);
+        assert!(with_suffix(&settings_path(), "bad").exists());
+    }
+
+    #[test]
+    fn newer_version_is_left_alone() {
+        use_test_data_dir("newer");
+        let contents = format!("schema_version={}\ntext_scale=2\n", Settings::version() + 1);
+        fs::write(settings_path(), &contents).unwrap();
+
+        let settings: Settings = load();
+        assert_eq!(settings.text_scale, Settings::default().text_scale);
+        save(&settings);
+        assert!(flush(Duration::from_secs(5)));
+        assert_eq!(fs::read_to_string(settings_path()).unwrap(), contents);
+    }
+
+    #[test]
+    fn save_replaces_the_file_and_keeps_a_backup() {
+        use_test_data_dir("backup");
+        let mut settings = Settings {
+            text_scale: 1.5,
+            ..Settings::default()
+        };
+        save(&settings);
+        settings.text_scale = 2.0;
+        save(&settings);
+        assert!(flush(Duration::from_secs(5)));
+
+        let loaded: Settings = load();
+        assert_eq!(loaded.text_scale, 2.0);
+        let backup = fs::read_to_string(with_suffix(&settings_path(), "bak")).unwrap();
+        assert!(backup.contains("text_scale=1.5"));
+        assert!(!with_suffix(&settings_path(), "tmp").exists());
+    }
+
+    #[test]
+    fn missing_file_loads_defaults() {
+        use_test_data_dir("missing");
+        let settings: Settings = load();
+        assert_eq!(settings.lanes, Settings::default().lanes);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::keys;
+use crate::{crt::CrtFilter, draw_text_h_centered_color, persist, Game};
+use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
+
+// Free camera over the frozen game for taking screenshots, with the HUD hidden
+pub struct PhotoMode {
+    target: Vec2,
+    zoom: f32,
+    hide_ship: bool,
+    // Saved at the end of the next render, once the frame has been drawn
+    capture: Option<PathBuf>,
+}
+impl PhotoMode {
+    const PAN_SPEED: f32 = 500.0;
+    const MIN_ZOOM: f32 = 0.25;
+    const MAX_ZOOM: f32 = 4.0;
+
+    pub fn new(target: Vec2) -> PhotoMode {
+        PhotoMode {
+            target,
+            zoom: 1.0,
+            hide_ship: false,
+            capture: None,
+        }
+    }
+
+    // Returns where a photo was saved since the last update
+    pub fn update(&mut self, frame_time: f32) -> Option<PathBuf> {
+        let saved = self.capture.take();
+
+        let mut pan = Vec2::ZERO;
+        if keys::is_down(KeyCode::W) {
+            pan.y -= 1.0;
+        }
+        if keys::is_down(KeyCode::S) {
+            pan.y += 1.0;
+        }
+        if keys::is_down(KeyCode::A) {
+            pan.x -= 1.0;
+        }
+        if keys::is_down(KeyCode::D) {
+            pan.x += 1.0;
+        }
+        // Pan by the same amount on screen whatever the zoom
+        self.target += pan * Self::PAN_SPEED * frame_time / self.zoom;
+
+        let wheel = keys::wheel();
+        if wheel != 0.0 {
+            self.zoom =
+                (self.zoom * 1.1_f32.powf(wheel.signum())).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        }
+        if keys::is_pressed(KeyCode::H) {
+            self.hide_ship = !self.hide_ship;
+        }
+        if keys::is_pressed(KeyCode::Enter) {
+            let name = format!("photo-{}.png", miniquad::date::now() as u64);
+            self.capture = Some(persist::data_dir().join("photos").join(name));
+        }
+
+        saved
+    }
+
+    fn view(&self) -> Rect {
+        let size = Vec2::new(screen_width(), screen_height()) / self.zoom;
+        Rect::new(
+            self.target.x - size.x / 2.0,
+            self.target.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+
+    pub fn render(&self, game: &Game, crt: Option<&CrtFilter>) {
+        set_camera(&Camera2D {
+            target: self.target,
+            // Positive y zoom keeps y pointing down when drawing to the screen
+            zoom: Vec2::new(2.0 / screen_width(), 2.0 / screen_height()) * self.zoom,
+            render_target: crt.map(|crt| crt.target().clone()),
+            ..Default::default()
+        });
+        game.render_world(self.view(), self.zoom, !self.hide_ship);
+        match crt {
+            Some(crt) => set_camera(&crt.camera()),
+            None => set_default_camera(),
+        }
+
+        if let Some(path) = &self.capture {
+            let image = match crt {
+                Some(crt) if game.settings.crt_capture_filtered => {
+                    // Filter what's been drawn so far onto the screen early and grab that
+                    crt.present();
+                    set_camera(&crt.camera());
+                    screen_image()
+                }
+                Some(crt) => crt.capture(),
+                None => screen_image(),
+            };
+            save_png(image, path);
+        }
+
+        draw_text_h_centered_color(
+            "Photo mode - WASD: pan   Wheel: zoom   H: hide ship   Enter: save   Esc: back",
+            screen_height() - 20.0,
+            20,
+            GRAY,
+        );
+    }
+}
+
+pub fn screen_image() -> Image {
+    let mut image = get_screen_data();
+    // The framebuffer is read bottom row first
+    let row = image.width as usize * 4;
+    let rows: Vec<&[u8]> = image.bytes.chunks(row).rev().collect();
+    image.bytes = rows.concat();
+    image
+}
+
+pub fn save_png(image: Image, path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("Failed to create {}: {}", dir.display(), e);
+            return;
+        }
+    }
+    image.export_png(&path.to_string_lossy());
+}
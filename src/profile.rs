@@ -0,0 +1,442 @@
+use crate::heatmap::Heatmap;
+use crate::persist::{self, Document, Persisted};
+use crate::unlocks::{Unlockable, UNLOCKABLES};
+
+// One finished run, kept in the profile for the History screen
+#[derive(Clone, Default)]
+pub struct RunSummary {
+    // Seconds since the Unix epoch when the run ended
+    pub ended_at: f64,
+    // `GameMode` name, like "Waves"
+    pub mode: String,
+    pub seed: u64,
+    pub score: u32,
+    pub won: bool,
+    // Seconds played
+    pub duration: f32,
+    pub asteroids_destroyed: u32,
+    pub escaped: u32,
+    // Wave reached, only meaningful in Waves mode
+    pub wave: u32,
+    // Played under weekly challenge rules, which can't be retried later
+    pub weekly: bool,
+    // Empty for runs from before heatmaps were kept
+    pub heatmap: Heatmap,
+    // Cut short by the game crashing, salvaged from the crash report
+    pub crashed: bool,
+    // Thrown away with a quick restart, so it counted for nothing else
+    pub abandoned: bool,
+}
+impl RunSummary {
+    // Fields in order separated by `:`. Fields added later go on the end, so
+    // older records just miss the trailing ones and get defaults.
+    pub fn to_field(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{:.1}:{}:{}:{}:{}:{}:{}:{}",
+            self.ended_at as u64,
+            self.mode,
+            self.seed,
+            self.score,
+            self.won,
+            self.duration,
+            self.asteroids_destroyed,
+            self.escaped,
+            self.wave,
+            self.weekly,
+            self.heatmap.to_field(),
+            self.crashed,
+            self.abandoned
+        )
+    }
+
+    pub fn from_field(field: &str) -> Option<RunSummary> {
+        let mut parts = field.split(':');
+        let mut next = || parts.next().unwrap_or_default();
+        Some(RunSummary {
+            ended_at: next().parse().ok()?,
+            mode: String::from(next()),
+            seed: next().parse().ok()?,
+            score: next().parse().ok()?,
+            won: next().parse().unwrap_or_default(),
+            duration: next().parse().unwrap_or_default(),
+            asteroids_destroyed: next().parse().unwrap_or_default(),
+            escaped: next().parse().unwrap_or_default(),
+            wave: next().parse().unwrap_or_default(),
+            weekly: next().parse().unwrap_or_default(),
+            heatmap: Heatmap::from_field(next()),
+            crashed: next().parse().unwrap_or_default(),
+            abandoned: next().parse().unwrap_or_default(),
+        })
+    }
+}
+
+// A run that made its mode's high score table
+#[derive(Clone)]
+pub struct ScoreRecord {
+    // `GameMode` name of the table it's in
+    pub mode: String,
+    pub score: u32,
+    // Seconds since the Unix epoch when the run ended, 0 if it isn't known
+    pub ended_at: f64,
+    pub seed: u64,
+}
+impl ScoreRecord {
+    fn to_field(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.mode, self.score, self.ended_at as u64, self.seed
+        )
+    }
+
+    fn from_field(field: &str) -> Option<ScoreRecord> {
+        let mut parts = field.split(':');
+        Some(ScoreRecord {
+            mode: String::from(parts.next()?),
+            score: parts.next()?.parse().ok()?,
+            ended_at: parts.next()?.parse().ok()?,
+            seed: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+// Lifetime progress across all runs, stored as `key=value` lines
+#[derive(Default)]
+pub struct Profile {
+    pub runs_played: u32,
+    pub runs_won: u32,
+    pub asteroids_destroyed: u32,
+    pub best_score: u32,
+    pub unlocked: Vec<String>,
+    // Wave mode: waves cleared without damage, and the fastest clear of each wave
+    // in seconds, indexed from wave 1
+    pub perfect_waves: u32,
+    pub best_clear_times: Vec<f32>,
+    // Highest wave reached, the latest a practice run can start from
+    pub best_wave: u32,
+    // Best weekly challenge score by week tag
+    pub weekly_best: Vec<(String, u32)>,
+    // Ids of the new player hints already shown, each only ever comes up once
+    pub shown_hints: Vec<String>,
+    // Ids of the derelict lore lines already found
+    pub seen_lore: Vec<String>,
+    // Every mode's high score table, each best first and capped at `MAX_RECORDS`
+    pub records: Vec<ScoreRecord>,
+    // Most recent runs, oldest first and capped at `MAX_HISTORY`
+    pub history: Vec<RunSummary>,
+    // Debug flag that treats every unlockable as unlocked without saving it
+    pub unlock_all: bool,
+}
+impl Persisted for Profile {
+    const FILE_NAME: &'static str = "profile.txt";
+    const MIGRATIONS: &'static [fn(&mut Document)] = &[add_history, add_records];
+
+    fn from_document(document: &Document) -> Profile {
+        Profile {
+            runs_played: document.get_or_default("runs_played"),
+            runs_won: document.get_or_default("runs_won"),
+            asteroids_destroyed: document.get_or_default("asteroids_destroyed"),
+            best_score: document.get_or_default("best_score"),
+            unlocked: document
+                .get("unlocked")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|id| !id.is_empty())
+                .map(String::from)
+                .collect(),
+            perfect_waves: document.get_or_default("perfect_waves"),
+            best_wave: document.get_or_default("best_wave"),
+            best_clear_times: document
+                .get("best_clear_times")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|time| time.parse().ok())
+                .collect(),
+            weekly_best: document
+                .get("weekly_best")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|entry| {
+                    let (tag, score) = entry.split_once(':')?;
+                    Some((String::from(tag), score.parse().ok()?))
+                })
+                .collect(),
+            shown_hints: document
+                .get("shown_hints")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|id| !id.is_empty())
+                .map(String::from)
+                .collect(),
+            seen_lore: document
+                .get("seen_lore")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|id| !id.is_empty())
+                .map(String::from)
+                .collect(),
+            records: document
+                .get("records")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(ScoreRecord::from_field)
+                .collect(),
+            history: document
+                .get("history")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(RunSummary::from_field)
+                .collect(),
+            unlock_all: false,
+        }
+    }
+
+    fn to_document(&self) -> Document {
+        let mut document = Document::default();
+        document.set("runs_played", self.runs_played);
+        document.set("runs_won", self.runs_won);
+        document.set("asteroids_destroyed", self.asteroids_destroyed);
+        document.set("best_score", self.best_score);
+        document.set("unlocked", self.unlocked.join(","));
+        document.set("perfect_waves", self.perfect_waves);
+        document.set("best_wave", self.best_wave);
+        let clear_times: Vec<String> = self
+            .best_clear_times
+            .iter()
+            .map(|time| format!("{:.2}", time))
+            .collect();
+        document.set("best_clear_times", clear_times.join(","));
+        let weekly_best: Vec<String> = self
+            .weekly_best
+            .iter()
+            .map(|(tag, score)| format!("{}:{}", tag, score))
+            .collect();
+        document.set("weekly_best", weekly_best.join(","));
+        document.set("shown_hints", self.shown_hints.join(","));
+        document.set("seen_lore", self.seen_lore.join(","));
+        let records: Vec<String> = self.records.iter().map(ScoreRecord::to_field).collect();
+        document.set("records", records.join(","));
+        let history: Vec<String> = self.history.iter().map(RunSummary::to_field).collect();
+        document.set("history", history.join(","));
+        document
+    }
+}
+impl Profile {
+    const MAX_HISTORY: usize = 50;
+    pub const MAX_RECORDS: usize = 10;
+    // Hints only come up during a player's first few runs
+    const HINT_RUNS: u32 = 5;
+
+    pub fn load() -> Profile {
+        persist::load()
+    }
+
+    pub fn save(&self) {
+        persist::save(self);
+    }
+
+    // Keep a finished run in the history, dropping the oldest past the cap
+    pub fn record_history(&mut self, run: RunSummary) {
+        self.history.push(run);
+        if self.history.len() > Self::MAX_HISTORY {
+            let excess = self.history.len() - Self::MAX_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+
+    // Retried seeds still count toward lifetime stats, but not the best score
+    pub fn record_run(
+        &mut self,
+        score: u32,
+        won: bool,
+        asteroids_destroyed: u32,
+        retried_seed: bool,
+    ) {
+        self.runs_played += 1;
+        if won {
+            self.runs_won += 1;
+        }
+        self.asteroids_destroyed += asteroids_destroyed;
+        if !retried_seed {
+            self.best_score = self.best_score.max(score);
+        }
+    }
+
+    // One mode's high score table, best first
+    pub fn records<'a>(&'a self, mode: &'a str) -> impl Iterator<Item = &'a ScoreRecord> {
+        self.records.iter().filter(move |r| r.mode == mode)
+    }
+
+    // Put a run in its mode's table if it's good enough, returning its place
+    // from 0. Ties go below the runs already there.
+    pub fn record_score(&mut self, record: ScoreRecord) -> Option<usize> {
+        let table: Vec<usize> = (0..self.records.len())
+            .filter(|&i| self.records[i].mode == record.mode)
+            .collect();
+        let place = table
+            .iter()
+            .take_while(|&&i| self.records[i].score >= record.score)
+            .count();
+        if place >= Self::MAX_RECORDS {
+            return None;
+        }
+        // Just before the run it beat, which keeps the table in order
+        let index = table.get(place).copied().unwrap_or(self.records.len());
+        self.records.insert(index, record);
+        if table.len() >= Self::MAX_RECORDS {
+            // The old last place, moved along one by the insert
+            self.records.remove(table[Self::MAX_RECORDS - 1] + 1);
+        }
+        Some(place)
+    }
+
+    pub fn record_waves(&mut self, wave: u32, perfect_waves: u32, clear_times: &[f32]) {
+        self.best_wave = self.best_wave.max(wave);
+        self.perfect_waves += perfect_waves;
+        for (i, &time) in clear_times.iter().enumerate() {
+            match self.best_clear_times.get_mut(i) {
+                Some(best) => *best = best.min(time),
+                None => self.best_clear_times.push(time),
+            }
+        }
+    }
+
+    pub fn record_weekly(&mut self, tag: &str, score: u32) {
+        match self.weekly_best.iter_mut().find(|(t, _)| t == tag) {
+            Some((_, best)) => *best = (*best).max(score),
+            None => self.weekly_best.push((String::from(tag), score)),
+        }
+    }
+
+    pub fn weekly_best(&self, tag: &str) -> Option<u32> {
+        self.weekly_best
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, best)| *best)
+    }
+
+    pub fn wants_hints(&self) -> bool {
+        self.runs_played < Self::HINT_RUNS
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlock_all || self.unlocked.iter().any(|u| u == id)
+    }
+
+    // Unlock everything whose requirement is now met, returning what was new
+    pub fn grant_unlocks(&mut self) -> Vec<&'static Unlockable> {
+        let mut granted = vec![];
+        for unlockable in UNLOCKABLES.iter() {
+            if !self.unlocked.iter().any(|u| u == unlockable.id) && (unlockable.is_met)(self) {
+                self.unlocked.push(String::from(unlockable.id));
+                granted.push(unlockable);
+            }
+        }
+        granted
+    }
+}
+
+// Version 2 added the run history. Older profiles start with an empty one.
+fn add_history(document: &mut Document) {
+    document.set("history", "");
+}
+
+// Version 3 split high scores into a table per mode. The single best score
+// from before starts the Classic table, with no date or seed to go with it.
+fn add_records(document: &mut Document) {
+    let best: u32 = document.get_or_default("best_score");
+    if best > 0 {
+        document.set("records", format!("Classic:{}:0:0", best));
+    } else {
+        document.set("records", "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn record(mode: &str, score: u32, seed: u64) -> ScoreRecord {
+        ScoreRecord {
+            mode: String::from(mode),
+            score,
+            ended_at: 0.0,
+            seed,
+        }
+    }
+
+    fn scores(profile: &Profile, mode: &str) -> Vec<u32> {
+        profile.records(mode).map(|r| r.score).collect()
+    }
+
+    #[test]
+    fn scores_go_in_best_first() {
+        let mut profile = Profile::default();
+        assert_eq!(profile.record_score(record("Classic", 50, 1)), Some(0));
+        assert_eq!(profile.record_score(record("Classic", 100, 2)), Some(0));
+        assert_eq!(profile.record_score(record("Classic", 75, 3)), Some(1));
+        assert_eq!(profile.record_score(record("Classic", 10, 4)), Some(3));
+        assert_eq!(scores(&profile, "Classic"), [100, 75, 50, 10]);
+    }
+
+    #[test]
+    fn ties_go_below_the_runs_already_there() {
+        let mut profile = Profile::default();
+        profile.record_score(record("Classic", 100, 1));
+        profile.record_score(record("Classic", 50, 2));
+        assert_eq!(profile.record_score(record("Classic", 100, 3)), Some(1));
+        let seeds: Vec<u64> = profile.records("Classic").map(|r| r.seed).collect();
+        assert_eq!(seeds, [1, 3, 2]);
+    }
+
+    #[test]
+    fn a_full_table_drops_its_last_place() {
+        let mut profile = Profile::default();
+        for score in 1..=Profile::MAX_RECORDS as u32 {
+            profile.record_score(record("Classic", score * 10, 0));
+        }
+        assert_eq!(profile.record_score(record("Classic", 5, 0)), None);
+        assert_eq!(profile.record_score(record("Classic", 10, 0)), None);
+        assert_eq!(profile.record_score(record("Classic", 55, 0)), Some(5));
+        assert_eq!(
+            scores(&profile, "Classic"),
+            [100, 90, 80, 70, 60, 55, 50, 40, 30, 20]
+        );
+    }
+
+    #[test]
+    fn modes_keep_separate_tables() {
+        let mut profile = Profile::default();
+        for score in 1..=Profile::MAX_RECORDS as u32 {
+            profile.record_score(record("Classic", score * 10, 0));
+            profile.record_score(record("Waves", score, 0));
+        }
+        let classic = scores(&profile, "Classic");
+
+        // A low score still tops an empty table, and a full one only pushes out its own runs
+        assert_eq!(profile.record_score(record("Mining", 1, 0)), Some(0));
+        assert_eq!(profile.record_score(record("Waves", 1000, 0)), Some(0));
+        assert_eq!(scores(&profile, "Classic"), classic);
+        assert_eq!(scores(&profile, "Mining"), [1]);
+        assert_eq!(
+            scores(&profile, "Waves"),
+            [1000, 10, 9, 8, 7, 6, 5, 4, 3, 2]
+        );
+        assert_eq!(profile.records.len(), 2 * Profile::MAX_RECORDS + 1);
+    }
+
+    #[test]
+    fn version_2_best_score_starts_the_classic_table() {
+        persist::use_test_data_dir("profile-v2");
+        let v2 = "schema_version=2\nruns_played=4\nbest_score=420\nhistory=\n";
+        fs::write(Profile::path(), v2).unwrap();
+        let profile = Profile::load();
+        assert_eq!(profile.runs_played, 4);
+        assert_eq!(scores(&profile, "Classic"), [420]);
+        let migrated = profile.records("Classic").next().unwrap();
+        assert_eq!((migrated.ended_at, migrated.seed), (0.0, 0));
+
+        fs::write(Profile::path(), "schema_version=2\nruns_played=1\n").unwrap();
+        assert!(Profile::load().records.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+// UI text that names actions instead of keys. `{Confirm}` or `{fire}` in a
+// prompt is drawn as a boxed glyph of whatever key is bound to it, and any
+// other `{token}` is shown as a key label as-is.
+use crate::bindings::{key_label, Bindings, GameAction, UiAction};
+use macroquad::prelude::*;
+
+enum Segment<'a> {
+    Text(&'a str),
+    Glyph(String),
+}
+
+fn segments<'a>(text: &'a str, bindings: &Bindings) -> Vec<Segment<'a>> {
+    let mut segments = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        segments.push(Segment::Glyph(glyph_label(
+            &rest[start + 1..start + length],
+            bindings,
+        )));
+        rest = &rest[start + length + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+fn glyph_label(token: &str, bindings: &Bindings) -> String {
+    if let Some(action) = UiAction::from_name(token) {
+        key_label(action.key())
+    } else if let Some(action) = GameAction::from_name(token) {
+        key_label(bindings.key(action))
+    } else {
+        String::from(token)
+    }
+}
+
+fn glyph_font_size(font_size: u16) -> u16 {
+    (font_size as f32 * 0.75) as u16
+}
+
+fn segment_width(segment: &Segment, font_size: u16) -> f32 {
+    match segment {
+        Segment::Text(text) => measure_text(text, None, font_size, 1.0).width,
+        Segment::Glyph(label) => {
+            measure_text(label, None, glyph_font_size(font_size), 1.0).width + 10.0
+        }
+    }
+}
+
+pub fn measure_prompt(text: &str, font_size: u16, bindings: &Bindings) -> f32 {
+    segments(text, bindings)
+        .iter()
+        .map(|s| segment_width(s, font_size))
+        .sum()
+}
+
+pub fn draw_prompt(text: &str, x: f32, y: f32, font_size: u16, color: Color, bindings: &Bindings) {
+    let mut x = x;
+    for segment in segments(text, bindings) {
+        let width = segment_width(&segment, font_size);
+        match &segment {
+            Segment::Text(text) => {
+                draw_text(text, x, y, font_size as f32, color);
+            }
+            Segment::Glyph(label) => {
+                // Key cap box sitting on the text baseline
+                let height = font_size as f32 * 0.85;
+                draw_rectangle_lines(x + 1.0, y - height * 0.8, width - 2.0, height, 1.0, color);
+                draw_text(
+                    label,
+                    x + 5.0,
+                    y - height * 0.15,
+                    glyph_font_size(font_size) as f32,
+                    color,
+                );
+            }
+        }
+        x += width;
+    }
+}
+
+pub fn draw_prompt_centered_at(
+    text: &str,
+    center_x: f32,
+    y: f32,
+    font_size: u16,
+    color: Color,
+    bindings: &Bindings,
+) {
+    // Scaled with the UI text, and shrunk if it would run off the screen
+    let font_size = crate::fit_font_size(measure_prompt(text, font_size, bindings), font_size);
+    let width = measure_prompt(text, font_size, bindings);
+    draw_prompt(text, center_x - width / 2.0, y, font_size, color, bindings);
+}
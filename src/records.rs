@@ -0,0 +1,101 @@
+use crate::bindings::{Bindings, UiAction};
+use crate::history::format_date;
+use crate::profile::Profile;
+use crate::{draw_text_h_centered, draw_text_h_centered_color, prompt, ui, GameMode};
+use macroquad::prelude::*;
+
+// High score tables off the title screen, a page per mode
+pub struct RecordsBrowser {
+    page: usize,
+}
+impl RecordsBrowser {
+    const TOP: f32 = 170.0;
+    const ROW_HEIGHT: f32 = 32.0;
+    const COLUMNS: [(&'static str, f32); 4] = [
+        ("#", 0.0),
+        ("Score", 60.0),
+        ("Date", 200.0),
+        ("Seed", 400.0),
+    ];
+
+    // Versus scores only count the defender's side, so it has no table
+    fn modes() -> impl Iterator<Item = GameMode> {
+        GameMode::ALL.into_iter().filter(|m| *m != GameMode::Versus)
+    }
+
+    // Opened on `mode`'s page, or the first if it has none
+    pub fn new(mode: GameMode) -> RecordsBrowser {
+        RecordsBrowser {
+            page: Self::modes().position(|m| m == mode).unwrap_or(0),
+        }
+    }
+
+    fn mode(&self) -> GameMode {
+        Self::modes().nth(self.page).unwrap_or(GameMode::Classic)
+    }
+
+    // Returns true when it's closed
+    pub fn update(&mut self) -> bool {
+        let pages = Self::modes().count();
+        if UiAction::NavLeft.is_pressed() {
+            self.page = (self.page + pages - 1) % pages;
+        } else if UiAction::NavRight.is_pressed() {
+            self.page = (self.page + 1) % pages;
+        }
+        UiAction::Back.is_pressed()
+    }
+
+    pub fn render(&self, profile: &Profile, bindings: &Bindings) {
+        draw_text_h_centered("High Scores", ui(60.0), 40);
+        let mode = format!("{:?}", self.mode());
+        draw_text_h_centered_color(
+            &format!(
+                "< {} >   {} / {}",
+                mode,
+                self.page + 1,
+                Self::modes().count()
+            ),
+            ui(105.0),
+            28,
+            YELLOW,
+        );
+
+        let records: Vec<_> = profile.records(&mode).collect();
+        if records.is_empty() {
+            draw_text_h_centered_color("No scores yet", screen_height() / 2.0, 28, GRAY);
+        } else {
+            let x = (screen_width() / 2.0 - ui(270.0)).max(10.0);
+            for (header, column_x) in Self::COLUMNS {
+                draw_text(
+                    header,
+                    x + ui(column_x),
+                    ui(Self::TOP - 20.0),
+                    ui(20.0),
+                    GRAY,
+                );
+            }
+            for (i, record) in records.iter().enumerate() {
+                let y = ui(Self::TOP + 10.0) + i as f32 * ui(Self::ROW_HEIGHT);
+                // Carried over from before there were tables, without the details
+                let (date, seed) = if record.ended_at > 0.0 {
+                    (format_date(record.ended_at), record.seed.to_string())
+                } else {
+                    (String::from("-"), String::from("-"))
+                };
+                let cells = [(i + 1).to_string(), record.score.to_string(), date, seed];
+                for ((_, column_x), text) in Self::COLUMNS.iter().zip(cells) {
+                    draw_text(&text, x + ui(*column_x), y, ui(22.0), WHITE);
+                }
+            }
+        }
+
+        prompt::draw_prompt_centered_at(
+            "{NavLeft} / {NavRight} to change mode, {Back} to return",
+            screen_width() / 2.0,
+            screen_height() - ui(30.0),
+            20,
+            GRAY,
+            bindings,
+        );
+    }
+}
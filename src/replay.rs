@@ -0,0 +1,207 @@
+use crate::keys;
+use crate::{draw_text_h_centered, ui, Game, Input};
+use macroquad::prelude::*;
+
+// Seconds of recorded play between snapshots used for seeking
+const SNAPSHOT_INTERVAL: f32 = 10.0;
+// Seconds skipped by the left and right arrow keys
+const SEEK_STEP: f32 = 5.0;
+const PLAYBACK_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+
+#[derive(Clone, Copy)]
+struct ReplayFrame {
+    frame_time: f32,
+    input: Input,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MarkerKind {
+    Hit,
+    Death,
+}
+
+struct Snapshot {
+    // Index of the first frame that has not been applied to `game` yet
+    frame: usize,
+    time: f32,
+    game: Game,
+}
+
+// Inputs of a single run, replayed on top of the seeded starting state
+pub struct Replay {
+    frames: Vec<ReplayFrame>,
+    snapshots: Vec<Snapshot>,
+    markers: Vec<(f32, MarkerKind)>,
+    duration: f32,
+    last_health: usize,
+}
+impl Replay {
+    pub fn new(game: &Game) -> Replay {
+        Replay {
+            frames: vec![],
+            snapshots: vec![Snapshot {
+                frame: 0,
+                time: 0.0,
+                game: game.clone(),
+            }],
+            markers: vec![],
+            duration: 0.0,
+            last_health: game.player.health,
+        }
+    }
+
+    // Called after every tick with the inputs that were fed to it
+    pub fn record(&mut self, frame_time: f32, input: Input, game: &Game) {
+        self.frames.push(ReplayFrame { frame_time, input });
+        self.duration += frame_time;
+
+        if game.player.health < self.last_health {
+            let kind = if game.player.health == 0 {
+                MarkerKind::Death
+            } else {
+                MarkerKind::Hit
+            };
+            self.markers.push((self.duration, kind));
+        }
+        self.last_health = game.player.health;
+
+        let last_snapshot_time = self.snapshots.last().map_or(0.0, |s| s.time);
+        if self.duration - last_snapshot_time >= SNAPSHOT_INTERVAL {
+            self.snapshots.push(Snapshot {
+                frame: self.frames.len(),
+                time: self.duration,
+                game: game.clone(),
+            });
+        }
+    }
+}
+
+pub struct ReplayPlayer {
+    replay: Replay,
+    game: Game,
+    frame: usize,
+    time: f32,
+    paused: bool,
+    speed_index: usize,
+    // Fractional sim steps owed to the playback speed
+    step_accumulator: f32,
+}
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        let game = replay.snapshots[0].game.clone();
+        ReplayPlayer {
+            replay,
+            game,
+            frame: 0,
+            time: 0.0,
+            paused: false,
+            speed_index: 1,
+            step_accumulator: 0.0,
+        }
+    }
+
+    pub fn into_replay(self) -> Replay {
+        self.replay
+    }
+
+    pub fn update(&mut self) {
+        if keys::is_pressed(KeyCode::Space) {
+            self.paused = !self.paused;
+        }
+        if keys::is_pressed(KeyCode::Up) {
+            self.speed_index = (self.speed_index + 1).min(PLAYBACK_SPEEDS.len() - 1);
+        }
+        if keys::is_pressed(KeyCode::Down) {
+            self.speed_index = self.speed_index.saturating_sub(1);
+        }
+        if keys::is_pressed(KeyCode::Left) {
+            self.seek(self.time - SEEK_STEP);
+        }
+        if keys::is_pressed(KeyCode::Right) {
+            self.seek(self.time + SEEK_STEP);
+        }
+
+        if self.paused {
+            return;
+        }
+        // Playback speed is the number of recorded frames simulated per rendered frame
+        self.step_accumulator += PLAYBACK_SPEEDS[self.speed_index];
+        while self.step_accumulator >= 1.0 && self.frame < self.replay.frames.len() {
+            self.step();
+            self.step_accumulator -= 1.0;
+        }
+    }
+
+    fn step(&mut self) {
+        let frame = self.replay.frames[self.frame];
+        self.game.tick(frame.frame_time, &frame.input);
+        // The live run already logged these events
+        self.game.events.clear();
+        self.time += frame.frame_time;
+        self.frame += 1;
+    }
+
+    fn seek(&mut self, target: f32) {
+        let target = target.clamp(0.0, self.replay.duration);
+        let snapshot = self
+            .replay
+            .snapshots
+            .iter()
+            .rev()
+            .find(|s| s.time <= target)
+            .unwrap_or(&self.replay.snapshots[0]);
+
+        self.game = snapshot.game.clone();
+        self.frame = snapshot.frame;
+        self.time = snapshot.time;
+        while self.frame < self.replay.frames.len()
+            && self.time + self.replay.frames[self.frame].frame_time <= target
+        {
+            self.step();
+        }
+        self.step_accumulator = 0.0;
+    }
+
+    pub fn render(&self) {
+        self.game.render();
+
+        let status = if self.paused {
+            String::from("Replay - paused")
+        } else {
+            format!("Replay - {}x", PLAYBACK_SPEEDS[self.speed_index])
+        };
+        draw_text_h_centered(&status, ui(28.0), 28);
+
+        // Timeline bar along the bottom of the screen
+        let margin = 40.0;
+        let bar_width = screen_width() - margin * 2.0;
+        let bar_y = screen_height() - 40.0;
+        let duration = self.replay.duration.max(f32::EPSILON);
+        draw_line(margin, bar_y, margin + bar_width, bar_y, 2.0, GRAY);
+        draw_line(
+            margin,
+            bar_y,
+            margin + bar_width * (self.time / duration),
+            bar_y,
+            2.0,
+            WHITE,
+        );
+        for (time, kind) in &self.replay.markers {
+            let x = margin + bar_width * (time / duration);
+            let color = if *kind == MarkerKind::Death {
+                RED
+            } else {
+                ORANGE
+            };
+            draw_line(x, bar_y - 8.0, x, bar_y + 8.0, 2.0, color);
+        }
+
+        draw_text(
+            "Space: pause   Left/Right: seek 5s   Up/Down: speed   Esc: back",
+            margin,
+            bar_y + 28.0,
+            ui(20.0),
+            GRAY,
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// Small seedable PCG32 generator. Unlike macroquad's global `rand`, the state
+// lives on the game so runs can be reproduced and cloned into snapshots.
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+    // Numbers drawn since seeding, logged to pin down where two runs diverge
+    draws: u64,
+}
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+    const INCREMENT: u64 = 1442695040888963407;
+
+    pub fn new(seed: u64) -> Rng {
+        let mut rng = Rng { state: 0, draws: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng.draws = 0;
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.draws += 1;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        let r = self.next_u32() as f32 / u32::MAX as f32;
+        low + (high - low) * r
+    }
+}
+
+pub fn fresh_seed() -> u64 {
+    (macroquad::miniquad::date::now() * 1000.0) as u64
+}
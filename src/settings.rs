@@ -0,0 +1,440 @@
+use crate::bindings::{self, Bindings, GameAction};
+use crate::pacing::FPS_CAPS;
+use crate::persist::{self, Document, Persisted};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum DisplayMode {
+    // Covers the whole display at desktop resolution. miniquad implements
+    // fullscreen as a borderless window, so there is no separate exclusive mode.
+    #[default]
+    Fullscreen,
+    Windowed,
+}
+
+// What happens at the edges of the play field
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum BoundaryMode {
+    // The ship can fly off and asteroids drift away for good
+    #[default]
+    Open,
+    // The ship stops at the edge and asteroids bounce back in
+    Walls,
+    // The ship comes back in on the opposite edge, asteroids drift away as in
+    // Open unless they wrap as well
+    Wrap,
+}
+
+// What becomes of asteroids that drift off the field, outside of walls mode
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum AsteroidBehavior {
+    // Gone for good and counted as escaped, with new ones topping the field back up
+    #[default]
+    Despawn,
+    // Back in on the opposite edge straight away, so the field only thins out
+    // as rocks are destroyed
+    Wrap,
+}
+
+// How much the HUD shows beyond the essentials
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum HudDetail {
+    #[default]
+    Full,
+    // Score, health and mode status only, no event feed
+    Minimal,
+}
+
+// Information panels the idle title screen cycles through
+#[derive(Clone, Copy, PartialEq)]
+pub enum TitlePanel {
+    // Title and start prompts
+    Main,
+    LifetimeStats,
+    Controls,
+    // Only shown once something has been unlocked
+    Unlocks,
+}
+
+// Player preferences that apply across runs
+#[derive(Clone)]
+pub struct Settings {
+    // Skip screen-freezing and other motion-heavy effects
+    pub reduced_motion: bool,
+    // Accessibility: UI text size from 1x to 2x, a thick filled ship and a line
+    // showing which way it faces. Saved between sessions.
+    pub text_scale: f32,
+    pub high_visibility_ship: bool,
+    pub heading_line: bool,
+    pub display_mode: DisplayMode,
+    pub monitor: Option<usize>,
+    // Slow the game down for a moment when health first drops to 1
+    pub last_hit_warning: bool,
+    // Index into `unlocks::PALETTES`
+    pub palette: usize,
+    // Debug flag that makes every unlockable available
+    pub unlock_all: bool,
+    // Record debug events, on by default in debug builds or when writing a log file
+    pub log_events: bool,
+    pub log_file: Option<String>,
+    // Backtick opens the developer console, on by default in debug builds
+    pub dev_console: bool,
+    // Debug builds check the game state after every tick and panic on a broken
+    // invariant. With this set the state is written to a file first.
+    pub integrity_dump: bool,
+    // Run the random training agent headless for this many steps and exit
+    pub random_agent_steps: Option<u64>,
+    // Seed for runs started from the title instead of a fresh one each time
+    pub seed: Option<u64>,
+    // Save the main screens at a few resolutions to target/ui-snapshots and
+    // exit, debug builds only
+    pub ui_snapshot: bool,
+    // Port for the stream overlay feed, only in builds with the `overlay` feature
+    pub overlay_port: Option<u16>,
+    // Order of the idle title screen panels and seconds each one stays up. Any
+    // input goes back to the first one.
+    pub title_panels: Vec<TitlePanel>,
+    pub title_panel_interval: f32,
+    pub bindings: Bindings,
+    pub hud_detail: HudDetail,
+    pub boundary: BoundaryMode,
+    pub asteroid_behavior: AsteroidBehavior,
+    // Sandbox that fills the field with hundreds of small asteroids, for fun
+    // and profiling. Runs played in it aren't recorded.
+    pub storm: bool,
+    // Lasers fired on the move scatter a little, more the faster the ship goes
+    pub laser_spread: bool,
+    // Lasers come out of a limited magazine refilled by salvage from destroyed
+    // asteroids and a slow trickle
+    pub ammo: bool,
+    // Longest the field is watched after the ship is destroyed before the
+    // results come up, 0 to go straight to them
+    pub spectate_max: f32,
+    // Frames per second the game is held to, None for as fast as it goes. Vsync
+    // is only applied when the window opens. Both are saved.
+    pub fps_cap: Option<u32>,
+    pub vsync: bool,
+    // One-time tips during the first few runs, saved
+    pub hints: bool,
+    // Spawn director buying what comes in from a budget instead of keeping the
+    // field topped up, outside of Waves and Versus
+    pub director: bool,
+    // Learning aid drawing where nearby asteroids are headed, off in
+    // competitive runs, saved
+    pub lanes: bool,
+    // Multiplier on the play field's line weights, on top of the screen size
+    pub stroke_scale: f32,
+    // Background stars per megapixel, 0 for none
+    pub star_density: f32,
+    // Asteroids are filled in dark so they hide the stars behind them. Off for
+    // slow machines, it about doubles what asteroids cost to draw.
+    pub asteroid_fill: bool,
+    // Seed cosmetic effects from the run seed so the same seed looks the same,
+    // instead of every run getting its own
+    pub deterministic_effects: bool,
+    // CRT post-process and its strengths from 0 to 1
+    pub crt: bool,
+    pub crt_scanlines: f32,
+    pub crt_curvature: f32,
+    pub crt_vignette: f32,
+    // Photos are saved with the filter applied instead of the clean frame
+    pub crt_capture_filtered: bool,
+}
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            reduced_motion: false,
+            text_scale: 1.0,
+            high_visibility_ship: false,
+            heading_line: false,
+            display_mode: DisplayMode::default(),
+            monitor: None,
+            last_hit_warning: true,
+            palette: 0,
+            unlock_all: false,
+            log_events: cfg!(debug_assertions),
+            log_file: None,
+            dev_console: cfg!(debug_assertions),
+            integrity_dump: false,
+            overlay_port: None,
+            random_agent_steps: None,
+            seed: None,
+            ui_snapshot: false,
+            title_panels: vec![
+                TitlePanel::Main,
+                TitlePanel::LifetimeStats,
+                TitlePanel::Controls,
+                TitlePanel::Unlocks,
+            ],
+            title_panel_interval: 8.0,
+            bindings: Bindings::default(),
+            hud_detail: HudDetail::default(),
+            boundary: BoundaryMode::default(),
+            asteroid_behavior: AsteroidBehavior::default(),
+            storm: false,
+            laser_spread: false,
+            ammo: false,
+            spectate_max: 4.0,
+            fps_cap: None,
+            vsync: true,
+            hints: true,
+            lanes: false,
+            director: false,
+            stroke_scale: 1.0,
+            star_density: 150.0,
+            asteroid_fill: true,
+            deterministic_effects: false,
+            crt: false,
+            crt_scanlines: 0.25,
+            crt_curvature: 0.08,
+            crt_vignette: 0.4,
+            crt_capture_filtered: false,
+        }
+    }
+}
+impl Settings {
+    const MAX_TEXT_SCALE: f32 = 2.0;
+
+    pub fn from_args() -> Settings {
+        let mut settings: Settings = persist::load();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--reduced-motion" => settings.reduced_motion = true,
+                "--text-scale" => match args.next().and_then(|s| s.parse::<f32>().ok()) {
+                    Some(scale) => settings.text_scale = scale.clamp(1.0, Self::MAX_TEXT_SCALE),
+                    None => eprintln!("--text-scale needs a multiplier from 1 to 2"),
+                },
+                "--high-visibility-ship" => settings.high_visibility_ship = true,
+                "--heading-line" => settings.heading_line = true,
+                "--fullscreen" => settings.display_mode = DisplayMode::Fullscreen,
+                "--windowed" => settings.display_mode = DisplayMode::Windowed,
+                "--unlock-all" => settings.unlock_all = true,
+                "--no-last-hit-warning" => settings.last_hit_warning = false,
+                "--log-file" => {
+                    settings.log_file = args.next();
+                    settings.log_events |= settings.log_file.is_some();
+                }
+                "--deterministic-effects" => settings.deterministic_effects = true,
+                "--laser-spread" => settings.laser_spread = true,
+                "--ammo" => settings.ammo = true,
+                "--storm" => settings.storm = true,
+                "--spectate" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(seconds) => settings.spectate_max = seconds,
+                    None => eprintln!("--spectate needs a number of seconds"),
+                },
+                "--stroke-scale" => match args.next().and_then(|s| s.parse::<f32>().ok()) {
+                    Some(scale) if scale > 0.0 => settings.stroke_scale = scale,
+                    _ => eprintln!("--stroke-scale needs a positive multiplier"),
+                },
+                "--stars" => match args.next().and_then(|d| d.parse().ok()) {
+                    Some(density) => settings.star_density = density,
+                    None => eprintln!("--stars needs a number of stars per megapixel"),
+                },
+                "--fps-cap" => match args.next().as_deref() {
+                    Some("off") => settings.fps_cap = None,
+                    Some(fps) => match fps.parse() {
+                        Ok(fps) if fps > 0 => settings.fps_cap = Some(fps),
+                        _ => eprintln!("--fps-cap needs a frame rate or off"),
+                    },
+                    None => eprintln!("--fps-cap needs a frame rate or off"),
+                },
+                "--no-hints" => settings.hints = false,
+                "--lanes" => settings.lanes = true,
+                "--no-asteroid-fill" => settings.asteroid_fill = false,
+                "--director" => settings.director = true,
+                "--vsync" => settings.vsync = true,
+                "--no-vsync" => settings.vsync = false,
+                "--walls" => settings.boundary = BoundaryMode::Walls,
+                "--wrap" => settings.boundary = BoundaryMode::Wrap,
+                "--asteroid-wrap" => settings.asteroid_behavior = AsteroidBehavior::Wrap,
+                "--minimal-hud" => settings.hud_detail = HudDetail::Minimal,
+                "--crt" => settings.crt = true,
+                "--crt-capture-filtered" => settings.crt_capture_filtered = true,
+                "--console" => settings.dev_console = true,
+                "--integrity-dump" => settings.integrity_dump = true,
+                "--ui-snapshot" => settings.ui_snapshot = true,
+                "--seed" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(seed) => settings.seed = Some(seed),
+                    None => eprintln!("--seed needs a number"),
+                },
+                "--random-agent" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(steps) => settings.random_agent_steps = Some(steps),
+                    None => eprintln!("--random-agent needs a number of steps"),
+                },
+                "--overlay" => match args.next().and_then(|p| p.parse().ok()) {
+                    Some(port) => settings.overlay_port = Some(port),
+                    None => eprintln!("--overlay needs a port number"),
+                },
+                "--bind" => match args.next() {
+                    Some(binding) => settings.bind(&binding),
+                    None => eprintln!("--bind needs an action=key argument"),
+                },
+                "--monitor" => settings.monitor = args.next().and_then(|m| m.parse().ok()),
+                _ => eprintln!("Ignoring unknown argument: {}", arg),
+            }
+        }
+        settings
+    }
+
+    // Next text size in 0.25 steps, back to 1x after the largest
+    pub fn cycle_text_scale(&mut self) {
+        self.text_scale = if self.text_scale >= Self::MAX_TEXT_SCALE {
+            1.0
+        } else {
+            (self.text_scale + 0.25).min(Self::MAX_TEXT_SCALE)
+        };
+    }
+
+    // Next frame-rate cap, back to uncapped after the highest. A cap set on the
+    // command line that isn't in the list goes to the first one above it.
+    pub fn cycle_fps_cap(&mut self) {
+        self.fps_cap = match self.fps_cap {
+            None => FPS_CAPS[1],
+            Some(current) => FPS_CAPS.into_iter().flatten().find(|&fps| fps > current),
+        };
+    }
+
+    // Apply an `action=key` rebind like `fire=Enter`
+    fn bind(&mut self, binding: &str) {
+        let Some((action_name, key_name)) = binding.split_once('=') else {
+            eprintln!("Ignoring binding {}, expected action=key", binding);
+            return;
+        };
+        let Some(action) = GameAction::from_name(action_name) else {
+            eprintln!("Ignoring binding for unknown action: {}", action_name);
+            return;
+        };
+        let Some(key) = bindings::parse_key(key_name) else {
+            eprintln!("Ignoring binding to unknown key: {}", key_name);
+            return;
+        };
+        match self.bindings.rebind(action, key) {
+            Ok(Some(ui_action)) => eprintln!(
+                "Note: {} is also the {:?} key in menus",
+                key_name, ui_action
+            ),
+            Ok(None) => {}
+            Err(e) => eprintln!("Ignoring binding for {}: {}", action_name, e),
+        }
+    }
+}
+
+// Only the accessibility, frame pacing, hint, lane and asteroid behavior options
+// are saved, everything else comes from arguments
+impl Persisted for Settings {
+    const FILE_NAME: &'static str = "settings.txt";
+    const MIGRATIONS: &'static [fn(&mut Document)] = &[spell_out_uncapped, rename_lanes];
+
+    fn from_document(document: &Document) -> Settings {
+        let defaults = Settings::default();
+        Settings {
+            text_scale: document
+                .get("text_scale")
+                .and_then(|s| s.parse::<f32>().ok())
+                .map_or(defaults.text_scale, |s| s.clamp(1.0, Self::MAX_TEXT_SCALE)),
+            high_visibility_ship: document.get_or_default("high_visibility_ship"),
+            heading_line: document.get_or_default("heading_line"),
+            fps_cap: document
+                .get("fps_cap")
+                .and_then(|s| s.parse().ok())
+                .filter(|&fps| fps > 0),
+            vsync: document
+                .get("vsync")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.vsync),
+            hints: document
+                .get("hints")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.hints),
+            lanes: document.get_or_default("asteroid_paths"),
+            asteroid_behavior: match document.get("asteroid_behavior") {
+                Some("wrap") => AsteroidBehavior::Wrap,
+                _ => AsteroidBehavior::Despawn,
+            },
+            ..defaults
+        }
+    }
+
+    fn to_document(&self) -> Document {
+        let mut document = Document::default();
+        document.set("text_scale", self.text_scale);
+        document.set("high_visibility_ship", self.high_visibility_ship);
+        document.set("heading_line", self.heading_line);
+        match self.fps_cap {
+            Some(fps) => document.set("fps_cap", fps),
+            None => document.set("fps_cap", "off"),
+        }
+        document.set("vsync", self.vsync);
+        document.set("hints", self.hints);
+        document.set("asteroid_paths", self.lanes);
+        match self.asteroid_behavior {
+            AsteroidBehavior::Despawn => document.set("asteroid_behavior", "despawn"),
+            AsteroidBehavior::Wrap => document.set("asteroid_behavior", "wrap"),
+        }
+        document
+    }
+}
+
+// Version 2 writes an uncapped frame rate as `off`, as on the command line,
+// where version 1 wrote 0
+fn spell_out_uncapped(document: &mut Document) {
+    if document.get("fps_cap") == Some("0") {
+        document.set("fps_cap", "off");
+    }
+}
+
+// Version 3 named the lanes option after the paths it draws, as the options screen calls it
+fn rename_lanes(document: &mut Document) {
+    if let Some(lanes) = document.remove("lanes") {
+        document.set("asteroid_paths", lanes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn version_1_file_loads_after_both_migrations() {
+        persist::use_test_data_dir("settings-v1");
+        let v1 = "schema_version=1\ntext_scale=1.5\nheading_line=true\nfps_cap=0\nlanes=true\n";
+        fs::write(Settings::path(), v1).unwrap();
+
+        let settings: Settings = persist::load();
+        assert_eq!(settings.text_scale, 1.5);
+        assert!(settings.heading_line);
+        assert_eq!(settings.fps_cap, None);
+        assert!(settings.lanes);
+    }
+
+    #[test]
+    fn migrations_bring_a_version_1_document_up_to_date() {
+        let mut document = Document::default();
+        document.set("fps_cap", "0");
+        document.set("lanes", "true");
+        for migrate in Settings::MIGRATIONS {
+            migrate(&mut document);
+        }
+        assert_eq!(Settings::version(), 3);
+        assert_eq!(document.get("fps_cap"), Some("off"));
+        assert_eq!(document.get("lanes"), None);
+        assert_eq!(document.get("asteroid_paths"), Some("true"));
+    }
+
+    #[test]
+    fn saved_settings_round_trip() {
+        let settings = Settings {
+            text_scale: 1.25,
+            fps_cap: Some(60),
+            lanes: true,
+            asteroid_behavior: AsteroidBehavior::Wrap,
+            ..Settings::default()
+        };
+        let loaded = Settings::from_document(&settings.to_document());
+        assert_eq!(loaded.text_scale, 1.25);
+        assert_eq!(loaded.fps_cap, Some(60));
+        assert!(loaded.lanes);
+        assert!(loaded.asteroid_behavior == AsteroidBehavior::Wrap);
+    }
+}
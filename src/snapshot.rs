@@ -0,0 +1,91 @@
+// Debug tool behind `--ui-snapshot`: resizes the window to each resolution in
+// turn, sets up every screen worth checking and saves a PNG of each to
+// target/ui-snapshots, for eyeballing layout breakage before a release.
+// The window has to actually reach a size, so resolutions bigger than the
+// display come out at whatever size the window manager allowed and are named
+// after that.
+use crate::history::HistoryBrowser;
+use crate::photo::{save_png, screen_image};
+use crate::records::RecordsBrowser;
+use crate::{App, GameMode, Input, Screen};
+use macroquad::prelude::*;
+use std::path::Path;
+
+const RESOLUTIONS: [(f32, f32); 4] = [
+    (1280.0, 720.0),
+    (1920.0, 1080.0),
+    (2560.0, 1080.0),
+    (3840.0, 2160.0),
+];
+// Frames to wait for a resize to land before giving up on the exact size
+const SETTLE_FRAMES: u32 = 30;
+// Fixed so every snapshot shows the same field
+const SEED: u64 = 1;
+
+pub async fn run(mut app: App) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/ui-snapshots");
+    let mut saved = 0;
+    for (width, height) in RESOLUTIONS {
+        request_new_screen_size(width, height);
+        for _ in 0..SETTLE_FRAMES {
+            next_frame().await;
+            if screen_width() == width && screen_height() == height {
+                break;
+            }
+        }
+        let size = format!("{}x{}", screen_width(), screen_height());
+        if screen_width() != width || screen_height() != height {
+            eprintln!(
+                "Asked for a {}x{} window and got {}, the display may be too small",
+                width, height, size
+            );
+        }
+        app.handle_window_size(screen_width(), screen_height());
+
+        for (name, setup) in SCREENS {
+            setup(&mut app);
+            app.toasts.clear();
+            app.transition = None;
+            clear_background(BLACK);
+            app.render();
+            save_png(screen_image(), &dir.join(format!("{}-{}.png", size, name)));
+            saved += 1;
+            next_frame().await;
+        }
+    }
+    println!("Saved {} snapshots to {}", saved, dir.display());
+}
+
+type Setup = fn(&mut App);
+
+const SCREENS: [(&str, Setup); 6] = [
+    ("title", |app| app.screen = Screen::Title),
+    ("hud", mid_game),
+    // Fully slid in
+    ("pause", |app| {
+        mid_game(app);
+        app.screen = Screen::Paused(1.0);
+    }),
+    ("history", |app| {
+        app.screen = Screen::History(HistoryBrowser::new())
+    }),
+    ("records", |app| {
+        app.screen = Screen::Records(RecordsBrowser::new(GameMode::Classic))
+    }),
+    ("game-over", |app| {
+        mid_game(app);
+        app.game.player.health = 0;
+        app.screen = Screen::GameOver(10.0);
+    }),
+];
+
+// A few seconds into a Classic run with some score and a hit taken, so the
+// HUD has something in every slot
+fn mid_game(app: &mut App) {
+    app.start_run(GameMode::Classic, SEED);
+    for _ in 0..180 {
+        app.game.tick(1.0 / 60.0, &Input::default());
+    }
+    app.game.score = 1240.0;
+    app.game.player.health = app.game.player.max_health - 1;
+}
@@ -0,0 +1,151 @@
+use crate::rng::Rng;
+use macroquad::prelude::*;
+
+// One depth of stars, all in a single mesh so it's drawn with one call
+struct StarLayer {
+    mesh: Mesh,
+    // How far the layer moves relative to the ship, nearer layers move more
+    parallax: f32,
+    brightness: f32,
+    // Per star, offset into `TWINKLE` so they don't pulse together
+    phases: Vec<u8>,
+}
+impl Clone for StarLayer {
+    fn clone(&self) -> StarLayer {
+        StarLayer {
+            mesh: Mesh {
+                vertices: self.mesh.vertices.clone(),
+                indices: self.mesh.indices.clone(),
+                texture: None,
+            },
+            parallax: self.parallax,
+            brightness: self.brightness,
+            phases: self.phases.clone(),
+        }
+    }
+}
+
+// Background stars in up to three parallax layers. The meshes are built once
+// per size and seed; twinkling only rewrites the colors of a few stars a frame.
+#[derive(Clone, Default)]
+pub struct Starfield {
+    layers: Vec<StarLayer>,
+    size: Vec2,
+    // Seconds of twinkle animation so far
+    time: f32,
+    // Next star to get its color refreshed, counted across all layers
+    next_star: usize,
+}
+impl Starfield {
+    // (parallax, size in pixels, brightness) from far to near
+    const LAYERS: [(f32, f32, f32); 3] = [(0.02, 1.0, 0.35), (0.05, 1.5, 0.55), (0.1, 2.0, 0.8)];
+    const TWINKLE: [f32; 8] = [1.0, 0.9, 0.7, 0.5, 0.4, 0.5, 0.7, 0.9];
+    const TWINKLE_RATE: f32 = 3.0;
+    const STARS_PER_FRAME: usize = 24;
+    // The mesh repeats the field 2x2 so any parallax offset still covers the
+    // screen, and macroquad clamps a draw to 5000 indices
+    const TILES: usize = 4;
+    const MAX_STARS_PER_LAYER: usize = 4999 / (6 * Self::TILES);
+
+    // `density` is stars per megapixel, split evenly between the layers
+    pub fn new(width: f32, height: f32, density: f32, seed: u64) -> Starfield {
+        let mut rng = Rng::new(seed);
+        let count =
+            (density * width * height / 1_000_000.0 / Self::LAYERS.len() as f32).round() as usize;
+        let count = count.min(Self::MAX_STARS_PER_LAYER);
+        let layers = Self::LAYERS
+            .iter()
+            .map(|&(parallax, size, brightness)| {
+                let mut vertices = Vec::with_capacity(count * Self::TILES * 4);
+                let mut indices = Vec::with_capacity(count * Self::TILES * 6);
+                let mut phases = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let x = rng.gen_range(0.0, width);
+                    let y = rng.gen_range(0.0, height);
+                    phases.push((rng.next_u32() % Self::TWINKLE.len() as u32) as u8);
+                    let color = Color::new(1.0, 1.0, 1.0, brightness);
+                    for (tile_x, tile_y) in
+                        [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+                    {
+                        let first = vertices.len() as u16;
+                        let (x, y) = (x + tile_x, y + tile_y);
+                        vertices.push(Vertex::new(x, y, 0.0, 0.0, 0.0, color));
+                        vertices.push(Vertex::new(x + size, y, 0.0, 0.0, 0.0, color));
+                        vertices.push(Vertex::new(x + size, y + size, 0.0, 0.0, 0.0, color));
+                        vertices.push(Vertex::new(x, y + size, 0.0, 0.0, 0.0, color));
+                        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+                    }
+                }
+                StarLayer {
+                    mesh: Mesh {
+                        vertices,
+                        indices,
+                        texture: None,
+                    },
+                    parallax,
+                    brightness,
+                    phases,
+                }
+            })
+            .collect();
+        Starfield {
+            layers,
+            size: Vec2::new(width, height),
+            time: 0.0,
+            next_star: 0,
+        }
+    }
+
+    // Advance the twinkle, recoloring the next few stars in turn
+    pub fn tick(&mut self, frame_time: f32) {
+        self.time += frame_time;
+        let total: usize = self.layers.iter().map(|l| l.phases.len()).sum();
+        if total == 0 {
+            return;
+        }
+        let step = (self.time * Self::TWINKLE_RATE) as usize;
+        for _ in 0..Self::STARS_PER_FRAME.min(total) {
+            let mut index = self.next_star;
+            self.next_star = (self.next_star + 1) % total;
+            let Some(layer) = self.layers.iter_mut().find(|l| {
+                let found = index < l.phases.len();
+                if !found {
+                    index -= l.phases.len();
+                }
+                found
+            }) else {
+                continue;
+            };
+            let phase = layer.phases[index] as usize;
+            let alpha = layer.brightness * Self::TWINKLE[(phase + step) % Self::TWINKLE.len()];
+            let alpha = (alpha * 255.0) as u8;
+            let first = index * Self::TILES * 4;
+            for vertex in &mut layer.mesh.vertices[first..first + Self::TILES * 4] {
+                vertex.color[3] = alpha;
+            }
+        }
+    }
+
+    // Draw every layer shifted against `focus`, returning the number of draw calls
+    pub fn render(&self, focus: Vec2) -> u32 {
+        let center = self.size / 2.0;
+        let mut draw_calls = 0;
+        for layer in &self.layers {
+            if layer.phases.is_empty() {
+                continue;
+            }
+            let shift = (focus - center) * layer.parallax;
+            // Wrapped into the first tile so the 2x2 mesh always covers the screen
+            let offset = Vec2::new(
+                -shift.x.rem_euclid(self.size.x),
+                -shift.y.rem_euclid(self.size.y),
+            );
+            let translation = Mat4::from_translation(offset.extend(0.0));
+            unsafe { get_internal_gl().quad_gl.push_model_matrix(translation) };
+            draw_mesh(&layer.mesh);
+            unsafe { get_internal_gl().quad_gl.pop_model_matrix() };
+            draw_calls += 1;
+        }
+        draw_calls
+    }
+}
@@ -0,0 +1,169 @@
+// Text field that works without a keyboard: a strip of the allowed characters
+// plus delete and done, moved along with Left and Right (held to repeat) and
+// picked with Confirm. Typing goes straight into the same field.
+use crate::bindings::UiAction;
+use crate::keys;
+use crate::{draw_text_h_centered, draw_text_h_centered_color, ui};
+use macroquad::prelude::*;
+
+pub const DIGITS: &str = "0123456789";
+
+pub enum EntryResult {
+    Done(String),
+    // The field is back to what it was when it was opened
+    Cancelled,
+}
+
+pub struct TextEntry {
+    pub text: String,
+    previous: String,
+    allowed: &'static str,
+    max_len: usize,
+    // Index into the strip: the allowed characters, then delete, then done
+    cursor: usize,
+    // Direction held and seconds until it moves again
+    held: Option<(isize, f32)>,
+}
+impl TextEntry {
+    const REPEAT_DELAY: f32 = 0.4;
+    const REPEAT_INTERVAL: f32 = 0.08;
+    // Characters shown either side of the cursor
+    const STRIP_RADIUS: isize = 6;
+
+    pub fn new(text: &str, allowed: &'static str, max_len: usize) -> TextEntry {
+        // Forget whatever was typed before the field came up
+        keys::clear_queue();
+        TextEntry {
+            text: String::from(text),
+            previous: String::from(text),
+            allowed,
+            max_len,
+            cursor: 0,
+            held: None,
+        }
+    }
+
+    fn strip_len(&self) -> usize {
+        self.allowed.chars().count() + 2
+    }
+
+    fn delete_slot(&self) -> usize {
+        self.strip_len() - 2
+    }
+
+    fn done_slot(&self) -> usize {
+        self.strip_len() - 1
+    }
+
+    fn push(&mut self, c: char) {
+        if self.text.chars().count() < self.max_len {
+            self.text.push(c);
+        }
+    }
+
+    pub fn update(&mut self, frame_time: f32) -> Option<EntryResult> {
+        // The queue pops the most recent character first
+        let mut typed = vec![];
+        while let Some(c) = keys::next_char() {
+            typed.push(c);
+        }
+        for c in typed.into_iter().rev() {
+            let c = if self.allowed.contains(c.to_ascii_uppercase()) {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            };
+            if self.allowed.contains(c) {
+                self.push(c);
+                // So Enter finishes after typing instead of adding a character
+                self.cursor = self.done_slot();
+            }
+        }
+        if keys::is_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+
+        if UiAction::Back.is_pressed() {
+            self.text = self.previous.clone();
+            return Some(EntryResult::Cancelled);
+        }
+        if UiAction::Confirm.is_pressed() {
+            if self.cursor == self.done_slot() {
+                return Some(EntryResult::Done(self.text.clone()));
+            } else if self.cursor == self.delete_slot() {
+                self.text.pop();
+            } else if let Some(c) = self.allowed.chars().nth(self.cursor) {
+                self.push(c);
+            }
+        }
+        self.update_cursor(frame_time);
+        None
+    }
+
+    // Moves once on press, then repeats after a delay while held
+    fn update_cursor(&mut self, frame_time: f32) {
+        let direction = if keys::is_down(UiAction::NavLeft.key()) {
+            -1
+        } else if keys::is_down(UiAction::NavRight.key()) {
+            1
+        } else {
+            0
+        };
+        if direction == 0 {
+            self.held = None;
+            return;
+        }
+        match self.held {
+            Some((held, remaining)) if held == direction => {
+                let remaining = remaining - frame_time;
+                if remaining > 0.0 {
+                    self.held = Some((held, remaining));
+                    return;
+                }
+                self.held = Some((held, Self::REPEAT_INTERVAL));
+            }
+            _ => self.held = Some((direction, Self::REPEAT_DELAY)),
+        }
+        let len = self.strip_len() as isize;
+        self.cursor = (self.cursor as isize + direction).rem_euclid(len) as usize;
+    }
+
+    // Label, the text so far and the strip centered on the cursor, from `y` down
+    pub fn render(&self, label: &str, y: f32) {
+        draw_text_h_centered(label, y, 36);
+        let caret = if (get_time() * 2.0) as i64 % 2 == 0 {
+            "_"
+        } else {
+            " "
+        };
+        draw_text_h_centered_color(&format!("{}{}", self.text, caret), y + ui(60.0), 40, YELLOW);
+
+        let slot_width = ui(36.0);
+        let strip_y = y + ui(130.0);
+        let len = self.strip_len() as isize;
+        // Short strips would wrap around into view twice
+        let radius = Self::STRIP_RADIUS.min((len - 1) / 2);
+        for offset in -radius..=radius {
+            let slot = (self.cursor as isize + offset).rem_euclid(len) as usize;
+            let label = if slot == self.done_slot() {
+                String::from("OK")
+            } else if slot == self.delete_slot() {
+                String::from("<-")
+            } else {
+                self.allowed.chars().nth(slot).unwrap_or(' ').to_string()
+            };
+            let size = ui(28.0);
+            let dimensions = measure_text(&label, None, size as u16, 1.0);
+            let x = screen_width() / 2.0 + offset as f32 * slot_width - dimensions.width / 2.0;
+            let fade = 1.0 - offset.abs() as f32 / (radius + 1) as f32;
+            let color = if offset == 0 {
+                YELLOW
+            } else {
+                Color { a: fade, ..GRAY }
+            };
+            draw_text(&label, x, strip_y, size, color);
+        }
+        let x = screen_width() / 2.0 - slot_width / 2.0;
+        draw_rectangle_lines(x, strip_y - ui(26.0), slot_width, ui(34.0), 1.0, YELLOW);
+    }
+}
@@ -0,0 +1,179 @@
+// Timers counted in seconds and run down by each tick's frame time, so they
+// behave the same at any frame rate and all expire the same way.
+
+// Gate on something happening again, like firing: ready once it has run out
+#[derive(Clone, Copy, Default)]
+pub struct Cooldown {
+    remaining: f32,
+}
+impl Cooldown {
+    pub fn start(&mut self, seconds: f32) {
+        self.remaining = seconds;
+    }
+
+    pub fn tick(&mut self, frame_time: f32) {
+        self.remaining = (self.remaining - frame_time).max(0.0);
+    }
+
+    pub fn ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+// Something that lasts a while, like a power-up. `just_expired` is true for
+// the one tick it ran out in, for anything that has to happen once at the end.
+#[derive(Clone, Copy, Default)]
+pub struct Countdown {
+    remaining: f32,
+    just_expired: bool,
+}
+impl Countdown {
+    // Already running for `seconds`
+    pub fn new(seconds: f32) -> Countdown {
+        let mut countdown = Countdown::default();
+        countdown.start(seconds);
+        countdown
+    }
+
+    pub fn start(&mut self, seconds: f32) {
+        self.remaining = seconds.max(0.0);
+        self.just_expired = false;
+    }
+
+    pub fn tick(&mut self, frame_time: f32) {
+        self.absorb(frame_time);
+    }
+
+    // Like `tick`, returning whatever part of `frame_time` was left over once
+    // it ran out, for time the countdown swallows like hit-stop
+    pub fn absorb(&mut self, frame_time: f32) -> f32 {
+        self.just_expired = false;
+        if self.remaining <= 0.0 {
+            return frame_time;
+        }
+        let used = self.remaining.min(frame_time);
+        self.remaining -= used;
+        if self.remaining <= 0.0 {
+            self.remaining = 0.0;
+            self.just_expired = true;
+        }
+        frame_time - used
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    pub fn just_expired(&self) -> bool {
+        self.just_expired
+    }
+
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+}
+
+// Fires every `interval` seconds. The interval is passed in on each tick so a
+// changed balance value takes effect straight away.
+#[derive(Clone, Copy, Default)]
+pub struct RepeatingTimer {
+    elapsed: f32,
+}
+impl RepeatingTimer {
+    // Times it fired during `frame_time`, more than once for a long frame
+    pub fn tick(&mut self, frame_time: f32, interval: f32) -> u32 {
+        if interval <= 0.0 {
+            return 0;
+        }
+        self.elapsed += frame_time;
+        let fired = (self.elapsed / interval) as u32;
+        self.elapsed -= fired as f32 * interval;
+        fired
+    }
+
+    // Start the wait for the next firing over
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    // Share of the way to the next firing, from 0 to 1
+    pub fn progress(&self, interval: f32) -> f32 {
+        if interval <= 0.0 {
+            return 0.0;
+        }
+        (self.elapsed / interval).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldown_waits_out_zero_and_huge_frames() {
+        let mut cooldown = Cooldown::default();
+        assert!(cooldown.ready());
+        cooldown.start(0.5);
+        cooldown.tick(0.0);
+        assert!(!cooldown.ready());
+        cooldown.tick(1.0e6);
+        assert!(cooldown.ready());
+        assert_eq!(cooldown.remaining, 0.0);
+    }
+
+    #[test]
+    fn countdown_zero_frame_changes_nothing() {
+        let mut countdown = Countdown::new(1.0);
+        assert_eq!(countdown.absorb(0.0), 0.0);
+        assert!(countdown.is_running());
+        assert!(!countdown.just_expired());
+        assert_eq!(countdown.remaining(), 1.0);
+
+        let mut idle = Countdown::default();
+        assert_eq!(idle.absorb(0.0), 0.0);
+        assert!(!idle.just_expired());
+        assert!(!Countdown::new(0.0).is_running());
+    }
+
+    #[test]
+    fn countdown_hands_back_what_a_huge_frame_overran() {
+        let mut countdown = Countdown::new(0.5);
+        assert_eq!(countdown.absorb(1000.0), 999.5);
+        assert!(countdown.just_expired());
+        assert!(!countdown.is_running());
+        assert_eq!(countdown.remaining(), 0.0);
+        // Only for the one tick, after that the whole frame passes through
+        assert_eq!(countdown.absorb(1000.0), 1000.0);
+        assert!(!countdown.just_expired());
+    }
+
+    #[test]
+    fn countdown_expires_on_an_exact_frame() {
+        let mut countdown = Countdown::new(0.5);
+        assert_eq!(countdown.absorb(0.5), 0.0);
+        assert!(countdown.just_expired());
+        countdown.start(0.5);
+        assert!(!countdown.just_expired());
+        assert!(countdown.is_running());
+    }
+
+    #[test]
+    fn repeating_timer_counts_every_firing_in_a_huge_frame() {
+        let mut timer = RepeatingTimer::default();
+        assert_eq!(timer.tick(0.0, 1.0), 0);
+        assert_eq!(timer.progress(1.0), 0.0);
+        assert_eq!(timer.tick(10.5, 1.0), 10);
+        assert_eq!(timer.progress(1.0), 0.5);
+        assert_eq!(timer.tick(0.5, 1.0), 1);
+        assert_eq!(timer.tick(1.0e4, 0.25), 40_000);
+        assert_eq!(timer.progress(0.25), 0.0);
+    }
+
+    #[test]
+    fn repeating_timer_without_an_interval_never_fires() {
+        let mut timer = RepeatingTimer::default();
+        assert_eq!(timer.tick(1.0e6, 0.0), 0);
+        assert_eq!(timer.tick(1.0, -1.0), 0);
+        assert_eq!(timer.progress(0.0), 0.0);
+    }
+}
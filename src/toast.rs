@@ -0,0 +1,46 @@
+use crate::timer::Countdown;
+use crate::{draw_text_h_centered, ui};
+
+// Short-lived centered announcement drawn over the play field
+#[derive(Clone)]
+pub struct Toast {
+    text: String,
+    font_size: u16,
+    life: Countdown,
+    flashing: bool,
+}
+impl Toast {
+    pub fn new(text: &str, font_size: u16, duration: f32) -> Toast {
+        Toast {
+            text: String::from(text),
+            font_size,
+            life: Countdown::new(duration),
+            flashing: false,
+        }
+    }
+
+    pub fn flashing(mut self) -> Toast {
+        self.flashing = true;
+        self
+    }
+
+    pub fn tick(&mut self, frame_time: f32) {
+        self.life.tick(frame_time);
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.life.is_running()
+    }
+
+    pub fn height(&self) -> f32 {
+        ui(self.font_size as f32 * 1.2)
+    }
+
+    pub fn render(&self, y: f32) {
+        // Blink on and off eight times a second
+        if self.flashing && (self.life.remaining() * 8.0) as i32 % 2 == 1 {
+            return;
+        }
+        draw_text_h_centered(&self.text, y, self.font_size);
+    }
+}
@@ -0,0 +1,50 @@
+use macroquad::prelude::*;
+
+// Fade through black from one screen to another. The change is handed back at
+// the midpoint, while the screen is fully covered, so the outgoing screen is
+// drawn under the first half of the fade and the incoming one under the second.
+pub struct Transition<T> {
+    change: Option<T>,
+    elapsed: f32,
+}
+impl<T> Transition<T> {
+    const DURATION: f32 = 0.3;
+
+    pub fn new(change: T) -> Transition<T> {
+        Transition {
+            change: Some(change),
+            elapsed: 0.0,
+        }
+    }
+
+    // Returns the pending change once the midpoint has been reached
+    pub fn tick(&mut self, frame_time: f32) -> Option<T> {
+        self.elapsed += frame_time;
+        if self.elapsed >= Self::DURATION / 2.0 {
+            return self.change.take();
+        }
+        None
+    }
+
+    // Jump straight to the end of the fade
+    pub fn skip(&mut self) -> Option<T> {
+        self.elapsed = Self::DURATION;
+        self.change.take()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= Self::DURATION
+    }
+
+    pub fn render(&self) {
+        let half = Self::DURATION / 2.0;
+        let alpha = 1.0 - (self.elapsed / half - 1.0).abs();
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::new(0.0, 0.0, 0.0, alpha.clamp(0.0, 1.0)),
+        );
+    }
+}
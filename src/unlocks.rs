@@ -0,0 +1,76 @@
+use crate::profile::Profile;
+use macroquad::prelude::*;
+
+// Content that becomes available once the lifetime profile meets a requirement
+pub struct Unlockable {
+    pub id: &'static str,
+    pub name: &'static str,
+    // Shown next to locked entries in selection menus
+    pub requirement: &'static str,
+    pub is_met: fn(&Profile) -> bool,
+}
+
+pub const UNLOCKABLES: [Unlockable; 4] = [
+    Unlockable {
+        id: "palette_amber",
+        name: "Amber palette",
+        requirement: "Destroy 250 asteroids",
+        is_met: |p| p.asteroids_destroyed >= 250,
+    },
+    Unlockable {
+        id: "palette_phosphor",
+        name: "Phosphor palette",
+        requirement: "Win a run",
+        is_met: |p| p.runs_won >= 1,
+    },
+    Unlockable {
+        id: "palette_ice",
+        name: "Ice palette",
+        requirement: "Destroy 1000 asteroids",
+        is_met: |p| p.asteroids_destroyed >= 1000,
+    },
+    Unlockable {
+        id: "strafe_thrusters",
+        name: "Strafe thrusters",
+        requirement: "Play 10 runs",
+        is_met: |p| p.runs_played >= 10,
+    },
+];
+
+// Color used to draw the ship, asteroids and lasers
+pub struct Palette {
+    pub name: &'static str,
+    pub foreground: Color,
+    // Id of the unlockable that grants it, if it isn't available from the start
+    pub unlock: Option<&'static str>,
+}
+
+pub const PALETTES: [Palette; 4] = [
+    Palette {
+        name: "Classic",
+        foreground: WHITE,
+        unlock: None,
+    },
+    Palette {
+        name: "Amber",
+        foreground: Color::new(1.0, 0.69, 0.0, 1.0),
+        unlock: Some("palette_amber"),
+    },
+    Palette {
+        name: "Phosphor",
+        foreground: Color::new(0.2, 1.0, 0.4, 1.0),
+        unlock: Some("palette_phosphor"),
+    },
+    Palette {
+        name: "Ice",
+        foreground: Color::new(0.6, 0.85, 1.0, 1.0),
+        unlock: Some("palette_ice"),
+    },
+];
+
+pub fn requirement_for(id: &str) -> &'static str {
+    UNLOCKABLES
+        .iter()
+        .find(|u| u.id == id)
+        .map_or("", |u| u.requirement)
+}
@@ -0,0 +1,162 @@
+use crate::{rng::Rng, Game};
+use macroquad::prelude::*;
+
+// Rule changes a weekly challenge can combine
+#[derive(Clone, Copy, PartialEq)]
+pub enum Modifier {
+    FastAsteroids,
+    NoRepairs,
+    Fragile,
+    Crowded,
+    NoFocus,
+}
+impl Modifier {
+    const ALL: [Modifier; 5] = [
+        Modifier::FastAsteroids,
+        Modifier::NoRepairs,
+        Modifier::Fragile,
+        Modifier::Crowded,
+        Modifier::NoFocus,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::FastAsteroids => "Double asteroid speed",
+            Modifier::NoRepairs => "No repairs",
+            Modifier::Fragile => "2 health",
+            Modifier::Crowded => "50% more asteroids",
+            Modifier::NoFocus => "No focus",
+        }
+    }
+
+    // Short label for the HUD badge
+    fn badge(self) -> &'static str {
+        match self {
+            Modifier::FastAsteroids => "2x SPD",
+            Modifier::NoRepairs => "NO REP",
+            Modifier::Fragile => "2 HP",
+            Modifier::Crowded => "+50%",
+            Modifier::NoFocus => "NO FOC",
+        }
+    }
+
+    // Called by `Game::reset` after everything is back to defaults
+    pub fn apply(self, game: &mut Game) {
+        match self {
+            Modifier::FastAsteroids => game.balance.asteroid_speed *= 2.0,
+            Modifier::NoRepairs => {
+                game.balance.repair_drop_chance = 0.0;
+                game.balance.field_repair_interval = f32::INFINITY;
+            }
+            Modifier::Fragile => {
+                game.player.max_health = 2;
+                game.player.health = 2;
+            }
+            Modifier::Crowded => game.balance.asteroid_density *= 1.5,
+            Modifier::NoFocus => game.balance.focus_per_kill = 0.0,
+        }
+    }
+}
+
+// This week's seed and rules. Everyone derives the same ones from the date, so
+// there's no server involved.
+#[derive(Clone)]
+pub struct Challenge {
+    // ISO week like "2026-W42", also the key for best scores
+    pub tag: String,
+    pub seed: u64,
+    pub modifiers: Vec<Modifier>,
+}
+impl Challenge {
+    pub fn current() -> Challenge {
+        let days = (miniquad::date::now() / 86400.0).floor() as i64;
+        let (year, week) = iso_week(days);
+        Challenge::for_week(year, week)
+    }
+
+    fn for_week(year: i64, week: u32) -> Challenge {
+        let mut rng = Rng::new((year * 100 + week as i64) as u64);
+        let seed = (rng.next_u32() as u64) << 32 | rng.next_u32() as u64;
+
+        let count = 2 + rng.next_u32() as usize % 2;
+        let mut remaining = Modifier::ALL.to_vec();
+        let mut modifiers = vec![];
+        for _ in 0..count {
+            let index = rng.next_u32() as usize % remaining.len();
+            modifiers.push(remaining.remove(index));
+        }
+
+        Challenge {
+            tag: format!("{}-W{:02}", year, week),
+            seed,
+            modifiers,
+        }
+    }
+
+    pub fn rules(&self) -> String {
+        let names: Vec<&str> = self.modifiers.iter().map(|m| m.name()).collect();
+        names.join(", ")
+    }
+
+    // Badges for the active rules, in a row from the bottom left corner
+    pub fn render_badges(&self, bottom: f32) {
+        let mut x = 10.0;
+        for modifier in &self.modifiers {
+            let label = modifier.badge();
+            let (font_size, height) = (crate::ui(16.0), crate::ui(20.0));
+            let width = measure_text(label, None, font_size as u16, 1.0).width + 8.0;
+            draw_rectangle_lines(x, bottom - height, width, height, 1.0, ORANGE);
+            draw_text(label, x + 4.0, bottom - height * 0.3, font_size, ORANGE);
+            x += width + 6.0;
+        }
+    }
+}
+
+// ISO 8601 year and week number of a day counted from 1970-01-01
+fn iso_week(days: i64) -> (i64, u32) {
+    // 1970-01-01 was a Thursday, weekdays here run from Monday = 0
+    let weekday = (days + 3).rem_euclid(7);
+    // A week belongs to the year its Thursday is in
+    let thursday = days - weekday + 3;
+    let year = civil_year(thursday);
+    let week = (thursday - days_to_new_year(year)) / 7 + 1;
+    (year, week as u32)
+}
+
+// Days from 1970-01-01 to January 1st of `year`, proleptic Gregorian
+fn days_to_new_year(year: i64) -> i64 {
+    // Counted from March so leap days fall at the end of the year
+    let y = year - 1;
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + 306;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_year(days: i64) -> i64 {
+    civil_date(days).0
+}
+
+// Year, month and day of a day counted from 1970-01-01
+pub fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400;
+    // January and February belong to the next civil year
+    if month <= 2 {
+        (year + 1, month as u32, day as u32)
+    } else {
+        (year, month as u32, day as u32)
+    }
+}